@@ -0,0 +1,197 @@
+use crate::config::{Config, Theme};
+use ratatui::{
+    Frame,
+    layout::{Constraint, Layout, Rect},
+    style::{Color, Style, Stylize},
+    symbols::Marker,
+    text::Line,
+    widgets::{Axis, Block, Cell, Chart, Dataset, GraphType, Padding, Paragraph, Row, Table},
+};
+use serde::Deserialize;
+use ureq::Agent;
+use urlencoding::encode;
+
+/// The air quality API forecasts at most this many days
+const MAX_DAYS: u8 = 7;
+
+/// Current concentrations in µg/m³, and the indices derived from them
+#[derive(Deserialize, Debug, Clone, Default)]
+pub struct Current {
+    pub time: String,
+    pub european_aqi: Option<f32>,
+    pub us_aqi: Option<f32>,
+    pub pm2_5: Option<f32>,
+    pub pm10: Option<f32>,
+    pub ozone: Option<f32>,
+    pub nitrogen_dioxide: Option<f32>,
+    pub sulphur_dioxide: Option<f32>,
+    pub carbon_monoxide: Option<f32>,
+}
+
+#[derive(Deserialize, Debug, Clone, Default)]
+pub struct Hourly {
+    /// Local times (YYYY-MM-DDTHH:MM)
+    pub time: Vec<String>,
+    pub european_aqi: Vec<Option<f32>>,
+}
+
+/// Air quality at the location, now and for the coming days
+#[derive(Deserialize, Debug, Clone, Default)]
+pub struct AirQuality {
+    pub current: Current,
+    pub hourly: Hourly,
+}
+
+/// Fetches the air quality from Open-Meteo's CAMS-based API, whichever provider the forecast
+/// comes from
+/// Using this API: <https://air-quality-api.open-meteo.com/v1/air-quality>
+pub fn fetch(agent: &Agent, config: &Config) -> Result<AirQuality, ureq::Error> {
+    let location = config.location();
+    let url = format!(
+        "{}?latitude={}&longitude={}&current=european_aqi,us_aqi,pm2_5,pm10,ozone,nitrogen_dioxide,sulphur_dioxide,carbon_monoxide&hourly=european_aqi&timezone={}&forecast_days={}",
+        config.endpoints.air_quality,
+        location.latitude,
+        location.longitude,
+        encode(&location.timezone),
+        config.forecast_days.min(MAX_DAYS),
+    );
+    agent.get(url).call()?.body_mut().read_json::<AirQuality>()
+}
+
+/// Band of the European Air Quality Index and its colour
+fn category(index: f32) -> (&'static str, Color) {
+    match index {
+        i if i <= 20.0 => ("Good", Color::Green),
+        i if i <= 40.0 => ("Fair", Color::LightGreen),
+        i if i <= 60.0 => ("Moderate", Color::Yellow),
+        i if i <= 80.0 => ("Poor", Color::LightRed),
+        i if i <= 100.0 => ("Very poor", Color::Red),
+        _ => ("Extremely poor", Color::Magenta),
+    }
+}
+
+fn value(value: Option<f32>) -> String {
+    value.map_or(String::from("\u{2013}"), |value| format!("{:.0}", value))
+}
+
+/// Current indices and concentrations, for the Air tab and the Overview's sidebar
+pub fn create_current_table<'a>(current: &Current) -> Table<'a> {
+    let pollutant = |name: &'a str, concentration: Option<f32>| {
+        Row::new(vec![
+            Cell::from(name),
+            Cell::from(format!("{} \u{b5}g/m\u{b3}", value(concentration))),
+        ])
+    };
+    let (label, color) = current.european_aqi.map_or(("", Color::Reset), category);
+    let rows = vec![
+        Row::new(vec![
+            Cell::from("European AQI:").bold(),
+            Cell::from(format!("{} {}", value(current.european_aqi), label)).fg(color),
+        ]),
+        Row::new(vec![
+            Cell::from("US AQI:").bold(),
+            Cell::from(value(current.us_aqi)),
+        ]),
+        Row::new([""]),
+        pollutant("PM2.5", current.pm2_5),
+        pollutant("PM10", current.pm10),
+        pollutant("Ozone", current.ozone),
+        pollutant("Nitrogen dioxide", current.nitrogen_dioxide),
+        pollutant("Sulphur dioxide", current.sulphur_dioxide),
+        pollutant("Carbon monoxide", current.carbon_monoxide),
+    ];
+    Table::new(rows, [Constraint::Length(18), Constraint::Fill(1)])
+}
+
+/// The European AQI hour by hour, with the days on the x axis
+fn render_forecast_chart(frame: &mut Frame, area: Rect, hourly: &Hourly, config: &Config) {
+    let points: Vec<(f64, f64)> = hourly
+        .european_aqi
+        .iter()
+        .enumerate()
+        .filter_map(|(i, index)| Some((i as f64, f64::from((*index)?))))
+        .collect();
+    let high = points
+        .iter()
+        .map(|(_, index)| *index)
+        .fold(100.0, f64::max)
+        .ceil();
+    let last = hourly.time.len().saturating_sub(1);
+    let date = |i: usize| {
+        hourly
+            .time
+            .get(i)
+            .and_then(|time| time.get(5..10))
+            .unwrap_or_default()
+            .to_string()
+    };
+
+    let chart = Chart::new(vec![
+        Dataset::default()
+            .marker(Marker::Braille)
+            .graph_type(GraphType::Line)
+            .style(Style::new().fg(Theme::color(&config.theme.data)))
+            .data(&points),
+    ])
+    .block(Block::bordered().title(Line::from(" European AQI ").centered().bold()))
+    .y_axis(
+        Axis::default()
+            .bounds([0.0, high])
+            .style(Style::default().fg(Color::Gray))
+            .labels([
+                String::from("0"),
+                format!("{:.0}", high / 2.0),
+                format!("{:.0}", high),
+            ]),
+    )
+    .x_axis(
+        Axis::default()
+            .bounds([0.0, last.max(1) as f64])
+            .style(Style::default().fg(Color::Gray))
+            .labels([date(0), date(last / 2), date(last)]),
+    );
+    frame.render_widget(chart, area);
+}
+
+/// Renders the Air tab, or why there's nothing to show
+pub fn render(
+    frame: &mut Frame,
+    area: Rect,
+    air: Option<&AirQuality>,
+    error: Option<&str>,
+    config: &Config,
+) {
+    let block = Block::bordered()
+        .title(
+            Line::from(" Air Quality ")
+                .fg(Theme::color(&config.theme.forecast))
+                .centered()
+                .bold(),
+        )
+        .padding(Padding::horizontal(1));
+    let inner = block.inner(area);
+    frame.render_widget(block, area);
+
+    let Some(air) = air else {
+        let message = error.map_or(String::from("No air quality data yet"), |err| {
+            format!("Air quality unavailable: {}", err)
+        });
+        frame.render_widget(Paragraph::new(message).centered().dim(), inner);
+        return;
+    };
+
+    let [left, right] =
+        Layout::horizontal([Constraint::Ratio(2, 5), Constraint::Ratio(3, 5)]).areas(inner);
+    let [current_area, note_area] =
+        Layout::vertical([Constraint::Length(10), Constraint::Fill(1)]).areas(left);
+    frame.render_widget(create_current_table(&air.current), current_area);
+
+    let mut note = vec![Line::from(format!("As of {}", air.current.time.replace('T', " "))).dim()];
+    // The last refresh failed, so the numbers above are from an earlier one
+    if let Some(err) = error {
+        note.push(Line::from(format!("Refresh failed: {}", err)).fg(Color::LightRed));
+    }
+    frame.render_widget(Paragraph::new(note), note_area);
+
+    render_forecast_chart(frame, right, &air.hourly, config);
+}
@@ -0,0 +1,302 @@
+use crate::config::{AlertCondition, AlertMetric, AlertRule, Units};
+use crate::forecast::{Forecast, Hour, Period};
+use chrono::{NaiveDateTime, NaiveTime, TimeDelta};
+use serde::Serialize;
+
+/// A stretch of consecutive hours in which one rule matches
+#[derive(Serialize, Debug, Clone, PartialEq)]
+pub struct AlertWindow {
+    pub name: String,
+    pub metric: AlertMetric,
+    pub condition: AlertCondition,
+    /// First matching hour (local YYYY-MM-DDTHH:MM)
+    pub start: String,
+    /// Last matching hour (local YYYY-MM-DDTHH:MM)
+    pub end: String,
+    /// Lowest value for "below" rules, highest for "above" rules
+    pub extreme: f32,
+}
+
+/// Result of evaluating every rule against a forecast
+#[derive(Debug, Default)]
+pub struct Alerts {
+    pub windows: Vec<AlertWindow>,
+    /// One entry per hour of `Forecast::hourly`, true where any rule matches
+    pub hours: Vec<bool>,
+    /// One entry per period of `Forecast::periods`, naming the rules that match that day
+    pub days: Vec<Vec<String>>,
+}
+
+/// An alert window with its unit and description
+#[derive(Serialize)]
+pub struct AlertReport<'a> {
+    #[serde(flatten)]
+    window: &'a AlertWindow,
+    unit: &'static str,
+    message: String,
+}
+
+/// The alerts of a location, published to `mqtt.alerts_topic` and served on `/alerts`
+/// Also sent when no rule matches, so the count drops back to 0
+#[derive(Serialize)]
+pub struct AlertsReport<'a> {
+    location: &'a str,
+    count: usize,
+    alerts: Vec<AlertReport<'a>>,
+}
+
+impl<'a> AlertsReport<'a> {
+    pub fn new(location: &'a str, windows: &'a [AlertWindow], units: &Units) -> AlertsReport<'a> {
+        AlertsReport {
+            location,
+            count: windows.len(),
+            alerts: windows
+                .iter()
+                .map(|window| AlertReport {
+                    window,
+                    unit: window.metric.unit(units),
+                    message: describe_window(window, units),
+                })
+                .collect(),
+        }
+    }
+}
+
+/// None when the provider has no value for the metric, which never matches a rule
+fn hourly_value(hour: &Hour, metric: AlertMetric) -> Option<f32> {
+    match metric {
+        AlertMetric::Temperature => Some(hour.temperature),
+        AlertMetric::ApparentTemperature => Some(hour.apparent_temperature),
+        AlertMetric::RainChance => hour.precipitation_probability.map(|value| value as f32),
+        AlertMetric::WindGusts => hour.wind_gusts,
+    }
+}
+
+/// The daily value a rule is compared with: the day's minimum for "below", its maximum for "above"
+fn daily_value(period: &Period, metric: AlertMetric, condition: AlertCondition) -> Option<f32> {
+    let below = condition == AlertCondition::Below;
+    match metric {
+        AlertMetric::Temperature if below => Some(period.temperature_min),
+        AlertMetric::Temperature => Some(period.temperature_max),
+        AlertMetric::ApparentTemperature if below => Some(period.apparent_temperature_min),
+        AlertMetric::ApparentTemperature => Some(period.apparent_temperature_max),
+        AlertMetric::RainChance => period.precipitation_probability.map(|value| value as f32),
+        AlertMetric::WindGusts => period.wind_gusts_max,
+    }
+}
+
+fn matches(rule: &AlertRule, value: f32) -> bool {
+    match rule.condition {
+        AlertCondition::Below => value < rule.threshold,
+        AlertCondition::Above => value > rule.threshold,
+    }
+}
+
+/// Evaluates the rules against the hourly and daily forecast
+pub fn evaluate(rules: &[AlertRule], forecast: &Forecast) -> Alerts {
+    let mut alerts = Alerts {
+        windows: Vec::new(),
+        hours: vec![false; forecast.hourly.len()],
+        days: vec![Vec::new(); forecast.periods.len()],
+    };
+
+    for rule in rules {
+        let more_extreme = |a: f32, b: f32| match rule.condition {
+            AlertCondition::Below => a.min(b),
+            AlertCondition::Above => a.max(b),
+        };
+
+        let mut open: Option<AlertWindow> = None;
+        for (i, hour) in forecast.hourly.iter().enumerate() {
+            let value = hourly_value(hour, rule.metric);
+            if let Some(value) = value.filter(|value| matches(rule, *value)) {
+                alerts.hours[i] = true;
+                let window = open.get_or_insert_with(|| AlertWindow {
+                    name: rule.name.clone(),
+                    metric: rule.metric,
+                    condition: rule.condition,
+                    start: hour.datetime.clone(),
+                    end: hour.datetime.clone(),
+                    extreme: value,
+                });
+                window.end = hour.datetime.clone();
+                window.extreme = more_extreme(window.extreme, value);
+            } else if let Some(window) = open.take() {
+                alerts.windows.push(window);
+            }
+        }
+        alerts.windows.extend(open);
+
+        for (i, period) in forecast.periods.iter().enumerate() {
+            let value = daily_value(period, rule.metric, rule.condition);
+            if value.is_some_and(|value| matches(rule, value)) {
+                alerts.days[i].push(rule.name.clone());
+            }
+        }
+    }
+
+    alerts.windows.sort_by(|a, b| a.start.cmp(&b.start));
+    alerts
+}
+
+/// One-line description of a window, e.g. "Frost: Thu 05:00–09:00, down to -2.4°C"
+/// The end is shown as the end of the last matching hour
+pub fn describe_window(window: &AlertWindow, units: &Units) -> String {
+    let parse = |time: &str| NaiveDateTime::parse_from_str(time, "%Y-%m-%dT%H:%M").ok();
+    let span = match (parse(&window.start), parse(&window.end)) {
+        (Some(start), Some(end)) => {
+            let end = end + TimeDelta::hours(1);
+            if start.date() == end.date() || end.time() == NaiveTime::MIN {
+                format!(
+                    "{} {}–{}",
+                    start.format("%a"),
+                    start.format("%H:%M"),
+                    end.format("%H:%M")
+                )
+            } else {
+                format!(
+                    "{} {}–{} {}",
+                    start.format("%a"),
+                    start.format("%H:%M"),
+                    end.format("%a"),
+                    end.format("%H:%M")
+                )
+            }
+        }
+        _ => format!("{}–{}", window.start, window.end),
+    };
+
+    let direction = match window.condition {
+        AlertCondition::Below => "down to",
+        AlertCondition::Above => "up to",
+    };
+    format!(
+        "{}: {}, {} {:.1}{}",
+        window.name,
+        span,
+        direction,
+        window.extreme,
+        window.metric.unit(units)
+    )
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::forecast::fixtures;
+
+    fn hour(datetime: &str, temperature: f32, rain: Option<i32>) -> Hour {
+        Hour {
+            precipitation_probability: rain,
+            ..fixtures::hour(datetime, temperature)
+        }
+    }
+
+    fn period(date: &str, temperature_min: f32, rain: Option<i32>) -> Period {
+        Period {
+            precipitation_probability: rain,
+            ..fixtures::period(date, temperature_min, 10.0)
+        }
+    }
+
+    fn rule(metric: AlertMetric, condition: AlertCondition, threshold: f32) -> AlertRule {
+        AlertRule {
+            name: String::from("Rule"),
+            metric,
+            condition,
+            threshold,
+        }
+    }
+
+    #[test]
+    fn consecutive_hours_form_one_window() {
+        let forecast = Forecast {
+            hourly: vec![
+                hour("2026-10-18T04:00", 1.0, None),
+                hour("2026-10-18T05:00", -1.0, None),
+                hour("2026-10-18T06:00", -2.5, None),
+                hour("2026-10-18T07:00", -0.5, None),
+                hour("2026-10-18T08:00", 2.0, None),
+                hour("2026-10-18T09:00", -1.0, None),
+            ],
+            ..Default::default()
+        };
+        let rules = [rule(AlertMetric::Temperature, AlertCondition::Below, 0.0)];
+        let alerts = evaluate(&rules, &forecast);
+
+        assert_eq!(alerts.hours, [false, true, true, true, false, true]);
+        assert_eq!(alerts.windows.len(), 2);
+        assert_eq!(alerts.windows[0].start, "2026-10-18T05:00");
+        assert_eq!(alerts.windows[0].end, "2026-10-18T07:00");
+        assert_eq!(alerts.windows[0].extreme, -2.5);
+        assert_eq!(alerts.windows[1].start, "2026-10-18T09:00");
+        assert_eq!(alerts.windows[1].end, "2026-10-18T09:00");
+    }
+
+    #[test]
+    fn missing_values_never_match() {
+        let forecast = Forecast {
+            hourly: vec![
+                hour("2026-10-18T10:00", 5.0, Some(5)),
+                hour("2026-10-18T11:00", 5.0, None),
+                hour("2026-10-18T12:00", 5.0, Some(8)),
+            ],
+            periods: vec![
+                period("2026-10-18", 5.0, None),
+                period("2026-10-19", 5.0, Some(5)),
+            ],
+            ..Default::default()
+        };
+        let rules = [rule(AlertMetric::RainChance, AlertCondition::Below, 10.0)];
+        let alerts = evaluate(&rules, &forecast);
+
+        assert_eq!(alerts.hours, [true, false, true]);
+        assert_eq!(alerts.windows.len(), 2);
+        assert_eq!(alerts.days, [vec![], vec![String::from("Rule")]]);
+    }
+
+    #[test]
+    fn days_compare_the_matching_extreme() {
+        let forecast = Forecast {
+            periods: vec![
+                period("2026-10-18", -3.0, None),
+                period("2026-10-19", 4.0, None),
+            ],
+            ..Default::default()
+        };
+        let rules = [
+            rule(AlertMetric::Temperature, AlertCondition::Below, 0.0),
+            rule(AlertMetric::Temperature, AlertCondition::Above, 12.0),
+        ];
+        let alerts = evaluate(&rules, &forecast);
+
+        assert_eq!(alerts.days, [vec![String::from("Rule")], vec![]]);
+        assert!(alerts.windows.is_empty());
+    }
+
+    #[test]
+    fn windows_end_with_their_last_hour() {
+        let window = AlertWindow {
+            name: String::from("Frost"),
+            metric: AlertMetric::Temperature,
+            condition: AlertCondition::Below,
+            start: String::from("2026-10-22T05:00"),
+            end: String::from("2026-10-22T08:00"),
+            extreme: -2.4,
+        };
+        assert_eq!(
+            describe_window(&window, &Units::default()),
+            "Frost: Thu 05:00–09:00, down to -2.4°C"
+        );
+
+        let overnight = AlertWindow {
+            start: String::from("2026-10-22T22:00"),
+            end: String::from("2026-10-23T01:00"),
+            ..window
+        };
+        assert_eq!(
+            describe_window(&overnight, &Units::default()),
+            "Frost: Thu 22:00–Fri 02:00, down to -2.4°C"
+        );
+    }
+}
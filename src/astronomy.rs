@@ -0,0 +1,119 @@
+use crate::config::{Config, Theme};
+use crate::forecast::{Forecast, Period};
+use crate::{MoonPhase, get_day_from_date};
+use chrono::{NaiveDateTime, TimeDelta};
+use ratatui::{
+    Frame,
+    layout::{Alignment, Constraint, Layout, Rect},
+    style::Stylize,
+    text::Line,
+    widgets::{Block, Cell, Paragraph, Row, Table},
+};
+
+/// Time between sunrise and sunset, if the provider reports both
+fn daylight(period: &Period) -> Option<TimeDelta> {
+    let parse = |time: &str| NaiveDateTime::parse_from_str(time, "%Y-%m-%dT%H:%M").ok();
+    Some(parse(&period.sunset)? - parse(&period.sunrise)?)
+}
+
+/// e.g. "10h 41m"
+fn duration(delta: TimeDelta) -> String {
+    format!("{}h {:02}m", delta.num_hours(), delta.num_minutes() % 60)
+}
+
+/// e.g. "+3m" or "-2m" against the day before
+fn change(delta: TimeDelta) -> String {
+    format!("{:+}m", delta.num_minutes())
+}
+
+fn create_sun_table<'a>(forecast: &Forecast) -> Table<'a> {
+    let header = Row::new(["Day", "Date", "Sunrise", "Sunset", "Daylight", "Change"]).bold();
+    let mut previous = None;
+    let rows: Vec<Row> = forecast
+        .periods
+        .iter()
+        .map(|period| {
+            let clock = |time: &str| time.get(11..16).unwrap_or("\u{2013}").to_string();
+            let daylight = daylight(period);
+            let row = Row::new(vec![
+                Cell::from(get_day_from_date(&period.date).unwrap_or_default()),
+                Cell::from(period.date.clone()),
+                Cell::from(clock(&period.sunrise)),
+                Cell::from(clock(&period.sunset)),
+                Cell::from(daylight.map_or(String::from("\u{2013}"), duration)),
+                Cell::from(
+                    daylight
+                        .zip(previous)
+                        .map_or(String::new(), |(today, before)| change(today - before)),
+                ),
+            ]);
+            previous = daylight;
+            row
+        })
+        .collect();
+    let widths = [
+        Constraint::Length(3),
+        Constraint::Length(10),
+        Constraint::Length(7),
+        Constraint::Length(6),
+        Constraint::Length(8),
+        Constraint::Length(6),
+    ];
+    Table::new(rows, widths).header(header).column_spacing(2)
+}
+
+/// Renders tonight's moon next to the sun times of every day of the forecast
+pub fn render(
+    frame: &mut Frame,
+    area: Rect,
+    forecast: &Forecast,
+    moon_phase: &MoonPhase,
+    moon_phase_art: &str,
+    config: &Config,
+) {
+    let theme = &config.theme;
+    let [moon_area, sun_area] =
+        Layout::horizontal([Constraint::Ratio(1, 3), Constraint::Ratio(2, 3)]).areas(area);
+
+    let mut moon = vec![Line::default()];
+    moon.extend(moon_phase_art.lines().map(Line::from));
+    moon.push(Line::default());
+    moon.push(Line::from(format!("Illumination: {}", moon_phase.illumination)).dim());
+    frame.render_widget(
+        Paragraph::new(moon).alignment(Alignment::Center).block(
+            Block::bordered().title(
+                Line::from(" Tonight's Moon Phase ")
+                    .fg(Theme::color(&theme.moon))
+                    .centered()
+                    .bold(),
+            ),
+        ),
+        moon_area,
+    );
+
+    let block = Block::bordered().title(
+        Line::from(" Sun ")
+            .fg(Theme::color(&theme.forecast))
+            .centered()
+            .bold(),
+    );
+    let inner = block.inner(sun_area);
+    frame.render_widget(block, sun_area);
+    if forecast
+        .periods
+        .iter()
+        .all(|period| period.sunrise.is_empty())
+    {
+        frame.render_widget(
+            Paragraph::new(format!(
+                "{} has no sunrise or sunset times",
+                config.provider.name()
+            ))
+            .centered()
+            .dim(),
+            inner,
+        );
+        return;
+    }
+    frame.render_widget(create_sun_table(forecast), inner);
+}
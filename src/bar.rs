@@ -0,0 +1,213 @@
+use crate::config::Config;
+use crate::forecast::{Forecast, Period};
+use crate::{MoonPhase, degrees, get_day_from_date, percent, weather_icon};
+use serde::Serialize;
+
+/// Placeholders understood by the bar's format string
+pub const PLACEHOLDERS: [&str; 9] = [
+    "icon",
+    "temp",
+    "feels_like",
+    "weather",
+    "high",
+    "low",
+    "rain_chance",
+    "moon",
+    "location",
+];
+
+/// Returns the first `{placeholder}` in `format` that the bar doesn't know
+pub fn unknown_placeholder(format: &str) -> Option<&str> {
+    format
+        .split('{')
+        .skip(1)
+        .filter_map(|piece| piece.split_once('}'))
+        .map(|(name, _)| name)
+        .find(|name| !PLACEHOLDERS.contains(name))
+}
+
+/// Fills in the placeholders of `format` with the current conditions
+fn render(format: &str, forecast: &Forecast, moon_phase: &MoonPhase, config: &Config) -> String {
+    let today = forecast.periods.first();
+    let today_degrees = |value: fn(&Period) -> f32| {
+        today.map_or(String::from("\u{2013}"), |today| degrees(value(today)))
+    };
+    let values = [
+        (
+            "icon",
+            weather_icon(forecast.current.weather_code).to_string(),
+        ),
+        ("temp", degrees(forecast.current.temperature)),
+        ("feels_like", degrees(forecast.current.apparent_temperature)),
+        ("weather", forecast.current.weather.clone()),
+        ("high", today_degrees(|today| today.temperature_max)),
+        ("low", today_degrees(|today| today.temperature_min)),
+        (
+            "rain_chance",
+            today
+                .and_then(|today| today.precipitation_probability)
+                .map_or(String::from("\u{2013}"), |value| value.to_string()),
+        ),
+        ("moon", moon_phase.phase.clone()),
+        ("location", config.location().name.clone()),
+    ];
+
+    let mut line = format.to_string();
+    for (name, value) in values {
+        line = line.replace(&format!("{{{}}}", name), &value);
+    }
+    line
+}
+
+/// The 4-cast as one line per day, used as the waybar tooltip
+fn forecast_tooltip(forecast: &Forecast) -> String {
+    forecast
+        .periods
+        .iter()
+        .skip(1)
+        .take(4)
+        .map(|period| {
+            format!(
+                "{} {}  {} {} / {}  {} rain",
+                get_day_from_date(&period.date).unwrap_or_default(),
+                period.date.get(5..).unwrap_or_default(),
+                weather_icon(period.weather_code),
+                degrees(period.temperature_max),
+                degrees(period.temperature_min),
+                percent(period.precipitation_probability)
+            )
+        })
+        .collect::<Vec<_>>()
+        .join("\n")
+}
+
+/// CSS class waybar puts on the module, so styles can follow the weather
+fn weather_class(code: i32) -> &'static str {
+    match code {
+        0 | 1 => "clear",
+        2 | 3 => "cloudy",
+        45 | 48 => "fog",
+        71..=77 | 85 | 86 => "snow",
+        95..=99 => "storm",
+        51..=67 | 80..=82 => "rain",
+        _ => "unknown",
+    }
+}
+
+/// Output format of waybar's custom modules with `"return-type": "json"`
+#[derive(Serialize, Debug)]
+struct WaybarOutput {
+    text: String,
+    tooltip: String,
+    class: &'static str,
+    alt: String,
+}
+
+/// Prints the one-line summary, or waybar's JSON when `waybar` is set
+pub fn print(forecast: &Forecast, moon_phase: &MoonPhase, config: &Config, waybar: bool) {
+    let text = render(&config.bar.format, forecast, moon_phase, config);
+    if !waybar {
+        println!("{}", text);
+        return;
+    }
+
+    let current = &forecast.current;
+    let tooltip = format!(
+        "{}: {}, {}\n\n{}",
+        config.location().name,
+        degrees(current.temperature),
+        current.weather,
+        forecast_tooltip(forecast)
+    );
+    let output = WaybarOutput {
+        text,
+        tooltip,
+        class: weather_class(current.weather_code),
+        alt: current.weather.clone(),
+    };
+    println!(
+        "{}",
+        serde_json::to_string(&output).expect("Bar output could not be serialized")
+    );
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::config::Location;
+    use crate::forecast::fixtures;
+
+    fn config(format: &str) -> Config {
+        let mut config = Config::default();
+        config.bar.format = format.to_string();
+        config.locations.push(Location {
+            name: String::from("Berlin"),
+            latitude: 52.52,
+            longitude: 13.41,
+            timezone: String::from("Europe/Berlin"),
+            warning_area: None,
+            geocodes: Vec::new(),
+        });
+        config
+    }
+
+    fn forecast(rain: Option<i32>) -> Forecast {
+        let mut forecast = Forecast {
+            periods: vec![fixtures::period("2026-10-18", 4.0, 16.0)],
+            ..Default::default()
+        };
+        forecast.periods[0].precipitation_probability = rain;
+        forecast.current.temperature = 12.3;
+        forecast.current.weather = String::from("Overcast");
+        forecast
+    }
+
+    #[test]
+    fn unknown_placeholders_are_found() {
+        assert_eq!(unknown_placeholder("{icon} {temp} {rain_chance}%"), None);
+        assert_eq!(unknown_placeholder("{temp} {wind}"), Some("wind"));
+        assert_eq!(unknown_placeholder("{}"), Some(""));
+        // Braces without a closing one are plain text
+        assert_eq!(unknown_placeholder("{temp} {"), None);
+    }
+
+    #[test]
+    fn placeholders_are_filled_in() {
+        let config = config("{location}: {temp} {weather}, {low}–{high}, {rain_chance}%");
+        let line = render(
+            &config.bar.format,
+            &forecast(Some(40)),
+            &MoonPhase::default(),
+            &config,
+        );
+        assert_eq!(line, "Berlin: 12.3° Overcast, 4°–16°, 40%");
+    }
+
+    #[test]
+    fn missing_rain_chance_is_a_dash() {
+        let config = config("{rain_chance}% {temp}{temp}");
+        let line = render(
+            &config.bar.format,
+            &forecast(None),
+            &MoonPhase::default(),
+            &config,
+        );
+        assert_eq!(line, "–% 12.3°12.3°");
+    }
+
+    #[test]
+    fn forecasts_without_days_leave_today_blank() {
+        let config = config("{low} to {high}, {rain_chance}% {temp}");
+        let forecast = Forecast {
+            periods: Vec::new(),
+            ..forecast(Some(40))
+        };
+        let line = render(
+            &config.bar.format,
+            &forecast,
+            &MoonPhase::default(),
+            &config,
+        );
+        assert_eq!(line, "– to –, –% 12.3°");
+    }
+}
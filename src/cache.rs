@@ -0,0 +1,114 @@
+use crate::MoonPhase;
+use crate::config::{Config, ProviderKind};
+use crate::forecast::Forecast;
+use serde::{Deserialize, Serialize};
+use std::path::PathBuf;
+use std::time::{SystemTime, UNIX_EPOCH};
+use std::{fs, io};
+
+/// Bump whenever the cached types change, so older caches are ignored instead of misread
+const CACHE_VERSION: u32 = 7;
+
+/// A fetched forecast as stored on disk, shared by every Raijin process
+#[derive(Serialize, Deserialize, Debug)]
+pub struct CachedForecast {
+    /// Unix timestamp of the fetch
+    pub fetched_at: u64,
+    pub forecast: Forecast,
+    pub moon_phase: MoonPhase,
+}
+
+/// Seconds since the Unix epoch
+pub fn now() -> u64 {
+    SystemTime::now()
+        .duration_since(UNIX_EPOCH)
+        .map(|d| d.as_secs())
+        .unwrap_or(0)
+}
+
+/// 64-bit FNV-1a, which unlike `DefaultHasher` gives the same value on every Rust release, so
+/// builds with different toolchains share their cache files
+pub fn fnv1a(key: &str) -> u64 {
+    key.bytes().fold(0xcbf2_9ce4_8422_2325, |hash, byte| {
+        (hash ^ u64::from(byte)).wrapping_mul(0x0100_0000_01b3)
+    })
+}
+
+/// Everything that changes what gets fetched: provider(s), location, units, days and endpoints
+fn cache_key(config: &Config) -> String {
+    let location = config.location();
+    let endpoints = &config.endpoints;
+    // The consensus providers only matter when they're the ones fetched
+    let providers: Vec<&str> = match config.provider {
+        ProviderKind::Consensus => config
+            .consensus
+            .providers
+            .iter()
+            .map(|kind| kind.name())
+            .collect(),
+        _ => Vec::new(),
+    };
+    [
+        CACHE_VERSION.to_string(),
+        config.provider.name().to_string(),
+        providers.join(","),
+        location.latitude.to_string(),
+        location.longitude.to_string(),
+        location.timezone.clone(),
+        config.units.temperature.api_name().to_string(),
+        config.units.wind_speed.api_name().to_string(),
+        config.units.precipitation.api_name().to_string(),
+        config.forecast_days.to_string(),
+        endpoints.open_meteo.clone(),
+        endpoints.moon_phase.clone(),
+        endpoints.nws.clone(),
+        endpoints.met_norway.clone(),
+        endpoints.bright_sky.clone(),
+    ]
+    .join("|")
+}
+
+/// Cache file named after the hash of `cache_key`
+/// Two configs that would request the same data share one file
+fn cache_path(config: &Config) -> Option<PathBuf> {
+    let dir = dirs::cache_dir()?.join("raijin");
+    Some(dir.join(format!("forecast-{:016x}.json", fnv1a(&cache_key(config)))))
+}
+
+/// Returns the cached forecast if it's younger than the refresh interval
+pub fn load(config: &Config) -> Option<CachedForecast> {
+    let contents = fs::read_to_string(cache_path(config)?).ok()?;
+    let cached: CachedForecast = serde_json::from_str(&contents).ok()?;
+    let max_age = config.refresh_interval * 60;
+    (now().saturating_sub(cached.fetched_at) < max_age).then_some(cached)
+}
+
+/// Stores a freshly fetched forecast
+/// Written to a temporary file first so concurrent readers never see half a forecast
+pub fn store(config: &Config, forecast: &Forecast, moon_phase: &MoonPhase) -> io::Result<()> {
+    let Some(path) = cache_path(config) else {
+        return Ok(());
+    };
+    #[derive(Serialize)]
+    struct CachedForecastRef<'a> {
+        fetched_at: u64,
+        forecast: &'a Forecast,
+        moon_phase: &'a MoonPhase,
+    }
+    let contents = serde_json::to_string(&CachedForecastRef {
+        fetched_at: now(),
+        forecast,
+        moon_phase,
+    })?;
+
+    if let Some(parent) = path.parent() {
+        fs::create_dir_all(parent)?;
+    }
+    let temporary = path.with_extension(format!("json.{}", std::process::id()));
+    let result = fs::write(&temporary, contents).and_then(|()| fs::rename(&temporary, &path));
+    // Each process writes its own temporary file, so a failed one would never be reused
+    if result.is_err() {
+        let _ = fs::remove_file(&temporary);
+    }
+    result
+}
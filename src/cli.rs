@@ -0,0 +1,263 @@
+use crate::config::{
+    Config, ConfigError, Location, MAX_FORECAST_DAYS, PrecipitationUnit, TemperatureUnit,
+    WindSpeedUnit, parse_coordinate,
+};
+use clap::{Args, Parser, Subcommand, ValueEnum};
+use std::path::PathBuf;
+
+/// A free, simple weather TUI that pulls data without the need for an API key
+#[derive(Parser, Debug)]
+#[command(name = "raijin", version, about)]
+pub struct Cli {
+    #[command(subcommand)]
+    pub command: Option<Command>,
+
+    /// Latitude to fetch the forecast for (requires --lon)
+    #[arg(long, global = true, allow_hyphen_values = true, requires = "lon", value_parser = parse_latitude)]
+    pub lat: Option<f64>,
+
+    /// Longitude to fetch the forecast for (requires --lat)
+    #[arg(long, global = true, allow_hyphen_values = true, requires = "lat", value_parser = parse_longitude)]
+    pub lon: Option<f64>,
+
+    /// Name of a location from the config file
+    #[arg(short, long, global = true, conflicts_with = "lat")]
+    pub location: Option<String>,
+
+    /// Unit system, overriding the config's [units] table
+    #[arg(short, long, global = true, value_enum)]
+    pub units: Option<UnitSystem>,
+
+    /// Path of the config file to use
+    #[arg(short, long, global = true)]
+    pub config: Option<PathBuf>,
+
+    /// Number of days to fetch, including today
+    #[arg(short, long, global = true, value_parser = clap::value_parser!(u8).range(1..=MAX_FORECAST_DAYS as i64))]
+    pub days: Option<u8>,
+
+    /// Print JSON instead of text or the TUI (see the README for the schema)
+    #[arg(long, global = true)]
+    pub json: bool,
+
+    /// Print a short coloured summary and exit instead of starting the TUI
+    #[arg(long, conflicts_with = "json")]
+    pub once: bool,
+}
+
+#[derive(Subcommand, Debug, Clone, PartialEq)]
+pub enum Command {
+    /// Print the current conditions
+    Now,
+    /// Print the daily forecast
+    Daily,
+    /// Print the hourly forecast
+    Hourly,
+    /// Print a one-line summary for tmux, waybar or polybar
+    Bar {
+        /// Format string, e.g. "{icon} {temp} {feels_like} {rain_chance}%"
+        #[arg(short, long)]
+        format: Option<String>,
+
+        /// Print waybar's JSON with the 4-cast as tooltip
+        #[arg(long)]
+        waybar: bool,
+    },
+    /// Export the forecast to a file
+    Export(ExportArgs),
+    /// Serve the forecast as JSON on localhost
+    Serve {
+        /// Port to listen on
+        #[arg(short, long, default_value_t = 7878)]
+        port: u16,
+    },
+    /// Check the alert rules every refresh, run the hooks and publish to MQTT, without the TUI
+    Daemon,
+    /// Publish the forecast to the MQTT broker once, e.g. from cron
+    Publish,
+}
+
+#[derive(Args, Debug, Clone, PartialEq)]
+pub struct ExportArgs {
+    /// File format to write
+    #[arg(short, long, value_enum)]
+    pub format: ExportFormat,
+
+    /// File to write to; stdout if not given
+    #[arg(short, long)]
+    pub output: Option<PathBuf>,
+
+    /// Export the hourly forecast (csv and parquet only)
+    #[arg(long, conflicts_with = "daily")]
+    pub hourly: bool,
+
+    /// Export the daily forecast; the default
+    #[arg(long)]
+    pub daily: bool,
+
+    /// Add sunrise and sunset events (ics only)
+    #[arg(long)]
+    pub sun_events: bool,
+}
+
+#[derive(ValueEnum, Debug, Clone, Copy, PartialEq)]
+pub enum ExportFormat {
+    /// iCalendar with one all-day event per day
+    Ics,
+    /// Comma-separated values with a header row
+    Csv,
+    /// Apache Parquet with typed columns
+    Parquet,
+}
+
+#[derive(ValueEnum, Debug, Clone, Copy, PartialEq)]
+pub enum UnitSystem {
+    /// °C, km/h, mm
+    Metric,
+    /// °F, mph, inch
+    Imperial,
+}
+
+fn parse_latitude(value: &str) -> Result<f64, String> {
+    parse_coordinate(value, 90.0)
+}
+
+fn parse_longitude(value: &str) -> Result<f64, String> {
+    parse_coordinate(value, 180.0)
+}
+
+impl Cli {
+    /// Config file to read, either from --config or the default location
+    pub fn config_path(&self) -> PathBuf {
+        self.config.clone().unwrap_or_else(Config::default_path)
+    }
+
+    /// The location given by --lat and --lon, named after its coordinates
+    pub fn coordinates(&self) -> Option<Location> {
+        let (latitude, longitude) = self.lat.zip(self.lon)?;
+        Some(Location {
+            name: format!("{}, {}", latitude, longitude),
+            latitude,
+            longitude,
+            timezone: String::from("auto"),
+            warning_area: None,
+            geocodes: Vec::new(),
+        })
+    }
+
+    /// Applies the per-invocation overrides on top of the loaded config
+    pub fn apply(&self, config: &mut Config) -> Result<(), ConfigError> {
+        if let Some(location) = self.coordinates() {
+            config.locations.retain(|l| l.name != location.name);
+            config.default_location = Some(location.name.clone());
+            config.locations.push(location);
+        }
+
+        if let Some(name) = &self.location {
+            if !config.locations.iter().any(|l| &l.name == name) {
+                return Err(ConfigError::Invalid {
+                    key: String::from("--location"),
+                    message: format!("no location named \"{}\" in the config", name),
+                });
+            }
+            config.default_location = Some(name.clone());
+        }
+
+        match self.units {
+            Some(UnitSystem::Metric) => {
+                config.units.temperature = TemperatureUnit::Celsius;
+                config.units.wind_speed = WindSpeedUnit::Kmh;
+                config.units.precipitation = PrecipitationUnit::Mm;
+            }
+            Some(UnitSystem::Imperial) => {
+                config.units.temperature = TemperatureUnit::Fahrenheit;
+                config.units.wind_speed = WindSpeedUnit::Mph;
+                config.units.precipitation = PrecipitationUnit::Inch;
+            }
+            None => {}
+        }
+
+        if let Some(Command::Bar {
+            format: Some(format),
+            ..
+        }) = &self.command
+        {
+            config.bar.format = format.clone();
+        }
+
+        if let Some(days) = self.days {
+            config.forecast_days = days;
+        }
+
+        config.validate()
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn config() -> Config {
+        let mut config = Config::default();
+        for (name, latitude, longitude) in [("Home", 35.96, -83.92), ("Work", 36.16, -86.78)] {
+            config.locations.push(Location {
+                name: name.to_string(),
+                latitude,
+                longitude,
+                timezone: String::from("America/New_York"),
+                warning_area: None,
+                geocodes: Vec::new(),
+            });
+        }
+        config
+    }
+
+    fn apply(args: &[&str]) -> Result<Config, ConfigError> {
+        let cli = Cli::try_parse_from([&["raijin"], args].concat()).unwrap();
+        let mut config = config();
+        cli.apply(&mut config)?;
+        Ok(config)
+    }
+
+    #[test]
+    fn coordinates_become_the_default_location() {
+        let config = apply(&["--lat", "52.52", "--lon", "-13.41"]).unwrap();
+        assert_eq!(config.locations.len(), 3);
+        let location = config.location();
+        assert_eq!(location.name, "52.52, -13.41");
+        assert_eq!((location.latitude, location.longitude), (52.52, -13.41));
+        assert_eq!(location.timezone, "auto");
+    }
+
+    #[test]
+    fn locations_are_picked_by_name() {
+        assert_eq!(
+            apply(&["--location", "Work"]).unwrap().location().name,
+            "Work"
+        );
+        assert_eq!(apply(&[]).unwrap().location().name, "Home");
+        let err = apply(&["-l", "Beach"]).unwrap_err();
+        assert!(matches!(err, ConfigError::Invalid { ref key, .. } if key == "--location"));
+    }
+
+    #[test]
+    fn overrides_replace_the_config() {
+        let config = apply(&["--units", "imperial", "--days", "3"]).unwrap();
+        assert_eq!(config.units.temperature, TemperatureUnit::Fahrenheit);
+        assert_eq!(config.units.wind_speed, WindSpeedUnit::Mph);
+        assert_eq!(config.units.precipitation, PrecipitationUnit::Inch);
+        assert_eq!(config.forecast_days, 3);
+
+        let config = apply(&["bar", "--format", "{temp}"]).unwrap();
+        assert_eq!(config.bar.format, "{temp}");
+        assert!(apply(&["bar", "--format", "{wind}"]).is_err());
+    }
+
+    #[test]
+    fn arguments_are_checked_by_clap() {
+        assert!(Cli::try_parse_from(["raijin", "--lat", "52.52"]).is_err());
+        assert!(Cli::try_parse_from(["raijin", "--lat", "91", "--lon", "0"]).is_err());
+        assert!(Cli::try_parse_from(["raijin", "--days", "0"]).is_err());
+        assert!(Cli::try_parse_from(["raijin", "--once", "--json"]).is_err());
+    }
+}
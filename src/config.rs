@@ -0,0 +1,1430 @@
+use ratatui::style::Color;
+use serde::{Deserialize, Serialize};
+use std::io::{IsTerminal, Write};
+use std::net::SocketAddr;
+use std::path::{Path, PathBuf};
+use std::str::FromStr;
+use std::{fmt, fs, io};
+
+/// Version of the config file layout this build understands
+pub const CONFIG_VERSION: u32 = 1;
+
+/// Longest forecast Open-Meteo serves
+pub const MAX_FORECAST_DAYS: u8 = 16;
+
+/// Header written above freshly generated config files
+const CONFIG_HEADER: &str =
+    "# Raijin configuration\n# See the README for a description of every key\n\n";
+
+/// Everything that can go wrong while loading the config
+#[derive(Debug)]
+pub enum ConfigError {
+    Io(PathBuf, io::Error),
+    Parse(toml::de::Error),
+    Serialize(toml::ser::Error),
+    Invalid { key: String, message: String },
+}
+
+impl fmt::Display for ConfigError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            ConfigError::Io(path, err) => write!(f, "{}: {}", path.display(), err),
+            ConfigError::Parse(err) => write!(f, "{}", err),
+            ConfigError::Serialize(err) => write!(f, "could not write config: {}", err),
+            ConfigError::Invalid { key, message } => {
+                write!(f, "invalid value for `{}`: {}", key, message)
+            }
+        }
+    }
+}
+
+impl std::error::Error for ConfigError {}
+
+/// Shorthand for building a validation error for `key`
+fn invalid(key: impl Into<String>, message: impl Into<String>) -> ConfigError {
+    ConfigError::Invalid {
+        key: key.into(),
+        message: message.into(),
+    }
+}
+
+/// Parses a coordinate and checks it lies within `-limit..=limit`
+pub fn parse_coordinate(value: &str, limit: f64) -> Result<f64, String> {
+    let coordinate: f64 = value
+        .parse()
+        .map_err(|_| format!("\"{}\" is not a number", value))?;
+    if !(-limit..=limit).contains(&coordinate) {
+        return Err(format!("must be between -{} and {}", limit, limit));
+    }
+    Ok(coordinate)
+}
+
+/// Checks a time zone is "auto" or a name from the IANA database
+pub fn parse_timezone(value: &str) -> Result<String, String> {
+    if value != "auto" && chrono_tz::Tz::from_str(value).is_err() {
+        return Err(format!(
+            "\"{}\" is not an IANA time zone such as Europe/Berlin, or \"auto\"",
+            value
+        ));
+    }
+    Ok(value.to_string())
+}
+
+/// A named place to fetch the forecast for
+#[derive(Serialize, Deserialize, Debug, Clone)]
+#[serde(deny_unknown_fields)]
+pub struct Location {
+    pub name: String,
+    pub latitude: f64,
+    pub longitude: f64,
+    /// IANA time zone name, or "auto" to let Open-Meteo derive it from the coordinates, also for
+    /// the other providers
+    #[serde(default = "default_timezone")]
+    pub timezone: String,
+    /// Text matched against the area description of official warnings, e.g. "Berlin"
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub warning_area: Option<String>,
+    /// Geocodes of the warning areas covering this location, e.g. MeteoAlarm's EMMA_ID "DE300"
+    #[serde(default, skip_serializing_if = "Vec::is_empty")]
+    pub geocodes: Vec<String>,
+}
+
+fn default_timezone() -> String {
+    String::from("auto")
+}
+
+#[derive(Serialize, Deserialize, Debug, Clone, Copy, Default, PartialEq)]
+#[serde(rename_all = "lowercase")]
+pub enum TemperatureUnit {
+    #[default]
+    Celsius,
+    Fahrenheit,
+}
+
+impl TemperatureUnit {
+    /// Value of Open-Meteo's `temperature_unit` parameter
+    pub fn api_name(&self) -> &'static str {
+        match self {
+            TemperatureUnit::Celsius => "celsius",
+            TemperatureUnit::Fahrenheit => "fahrenheit",
+        }
+    }
+
+    pub fn symbol(&self) -> &'static str {
+        match self {
+            TemperatureUnit::Celsius => "\u{00B0}C",
+            TemperatureUnit::Fahrenheit => "\u{00B0}F",
+        }
+    }
+
+    /// Converts a temperature for providers that only report Celsius, to one decimal
+    pub fn convert_celsius(&self, celsius: f32) -> f32 {
+        let value = match self {
+            TemperatureUnit::Celsius => celsius,
+            TemperatureUnit::Fahrenheit => celsius * 9.0 / 5.0 + 32.0,
+        };
+        (value * 10.0).round() / 10.0
+    }
+}
+
+#[derive(Serialize, Deserialize, Debug, Clone, Copy, Default, PartialEq)]
+#[serde(rename_all = "lowercase")]
+pub enum WindSpeedUnit {
+    #[default]
+    Kmh,
+    Ms,
+    Mph,
+    Kn,
+}
+
+impl WindSpeedUnit {
+    /// Value of Open-Meteo's `wind_speed_unit` parameter
+    pub fn api_name(&self) -> &'static str {
+        match self {
+            WindSpeedUnit::Kmh => "kmh",
+            WindSpeedUnit::Ms => "ms",
+            WindSpeedUnit::Mph => "mph",
+            WindSpeedUnit::Kn => "kn",
+        }
+    }
+
+    pub fn symbol(&self) -> &'static str {
+        match self {
+            WindSpeedUnit::Kmh => "km/h",
+            WindSpeedUnit::Ms => "m/s",
+            WindSpeedUnit::Mph => "mph",
+            WindSpeedUnit::Kn => "kn",
+        }
+    }
+
+    /// Converts a speed for providers that only report km/h, to one decimal
+    pub fn convert_kmh(&self, kmh: f32) -> f32 {
+        let value = match self {
+            WindSpeedUnit::Kmh => kmh,
+            WindSpeedUnit::Ms => kmh / 3.6,
+            WindSpeedUnit::Mph => kmh / 1.609_344,
+            WindSpeedUnit::Kn => kmh / 1.852,
+        };
+        (value * 10.0).round() / 10.0
+    }
+}
+
+#[derive(Serialize, Deserialize, Debug, Clone, Copy, Default, PartialEq)]
+#[serde(rename_all = "lowercase")]
+pub enum PrecipitationUnit {
+    #[default]
+    Mm,
+    Inch,
+}
+
+impl PrecipitationUnit {
+    /// Value of Open-Meteo's `precipitation_unit` parameter
+    pub fn api_name(&self) -> &'static str {
+        match self {
+            PrecipitationUnit::Mm => "mm",
+            PrecipitationUnit::Inch => "inch",
+        }
+    }
+
+    pub fn symbol(&self) -> &'static str {
+        match self {
+            PrecipitationUnit::Mm => "mm",
+            PrecipitationUnit::Inch => "in",
+        }
+    }
+
+    /// Converts an amount for providers that only report millimetres, to one decimal of a
+    /// millimetre or two of an inch
+    pub fn convert_mm(&self, mm: f32) -> f32 {
+        match self {
+            PrecipitationUnit::Mm => (mm * 10.0).round() / 10.0,
+            PrecipitationUnit::Inch => (mm / 25.4 * 100.0).round() / 100.0,
+        }
+    }
+}
+
+/// Units the forecast is requested and displayed in
+#[derive(Serialize, Deserialize, Debug, Clone, Default)]
+#[serde(default, deny_unknown_fields)]
+pub struct Units {
+    pub temperature: TemperatureUnit,
+    pub wind_speed: WindSpeedUnit,
+    pub precipitation: PrecipitationUnit,
+}
+
+/// Colours of the panel titles and chart data
+/// Accepts ratatui colour names ("lightblue", "red", ...), "#rrggbb", or a 0-255 index
+#[derive(Serialize, Deserialize, Debug, Clone)]
+#[serde(default, deny_unknown_fields)]
+pub struct Theme {
+    pub right_now: String,
+    pub moon: String,
+    pub today_chart: String,
+    pub fortnight_chart: String,
+    pub forecast: String,
+    pub logo: String,
+    pub data: String,
+    pub alert: String,
+    pub warning_extreme: String,
+    pub warning_severe: String,
+    pub warning_moderate: String,
+    pub warning_minor: String,
+    /// Range between the providers of a consensus forecast
+    pub spread: String,
+    /// Marks days on which the providers of a consensus forecast disagree
+    pub disagreement: String,
+    /// Border of the selected 4-cast card
+    pub selection: String,
+}
+
+impl Default for Theme {
+    fn default() -> Self {
+        Theme {
+            right_now: String::from("lightblue"),
+            moon: String::from("lightyellow"),
+            today_chart: String::from("cyan"),
+            fortnight_chart: String::from("cyan"),
+            forecast: String::from("lightmagenta"),
+            logo: String::from("red"),
+            data: String::from("yellow"),
+            alert: String::from("red"),
+            warning_extreme: String::from("red"),
+            warning_severe: String::from("#ff8c00"),
+            warning_moderate: String::from("yellow"),
+            warning_minor: String::from("green"),
+            spread: String::from("darkgray"),
+            disagreement: String::from("yellow"),
+            selection: String::from("white"),
+        }
+    }
+}
+
+impl Theme {
+    /// Every colour together with its key, used for validation
+    fn entries(&self) -> [(&'static str, &String); 15] {
+        [
+            ("right_now", &self.right_now),
+            ("moon", &self.moon),
+            ("today_chart", &self.today_chart),
+            ("fortnight_chart", &self.fortnight_chart),
+            ("forecast", &self.forecast),
+            ("logo", &self.logo),
+            ("data", &self.data),
+            ("alert", &self.alert),
+            ("warning_extreme", &self.warning_extreme),
+            ("warning_severe", &self.warning_severe),
+            ("warning_moderate", &self.warning_moderate),
+            ("warning_minor", &self.warning_minor),
+            ("spread", &self.spread),
+            ("disagreement", &self.disagreement),
+            ("selection", &self.selection),
+        ]
+    }
+
+    /// Resolves a colour from this theme; values are checked when the config is loaded
+    pub fn color(value: &str) -> Color {
+        Color::from_str(value).unwrap_or(Color::Reset)
+    }
+}
+
+/// Which panels of the main screen are shown
+#[derive(Serialize, Deserialize, Debug, Clone)]
+#[serde(default, deny_unknown_fields)]
+pub struct Panels {
+    pub right_now: bool,
+    pub moon: bool,
+    pub logo: bool,
+    pub today_chart: bool,
+    pub fortnight_chart: bool,
+    pub forecast: bool,
+    /// Takes the logo's place while any alert is active
+    pub alerts: bool,
+    /// Banner of official warnings above everything else, shown only while any are active
+    pub warnings: bool,
+}
+
+impl Default for Panels {
+    fn default() -> Self {
+        Panels {
+            right_now: true,
+            moon: true,
+            logo: true,
+            today_chart: true,
+            fortnight_chart: true,
+            forecast: true,
+            alerts: true,
+            warnings: true,
+        }
+    }
+}
+
+/// A panel the Overview's layout can place
+#[derive(Serialize, Deserialize, Debug, Clone, Copy, PartialEq)]
+#[serde(rename_all = "snake_case")]
+pub enum Panel {
+    RightNow,
+    Moon,
+    /// Shows the alerts instead while any are active, unless the layout has an `alerts` panel
+    Logo,
+    TodayChart,
+    FortnightChart,
+    Forecast,
+    /// Only takes space while any alert is active
+    Alerts,
+    Days,
+    Air,
+}
+
+impl Panel {
+    pub fn name(&self) -> &'static str {
+        match self {
+            Panel::RightNow => "right_now",
+            Panel::Moon => "moon",
+            Panel::Logo => "logo",
+            Panel::TodayChart => "today_chart",
+            Panel::FortnightChart => "fortnight_chart",
+            Panel::Forecast => "forecast",
+            Panel::Alerts => "alerts",
+            Panel::Days => "days",
+            Panel::Air => "air",
+        }
+    }
+}
+
+fn default_size() -> u16 {
+    1
+}
+
+/// A cell of the Overview: a panel, or rows or columns sharing its space by their `size`
+#[derive(Serialize, Deserialize, Debug, Clone, PartialEq)]
+#[serde(deny_unknown_fields)]
+pub struct OverviewLayout {
+    /// Share of the parent's space relative to the other rows or columns
+    #[serde(default = "default_size")]
+    pub size: u16,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub panel: Option<Panel>,
+    #[serde(default, skip_serializing_if = "Vec::is_empty")]
+    pub rows: Vec<OverviewLayout>,
+    #[serde(default, skip_serializing_if = "Vec::is_empty")]
+    pub columns: Vec<OverviewLayout>,
+}
+
+impl OverviewLayout {
+    fn panel(size: u16, panel: Panel) -> OverviewLayout {
+        OverviewLayout {
+            size,
+            panel: Some(panel),
+            rows: Vec::new(),
+            columns: Vec::new(),
+        }
+    }
+
+    fn rows(size: u16, rows: Vec<OverviewLayout>) -> OverviewLayout {
+        OverviewLayout {
+            size,
+            panel: None,
+            rows,
+            columns: Vec::new(),
+        }
+    }
+
+    fn columns(size: u16, columns: Vec<OverviewLayout>) -> OverviewLayout {
+        OverviewLayout {
+            size,
+            panel: None,
+            rows: Vec::new(),
+            columns,
+        }
+    }
+
+    /// Reads `layout` from the config
+    /// Errors name the offending cell by its path, e.g. "layout.rows[0].columns[1]"
+    pub fn parse(value: &toml::Value) -> Result<OverviewLayout, String> {
+        let layout = OverviewLayout::deserialize(value.clone())
+            .map_err(|err| format!("layout: {}", err.message()))?;
+        layout.check("layout", &mut Vec::new())?;
+        Ok(layout)
+    }
+
+    fn check(&self, path: &str, seen: &mut Vec<Panel>) -> Result<(), String> {
+        if self.size == 0 {
+            return Err(format!("{}: size must be at least 1", path));
+        }
+        let kinds = [
+            self.panel.is_some(),
+            !self.rows.is_empty(),
+            !self.columns.is_empty(),
+        ];
+        if kinds.iter().filter(|kind| **kind).count() != 1 {
+            return Err(format!(
+                "{}: needs exactly one of panel, rows or columns",
+                path
+            ));
+        }
+        if let Some(panel) = self.panel {
+            if seen.contains(&panel) {
+                return Err(format!("{}: {} appears more than once", path, panel.name()));
+            }
+            seen.push(panel);
+        }
+        for (i, row) in self.rows.iter().enumerate() {
+            row.check(&format!("{}.rows[{}]", path, i), seen)?;
+        }
+        for (i, column) in self.columns.iter().enumerate() {
+            column.check(&format!("{}.columns[{}]", path, i), seen)?;
+        }
+        Ok(())
+    }
+
+    pub fn contains(&self, panel: Panel) -> bool {
+        self.panel == Some(panel)
+            || self
+                .rows
+                .iter()
+                .chain(&self.columns)
+                .any(|child| child.contains(panel))
+    }
+}
+
+impl Default for OverviewLayout {
+    /// The current conditions, moon and charts above the 4-cast
+    fn default() -> Self {
+        use Panel::*;
+        OverviewLayout::rows(
+            1,
+            vec![
+                OverviewLayout::columns(
+                    7,
+                    vec![
+                        OverviewLayout::rows(
+                            2,
+                            vec![
+                                OverviewLayout::columns(
+                                    2,
+                                    vec![
+                                        OverviewLayout::panel(1, RightNow),
+                                        OverviewLayout::panel(1, Moon),
+                                    ],
+                                ),
+                                OverviewLayout::panel(3, FortnightChart),
+                            ],
+                        ),
+                        OverviewLayout::rows(
+                            1,
+                            vec![
+                                OverviewLayout::panel(3, TodayChart),
+                                OverviewLayout::panel(1, Logo),
+                            ],
+                        ),
+                    ],
+                ),
+                OverviewLayout::panel(3, Forecast),
+            ],
+        )
+    }
+}
+
+/// Forecast variable an alert rule looks at
+#[derive(Serialize, Deserialize, Debug, Clone, Copy, PartialEq)]
+#[serde(rename_all = "snake_case")]
+pub enum AlertMetric {
+    Temperature,
+    ApparentTemperature,
+    RainChance,
+    WindGusts,
+}
+
+impl AlertMetric {
+    /// Unit suffix of the metric's values
+    pub fn unit(&self, units: &Units) -> &'static str {
+        match self {
+            AlertMetric::Temperature | AlertMetric::ApparentTemperature => {
+                units.temperature.symbol()
+            }
+            AlertMetric::RainChance => "%",
+            AlertMetric::WindGusts => units.wind_speed.symbol(),
+        }
+    }
+}
+
+#[derive(Serialize, Deserialize, Debug, Clone, Copy, PartialEq)]
+#[serde(rename_all = "lowercase")]
+pub enum AlertCondition {
+    Below,
+    Above,
+}
+
+/// A user-defined threshold, e.g. "temperature below 0"
+/// Thresholds are in the configured units
+#[derive(Serialize, Deserialize, Debug, Clone)]
+#[serde(deny_unknown_fields)]
+pub struct AlertRule {
+    pub name: String,
+    pub metric: AlertMetric,
+    pub condition: AlertCondition,
+    pub threshold: f32,
+}
+
+/// Official warnings in the Common Alerting Protocol, see `warnings.rs`
+#[derive(Serialize, Deserialize, Debug, Clone, Default)]
+#[serde(default, deny_unknown_fields)]
+pub struct Warnings {
+    /// URL or path of a CAP alert or an Atom feed of CAP alerts
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub feed: Option<String>,
+}
+
+/// The user's own weather station, whose observations replace the provider's current conditions
+/// At most one of `ecowitt`, `weewx` and `file` may be set; see `station.rs`
+#[derive(Serialize, Deserialize, Debug, Clone)]
+#[serde(default, deny_unknown_fields)]
+pub struct PersonalStation {
+    /// Address to receive the station's Ecowitt "Customized" uploads on, e.g. "0.0.0.0:8090"
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub ecowitt: Option<String>,
+    /// URL or path of a WeeWX JSON report
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub weewx: Option<String>,
+    /// Path of a JSON file with the latest observation, written by any other tool
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub file: Option<String>,
+    /// Shown in the Right Now panel
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub name: Option<String>,
+    /// Minutes after which an observation is too old and the provider's conditions are shown
+    pub max_age: u64,
+}
+
+impl Default for PersonalStation {
+    fn default() -> Self {
+        PersonalStation {
+            ecowitt: None,
+            weewx: None,
+            file: None,
+            name: None,
+            max_age: 15,
+        }
+    }
+}
+
+impl PersonalStation {
+    pub fn is_configured(&self) -> bool {
+        self.ecowitt.is_some() || self.weewx.is_some() || self.file.is_some()
+    }
+}
+
+/// MQTT broker the forecast is published to after every refresh, see `mqtt.rs`
+/// Topics may contain `{location}`, replaced by the location's name in lowercase with
+/// underscores
+#[derive(Serialize, Deserialize, Debug, Clone)]
+#[serde(default, deny_unknown_fields)]
+pub struct Mqtt {
+    /// Host name or address of the broker; nothing is published if unset
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub host: Option<String>,
+    pub port: u16,
+    /// The process ID is appended, so the TUI and `raijin daemon` don't disconnect each other
+    pub client_id: String,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub username: Option<String>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub password: Option<String>,
+    /// Keep the last messages on the broker for subscribers that connect later
+    pub retain: bool,
+    pub current_topic: String,
+    pub today_topic: String,
+    pub alerts_topic: String,
+    /// Announce the sensors to Home Assistant's MQTT discovery
+    pub discovery: bool,
+    pub discovery_prefix: String,
+}
+
+impl Default for Mqtt {
+    fn default() -> Self {
+        Mqtt {
+            host: None,
+            port: 1883,
+            client_id: String::from("raijin"),
+            username: None,
+            password: None,
+            retain: true,
+            current_topic: String::from("raijin/{location}/current"),
+            today_topic: String::from("raijin/{location}/today"),
+            alerts_topic: String::from("raijin/{location}/alerts"),
+            discovery: true,
+            discovery_prefix: String::from("homeassistant"),
+        }
+    }
+}
+
+/// What to run when an alert rule starts matching, from the TUI or `raijin daemon`
+#[derive(Serialize, Deserialize, Debug, Clone, Default)]
+#[serde(default, deny_unknown_fields)]
+pub struct Hooks {
+    /// Shell command, run with the alert in `RAIJIN_ALERT_*` environment variables
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub command: Option<String>,
+    /// URL the alert is POSTed to as JSON
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub webhook: Option<String>,
+}
+
+impl Hooks {
+    pub fn is_empty(&self) -> bool {
+        self.command.is_none() && self.webhook.is_none()
+    }
+}
+
+/// Settings of `raijin bar`
+#[derive(Serialize, Deserialize, Debug, Clone)]
+#[serde(default, deny_unknown_fields)]
+pub struct Bar {
+    /// Line to print, see `bar::PLACEHOLDERS` for the `{placeholders}`
+    pub format: String,
+}
+
+impl Default for Bar {
+    fn default() -> Self {
+        Bar {
+            format: String::from("{icon} {temp}"),
+        }
+    }
+}
+
+/// Weather service the forecast is fetched from
+#[derive(Serialize, Deserialize, Debug, Clone, Copy, Default, PartialEq)]
+#[serde(rename_all = "kebab-case")]
+pub enum ProviderKind {
+    #[default]
+    OpenMeteo,
+    /// The US National Weather Service
+    Nws,
+    /// The Norwegian Meteorological Institute's Locationforecast
+    MetNorway,
+    /// Deutscher Wetterdienst open data through Bright Sky
+    BrightSky,
+    /// The mean of the providers in `consensus.providers`
+    Consensus,
+}
+
+impl ProviderKind {
+    /// Name as written in the config
+    pub fn name(&self) -> &'static str {
+        match self {
+            ProviderKind::OpenMeteo => "open-meteo",
+            ProviderKind::Nws => "nws",
+            ProviderKind::MetNorway => "met-norway",
+            ProviderKind::BrightSky => "bright-sky",
+            ProviderKind::Consensus => "consensus",
+        }
+    }
+}
+
+/// Providers combined by `provider = "consensus"`
+#[derive(Serialize, Deserialize, Debug, Clone)]
+#[serde(default, deny_unknown_fields)]
+pub struct Consensus {
+    /// In order of preference; weather descriptions come from the first one that has the hour
+    pub providers: Vec<ProviderKind>,
+    /// Difference between the providers' highs or lows, in the temperature unit, above which a
+    /// day is marked as disputed
+    pub threshold: f32,
+}
+
+impl Default for Consensus {
+    fn default() -> Self {
+        Consensus {
+            providers: vec![ProviderKind::OpenMeteo, ProviderKind::MetNorway],
+            threshold: 3.0,
+        }
+    }
+}
+
+/// Base URLs of the services Raijin talks to
+#[derive(Serialize, Deserialize, Debug, Clone)]
+#[serde(default, deny_unknown_fields)]
+pub struct Endpoints {
+    pub open_meteo: String,
+    pub moon_phase: String,
+    pub nws: String,
+    pub met_norway: String,
+    pub bright_sky: String,
+    pub air_quality: String,
+}
+
+impl Default for Endpoints {
+    fn default() -> Self {
+        Endpoints {
+            open_meteo: String::from("https://api.open-meteo.com/v1/forecast"),
+            moon_phase: String::from("https://api.viewbits.com/v1/moonphase"),
+            nws: String::from("https://api.weather.gov"),
+            met_norway: String::from("https://api.met.no/weatherapi/locationforecast/2.0/complete"),
+            bright_sky: String::from("https://api.brightsky.dev"),
+            air_quality: String::from("https://air-quality-api.open-meteo.com/v1/air-quality"),
+        }
+    }
+}
+
+/// Contents of `config.toml`
+#[derive(Serialize, Deserialize, Debug, Clone)]
+#[serde(default, deny_unknown_fields)]
+pub struct Config {
+    pub version: u32,
+    /// Name of the location used on start-up; the first location if unset
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub default_location: Option<String>,
+    /// Minutes between forecast refreshes
+    pub refresh_interval: u64,
+    /// Number of days to fetch, including today
+    pub forecast_days: u8,
+    pub provider: ProviderKind,
+    pub consensus: Consensus,
+    /// User-Agent sent with every request; see `Config::user_agent` for the default
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub user_agent: Option<String>,
+    /// How the services can reach whoever runs Raijin, e.g. an email address, for the default
+    /// User-Agent; Raijin's homepage if unset
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub contact: Option<String>,
+    pub units: Units,
+    pub theme: Theme,
+    pub panels: Panels,
+    pub bar: Bar,
+    pub endpoints: Endpoints,
+    pub locations: Vec<Location>,
+    pub alerts: Vec<AlertRule>,
+    pub hooks: Hooks,
+    pub warnings: Warnings,
+    pub station: PersonalStation,
+    pub mqtt: Mqtt,
+    /// Arrangement of the Overview's panels, read by `OverviewLayout::parse`; kept as plain TOML
+    /// so a broken layout falls back to the default instead of failing the whole config
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub layout: Option<toml::Value>,
+}
+
+impl Default for Config {
+    fn default() -> Self {
+        Config {
+            version: CONFIG_VERSION,
+            default_location: None,
+            refresh_interval: 30,
+            forecast_days: 14,
+            provider: ProviderKind::default(),
+            consensus: Consensus::default(),
+            user_agent: None,
+            contact: None,
+            units: Units::default(),
+            theme: Theme::default(),
+            panels: Panels::default(),
+            bar: Bar::default(),
+            endpoints: Endpoints::default(),
+            locations: Vec::new(),
+            alerts: Vec::new(),
+            hooks: Hooks::default(),
+            warnings: Warnings::default(),
+            station: PersonalStation::default(),
+            mqtt: Mqtt::default(),
+            layout: None,
+        }
+    }
+}
+
+impl Config {
+    /// Default location of the config file: `$XDG_CONFIG_HOME/raijin/config.toml`
+    pub fn default_path() -> PathBuf {
+        config_dir().join("raijin").join("config.toml")
+    }
+
+    /// Reads and validates the config at `path`
+    pub fn load(path: &Path) -> Result<Config, ConfigError> {
+        let contents =
+            fs::read_to_string(path).map_err(|err| ConfigError::Io(path.to_path_buf(), err))?;
+        let config: Config = toml::from_str(&contents).map_err(ConfigError::Parse)?;
+        config.validate()?;
+        Ok(config)
+    }
+
+    /// Loads the config at `path`, creating it first if it doesn't exist yet
+    /// An existing legacy `.env` file is migrated; otherwise a default config is written
+    /// Without a file or an old `.env` to migrate, the first location is `location` (from
+    /// --lat/--lon) or asked for on the terminal; there's no place worth guessing
+    pub fn load_or_init(path: &Path, location: Option<Location>) -> Result<Config, ConfigError> {
+        if !path.exists() {
+            let legacy = legacy_env_path();
+            let config = if legacy.exists() {
+                let config = migrate_env(&legacy)?;
+                eprintln!("Migrated {} to {}", legacy.display(), path.display());
+                config
+            } else {
+                let location = match location {
+                    Some(location) => location,
+                    None if io::stdin().is_terminal() => prompt_location(path)?,
+                    None => {
+                        return Err(invalid(
+                            "locations",
+                            "no config yet; pass --lat and --lon, or start raijin in a terminal to enter a location",
+                        ));
+                    }
+                };
+                Config {
+                    locations: vec![location],
+                    ..Config::default()
+                }
+            };
+            config.save(path)?;
+        }
+        Config::load(path)
+    }
+
+    /// Writes the config to `path`, creating parent directories as needed
+    pub fn save(&self, path: &Path) -> Result<(), ConfigError> {
+        let contents = toml::to_string(self).map_err(ConfigError::Serialize)?;
+        if let Some(parent) = path.parent() {
+            fs::create_dir_all(parent).map_err(|err| ConfigError::Io(parent.to_path_buf(), err))?;
+        }
+        fs::write(path, format!("{}{}", CONFIG_HEADER, contents))
+            .map_err(|err| ConfigError::Io(path.to_path_buf(), err))
+    }
+
+    /// Checks the values serde can't, naming the offending key on failure
+    pub fn validate(&self) -> Result<(), ConfigError> {
+        if self.version != CONFIG_VERSION {
+            return Err(invalid(
+                "version",
+                format!(
+                    "expected {}, found {} (this build only understands version {})",
+                    CONFIG_VERSION, self.version, CONFIG_VERSION
+                ),
+            ));
+        }
+        if self.refresh_interval == 0 {
+            return Err(invalid("refresh_interval", "must be at least 1 minute"));
+        }
+        if !(1..=MAX_FORECAST_DAYS).contains(&self.forecast_days) {
+            return Err(invalid(
+                "forecast_days",
+                format!("must be between 1 and {}", MAX_FORECAST_DAYS),
+            ));
+        }
+        if self.locations.is_empty() {
+            return Err(invalid("locations", "at least one location is required"));
+        }
+
+        for (i, location) in self.locations.iter().enumerate() {
+            let key = |field: &str| format!("locations[{}].{}", i, field);
+            if location.name.trim().is_empty() {
+                return Err(invalid(key("name"), "must not be empty"));
+            }
+            if self.locations[..i].iter().any(|l| l.name == location.name) {
+                return Err(invalid(
+                    key("name"),
+                    format!("\"{}\" is used by more than one location", location.name),
+                ));
+            }
+            if !(-90.0..=90.0).contains(&location.latitude) {
+                return Err(invalid(key("latitude"), "must be between -90 and 90"));
+            }
+            if !(-180.0..=180.0).contains(&location.longitude) {
+                return Err(invalid(key("longitude"), "must be between -180 and 180"));
+            }
+            if let Err(message) = parse_timezone(&location.timezone) {
+                return Err(invalid(key("timezone"), message));
+            }
+        }
+
+        if let Some(name) = &self.default_location
+            && !self.locations.iter().any(|l| &l.name == name)
+        {
+            return Err(invalid(
+                "default_location",
+                format!("no location named \"{}\"", name),
+            ));
+        }
+
+        for (i, rule) in self.alerts.iter().enumerate() {
+            if rule.name.trim().is_empty() {
+                return Err(invalid(format!("alerts[{}].name", i), "must not be empty"));
+            }
+            if !rule.threshold.is_finite() {
+                return Err(invalid(
+                    format!("alerts[{}].threshold", i),
+                    "must be a number",
+                ));
+            }
+        }
+
+        // Only a consensus that's in use needs enough providers to compare
+        if self.provider == ProviderKind::Consensus && self.consensus.providers.len() < 2 {
+            return Err(invalid(
+                "consensus.providers",
+                "at least two providers are required",
+            ));
+        }
+        for (i, kind) in self.consensus.providers.iter().enumerate() {
+            if *kind == ProviderKind::Consensus {
+                return Err(invalid(
+                    format!("consensus.providers[{}]", i),
+                    "a consensus can't include itself",
+                ));
+            }
+            if self.consensus.providers[..i].contains(kind) {
+                return Err(invalid(
+                    format!("consensus.providers[{}]", i),
+                    format!("\"{}\" is listed more than once", kind.name()),
+                ));
+            }
+        }
+        if !(self.consensus.threshold.is_finite() && self.consensus.threshold > 0.0) {
+            return Err(invalid("consensus.threshold", "must be a positive number"));
+        }
+
+        if let Some(user_agent) = &self.user_agent
+            && user_agent.trim().is_empty()
+        {
+            return Err(invalid("user_agent", "must not be empty"));
+        }
+        if let Some(contact) = &self.contact
+            && contact.trim().is_empty()
+        {
+            return Err(invalid("contact", "must not be empty"));
+        }
+
+        if let Some(feed) = &self.warnings.feed
+            && feed.trim().is_empty()
+        {
+            return Err(invalid("warnings.feed", "must not be empty"));
+        }
+
+        if let Some(command) = &self.hooks.command
+            && command.trim().is_empty()
+        {
+            return Err(invalid("hooks.command", "must not be empty"));
+        }
+        if let Some(url) = &self.hooks.webhook
+            && !(url.starts_with("http://") || url.starts_with("https://"))
+        {
+            return Err(invalid(
+                "hooks.webhook",
+                format!("\"{}\" is not an http(s) URL", url),
+            ));
+        }
+
+        let station = &self.station;
+        let sources = [&station.ecowitt, &station.weewx, &station.file];
+        if sources.iter().filter(|source| source.is_some()).count() > 1 {
+            return Err(invalid(
+                "station",
+                "set only one of ecowitt, weewx and file",
+            ));
+        }
+        if let Some(address) = &station.ecowitt
+            && address.parse::<SocketAddr>().is_err()
+        {
+            return Err(invalid(
+                "station.ecowitt",
+                format!("\"{}\" is not an address such as 0.0.0.0:8090", address),
+            ));
+        }
+        for (key, value) in [("weewx", &station.weewx), ("file", &station.file)] {
+            if let Some(value) = value
+                && value.trim().is_empty()
+            {
+                return Err(invalid(format!("station.{}", key), "must not be empty"));
+            }
+        }
+        if let Some(name) = &station.name
+            && name.trim().is_empty()
+        {
+            return Err(invalid("station.name", "must not be empty"));
+        }
+        if station.max_age == 0 {
+            return Err(invalid("station.max_age", "must be at least 1 minute"));
+        }
+
+        if let Some(host) = &self.mqtt.host
+            && host.trim().is_empty()
+        {
+            return Err(invalid("mqtt.host", "must not be empty"));
+        }
+        if self.mqtt.port == 0 {
+            return Err(invalid("mqtt.port", "must not be 0"));
+        }
+        if self.mqtt.client_id.trim().is_empty() {
+            return Err(invalid("mqtt.client_id", "must not be empty"));
+        }
+        if self.mqtt.password.is_some() && self.mqtt.username.is_none() {
+            return Err(invalid("mqtt.password", "needs a username"));
+        }
+        for (key, topic) in [
+            ("current_topic", &self.mqtt.current_topic),
+            ("today_topic", &self.mqtt.today_topic),
+            ("alerts_topic", &self.mqtt.alerts_topic),
+            ("discovery_prefix", &self.mqtt.discovery_prefix),
+        ] {
+            // Wildcards are only for subscribing
+            if topic.trim().is_empty() || topic.contains(['#', '+']) {
+                return Err(invalid(
+                    format!("mqtt.{}", key),
+                    "must be a topic without wildcards",
+                ));
+            }
+        }
+
+        for (key, value) in self.theme.entries() {
+            if Color::from_str(value).is_err() {
+                return Err(invalid(
+                    format!("theme.{}", key),
+                    format!("\"{}\" is not a colour", value),
+                ));
+            }
+        }
+
+        if let Some(name) = crate::bar::unknown_placeholder(&self.bar.format) {
+            return Err(invalid(
+                "bar.format",
+                format!(
+                    "unknown placeholder {{{}}}, expected one of {}",
+                    name,
+                    crate::bar::PLACEHOLDERS.join(", ")
+                ),
+            ));
+        }
+
+        for (key, value) in [
+            ("open_meteo", &self.endpoints.open_meteo),
+            ("moon_phase", &self.endpoints.moon_phase),
+            ("nws", &self.endpoints.nws),
+            ("met_norway", &self.endpoints.met_norway),
+            ("bright_sky", &self.endpoints.bright_sky),
+            ("air_quality", &self.endpoints.air_quality),
+        ] {
+            if !value.starts_with("http://") && !value.starts_with("https://") {
+                return Err(invalid(
+                    format!("endpoints.{}", key),
+                    "must be an http:// or https:// URL",
+                ));
+            }
+        }
+
+        Ok(())
+    }
+
+    /// User-Agent to send, `user_agent` if set, otherwise Raijin's name and version with
+    /// `contact`
+    /// MET Norway's and the NWS's terms require one that identifies the application and how to
+    /// reach its user, so every service gets the same
+    pub fn user_agent(&self) -> String {
+        if let Some(user_agent) = &self.user_agent {
+            return user_agent.clone();
+        }
+        format!(
+            "Raijin/{} ({})",
+            env!("CARGO_PKG_VERSION"),
+            self.contact
+                .as_deref()
+                .unwrap_or(env!("CARGO_PKG_REPOSITORY"))
+        )
+    }
+
+    /// The Overview's layout, None for the built-in one, and why a configured layout that's
+    /// broken was replaced by the built-in one
+    pub fn overview_layout(&self) -> (Option<OverviewLayout>, Option<String>) {
+        match self.layout.as_ref().map(OverviewLayout::parse) {
+            Some(Ok(layout)) => (Some(layout), None),
+            Some(Err(err)) => (None, Some(err)),
+            None => (None, None),
+        }
+    }
+
+    /// The location to show on start-up
+    pub fn location(&self) -> &Location {
+        self.default_location
+            .as_ref()
+            .and_then(|name| self.locations.iter().find(|l| &l.name == name))
+            .unwrap_or(&self.locations[0])
+    }
+}
+
+/// `$XDG_CONFIG_HOME`, falling back to `~/.config`
+fn config_dir() -> PathBuf {
+    dirs::config_dir().unwrap_or_else(|| {
+        dirs::home_dir()
+            .expect("Could not find home directory")
+            .join(".config")
+    })
+}
+
+/// Asks on stderr for one value, falling back to `default` on an empty answer and asking
+/// again until `parse` accepts it
+fn prompt<T>(
+    question: &str,
+    default: Option<&str>,
+    parse: impl Fn(&str) -> Result<T, String>,
+) -> Result<T, ConfigError> {
+    loop {
+        match default {
+            Some(default) => eprint!("{} [{}]: ", question, default),
+            None => eprint!("{}: ", question),
+        }
+        let _ = io::stderr().flush();
+        let mut answer = String::new();
+        let read = io::stdin()
+            .read_line(&mut answer)
+            .map_err(|err| ConfigError::Io(PathBuf::from("stdin"), err))?;
+        if read == 0 {
+            return Err(invalid("locations", "no location entered"));
+        }
+        let answer = match answer.trim() {
+            "" => default.unwrap_or_default(),
+            answer => answer,
+        };
+        match parse(answer) {
+            Ok(value) => return Ok(value),
+            Err(message) => eprintln!("{}", message),
+        }
+    }
+}
+
+/// Asks for the first location when there's no config yet
+fn prompt_location(path: &Path) -> Result<Location, ConfigError> {
+    eprintln!(
+        "No config at {} yet; enter the location to show the weather for.",
+        path.display()
+    );
+    let name = prompt("Name", Some("Home"), |answer| Ok(answer.to_string()))?;
+    let latitude = prompt("Latitude, e.g. 52.52", None, |answer| {
+        parse_coordinate(answer, 90.0)
+    })?;
+    let longitude = prompt("Longitude, e.g. 13.41", None, |answer| {
+        parse_coordinate(answer, 180.0)
+    })?;
+    let timezone = prompt(
+        "Time zone, e.g. Europe/Berlin",
+        Some("auto"),
+        parse_timezone,
+    )?;
+    Ok(Location {
+        name,
+        latitude,
+        longitude,
+        timezone,
+        warning_area: None,
+        geocodes: Vec::new(),
+    })
+}
+
+/// Where versions up to 0.1.8 kept their settings
+fn legacy_env_path() -> PathBuf {
+    dirs::home_dir()
+        .expect("Could not find home directory")
+        .join(".config")
+        .join("Raijin")
+        .join(".env")
+}
+
+/// Builds a config from a legacy `.env` file
+/// Only the coordinates and time zone carry over; the NWS `ZONE`/`STATE` keys are dropped
+fn migrate_env(path: &Path) -> Result<Config, ConfigError> {
+    let contents =
+        fs::read_to_string(path).map_err(|err| ConfigError::Io(path.to_path_buf(), err))?;
+
+    let mut latitude = None;
+    let mut longitude = None;
+    let mut timezone = None;
+    for line in contents.lines() {
+        let line = line.trim();
+        if line.is_empty() || line.starts_with('#') {
+            continue;
+        }
+        let Some((key, value)) = line.split_once('=') else {
+            continue;
+        };
+        let value = value
+            .trim()
+            .trim_matches('"')
+            .trim_matches('\'')
+            .to_string();
+        match key.trim() {
+            "LATITUDE" => latitude = Some(value),
+            "LONGITUDE" => longitude = Some(value),
+            "TIMEZONE" => timezone = Some(value),
+            _ => {}
+        }
+    }
+
+    let parse = |key: &str, value: Option<String>| -> Result<f64, ConfigError> {
+        let value = value.ok_or_else(|| invalid(key, format!("missing in {}", path.display())))?;
+        value
+            .parse::<f64>()
+            .map_err(|_| invalid(key, format!("\"{}\" is not a number", value)))
+    };
+
+    Ok(Config {
+        locations: vec![Location {
+            name: String::from("Home"),
+            latitude: parse("LATITUDE", latitude)?,
+            longitude: parse("LONGITUDE", longitude)?,
+            timezone: timezone.unwrap_or_else(default_timezone),
+            warning_area: None,
+            geocodes: Vec::new(),
+        }],
+        ..Config::default()
+    })
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    /// Writes `contents` to a file of its own in the temp directory
+    fn env_file(name: &str, contents: &str) -> PathBuf {
+        let path = std::env::temp_dir().join(format!("raijin-{}-{}.env", std::process::id(), name));
+        fs::write(&path, contents).unwrap();
+        path
+    }
+
+    #[test]
+    fn env_coordinates_and_time_zone_carry_over() {
+        let path = env_file(
+            "full",
+            "# Raijin\nLATITUDE=\"35.96\"\nLONGITUDE='-83.92'\n\nTIMEZONE = America/New_York\nZONE=TNZ069\nSTATE=TN\n",
+        );
+        let config = migrate_env(&path).unwrap();
+        fs::remove_file(&path).unwrap();
+
+        assert_eq!(config.locations.len(), 1);
+        let location = &config.locations[0];
+        assert_eq!(location.name, "Home");
+        assert_eq!(location.latitude, 35.96);
+        assert_eq!(location.longitude, -83.92);
+        assert_eq!(location.timezone, "America/New_York");
+        config.validate().unwrap();
+    }
+
+    #[test]
+    fn env_without_time_zone_uses_auto() {
+        let path = env_file("no-timezone", "LATITUDE=52.52\nLONGITUDE=13.41\n");
+        let config = migrate_env(&path).unwrap();
+        fs::remove_file(&path).unwrap();
+
+        assert_eq!(config.locations[0].timezone, "auto");
+    }
+
+    #[test]
+    fn env_errors_name_the_key() {
+        let path = env_file("missing", "LATITUDE=52.52\n");
+        let err = migrate_env(&path).unwrap_err();
+        fs::remove_file(&path).unwrap();
+        assert!(matches!(err, ConfigError::Invalid { ref key, .. } if key == "LONGITUDE"));
+
+        let path = env_file("not-a-number", "LATITUDE=north\nLONGITUDE=13.41\n");
+        let err = migrate_env(&path).unwrap_err();
+        fs::remove_file(&path).unwrap();
+        assert_eq!(
+            err.to_string(),
+            "invalid value for `LATITUDE`: \"north\" is not a number"
+        );
+    }
+
+    #[test]
+    fn coordinates_are_range_checked() {
+        assert_eq!(parse_coordinate("-83.92", 180.0), Ok(-83.92));
+        assert_eq!(parse_coordinate("90", 90.0), Ok(90.0));
+        assert!(parse_coordinate("90.5", 90.0).is_err());
+        assert!(parse_coordinate("east", 180.0).is_err());
+    }
+
+    #[test]
+    fn consensus_providers_only_matter_for_a_consensus() {
+        let mut config = Config::default();
+        config.locations.push(Location {
+            name: String::from("Home"),
+            latitude: 52.52,
+            longitude: 13.41,
+            timezone: String::from("auto"),
+            warning_area: None,
+            geocodes: Vec::new(),
+        });
+        config.consensus.providers = vec![ProviderKind::OpenMeteo];
+        assert!(config.validate().is_ok());
+
+        config.provider = ProviderKind::Consensus;
+        assert_eq!(
+            config.validate().unwrap_err().to_string(),
+            "invalid value for `consensus.providers`: at least two providers are required"
+        );
+    }
+
+    #[test]
+    fn time_zones_are_iana_names_or_auto() {
+        assert_eq!(parse_timezone("auto"), Ok(String::from("auto")));
+        assert_eq!(
+            parse_timezone("America/New_York"),
+            Ok(String::from("America/New_York"))
+        );
+        assert!(parse_timezone("").is_err());
+        assert!(parse_timezone("Berlin").is_err());
+
+        let mut config = Config::default();
+        config.locations.push(Location {
+            name: String::from("Home"),
+            latitude: 52.52,
+            longitude: 13.41,
+            timezone: String::from("Europe/Berln"),
+            warning_area: None,
+            geocodes: Vec::new(),
+        });
+        assert_eq!(
+            config.validate().unwrap_err().to_string(),
+            "invalid value for `locations[0].timezone`: \"Europe/Berln\" is not an IANA time \
+             zone such as Europe/Berlin, or \"auto\""
+        );
+        config.locations[0].timezone = String::from("Europe/Berlin");
+        assert!(config.validate().is_ok());
+    }
+
+    #[test]
+    fn user_agent_identifies_raijin_and_its_user() {
+        let mut config = Config::default();
+        let version = env!("CARGO_PKG_VERSION");
+        assert_eq!(
+            config.user_agent(),
+            format!("Raijin/{} ({})", version, env!("CARGO_PKG_REPOSITORY"))
+        );
+
+        config.contact = Some(String::from("me@example.com"));
+        assert_eq!(
+            config.user_agent(),
+            format!("Raijin/{} (me@example.com)", version)
+        );
+
+        config.user_agent = Some(String::from("myweather/1.0"));
+        assert_eq!(config.user_agent(), "myweather/1.0");
+    }
+
+    /// The layout and warning the TUI starts with for a config's `[layout]`
+    fn layout(toml: &str) -> (Option<OverviewLayout>, Option<String>) {
+        let config = Config {
+            layout: Some(toml::from_str(toml).unwrap()),
+            ..Config::default()
+        };
+        config.overview_layout()
+    }
+
+    #[test]
+    fn custom_layouts_are_used() {
+        let (layout, warning) = layout(
+            r#"
+            rows = [
+                { size = 2, columns = [{ panel = "right_now" }, { panel = "today_chart" }] },
+                { panel = "forecast" },
+            ]
+            "#,
+        );
+        assert_eq!(warning, None);
+        assert_eq!(
+            layout,
+            Some(OverviewLayout::rows(
+                1,
+                vec![
+                    OverviewLayout::columns(
+                        2,
+                        vec![
+                            OverviewLayout::panel(1, Panel::RightNow),
+                            OverviewLayout::panel(1, Panel::TodayChart),
+                        ],
+                    ),
+                    OverviewLayout::panel(1, Panel::Forecast),
+                ],
+            ))
+        );
+        assert_eq!(Config::default().overview_layout(), (None, None));
+    }
+
+    #[test]
+    fn broken_layouts_fall_back_with_a_warning() {
+        let unknown = layout(r#"rows = [{ panel = "radar" }, { panel = "forecast" }]"#);
+        assert_eq!(unknown.0, None);
+        assert!(
+            unknown
+                .1
+                .is_some_and(|warning| warning.starts_with("layout: unknown variant `radar`"))
+        );
+
+        let duplicate = layout(r#"rows = [{ panel = "forecast" }, { panel = "forecast" }]"#);
+        assert_eq!(duplicate.0, None);
+        assert_eq!(
+            duplicate.1.as_deref(),
+            Some("layout.rows[1]: forecast appears more than once")
+        );
+
+        let empty = layout(r#"rows = [{ columns = [] }, { panel = "forecast" }]"#);
+        assert_eq!(empty.0, None);
+        assert_eq!(
+            empty.1.as_deref(),
+            Some("layout.rows[0]: needs exactly one of panel, rows or columns")
+        );
+    }
+}
@@ -0,0 +1,150 @@
+use crate::alerts::Alerts;
+use crate::config::{Config, Theme};
+use crate::forecast::Forecast;
+use crate::{degrees, get_day_from_date, gusts, percent, weather_icon};
+use ratatui::{
+    Frame,
+    layout::{Constraint, Rect},
+    style::{Style, Stylize},
+    text::Line,
+    widgets::{Block, Cell, Padding, Row, Table, TableState},
+};
+
+/// "HH:MM" of a local time, or "–" for providers without sun times and polar day and night
+fn clock(datetime: &str) -> &str {
+    datetime.get(11..16).unwrap_or("\u{2013}")
+}
+
+/// A narrow list of every day, for the Overview's sidebar on large terminals
+pub fn create_summary_table<'a>(
+    forecast: &Forecast,
+    alerts: &Alerts,
+    config: &Config,
+) -> Table<'a> {
+    let theme = &config.theme;
+    let rows: Vec<Row> = forecast
+        .periods
+        .iter()
+        .enumerate()
+        .map(|(i, period)| {
+            let row = Row::new(vec![
+                Cell::from(get_day_from_date(&period.date).unwrap_or_default()),
+                Cell::from(period.date.get(5..).unwrap_or_default().to_string()),
+                Cell::from(weather_icon(period.weather_code)),
+                Cell::from(degrees(period.temperature_max)),
+                Cell::from(degrees(period.temperature_min)),
+                Cell::from(percent(period.precipitation_probability)),
+            ]);
+            if alerts.days.get(i).is_some_and(|names| !names.is_empty()) {
+                row.fg(Theme::color(&theme.alert))
+            } else {
+                row
+            }
+        })
+        .collect();
+    let widths = [
+        Constraint::Length(3),
+        Constraint::Length(5),
+        Constraint::Length(2),
+        Constraint::Length(6),
+        Constraint::Length(6),
+        Constraint::Length(4),
+    ];
+    Table::new(rows, widths)
+        .header(Row::new(["Day", "Date", "", "High", "Low", "Rain"]).bold())
+        .column_spacing(1)
+        .block(
+            Block::bordered()
+                .title(
+                    Line::from(" Days ")
+                        .fg(Theme::color(&theme.forecast))
+                        .centered()
+                        .bold(),
+                )
+                .padding(Padding::horizontal(1)),
+        )
+}
+
+/// Renders one row per day of the forecast, with the selected day highlighted
+pub fn render(
+    frame: &mut Frame,
+    area: Rect,
+    forecast: &Forecast,
+    selected: usize,
+    alerts: &Alerts,
+    config: &Config,
+) {
+    let theme = &config.theme;
+    let header = Row::new([
+        "Day", "Date", "Weather", "High", "Low", "Feels", "Rain", "Gusts", "Sunrise", "Sunset",
+        "Alerts",
+    ])
+    .bold();
+    let rows: Vec<Row> = forecast
+        .periods
+        .iter()
+        .enumerate()
+        .map(|(i, period)| {
+            let alert_names = alerts.days.get(i).map_or(&[][..], Vec::as_slice);
+            Row::new(vec![
+                Cell::from(get_day_from_date(&period.date).unwrap_or_default()),
+                Cell::from(period.date.clone()),
+                Cell::from(format!(
+                    "{} {}",
+                    weather_icon(period.weather_code),
+                    period.weather
+                )),
+                Cell::from(degrees(period.temperature_max)),
+                Cell::from(degrees(period.temperature_min)),
+                Cell::from(format!(
+                    "{}/{}",
+                    degrees(period.apparent_temperature_max),
+                    degrees(period.apparent_temperature_min)
+                )),
+                Cell::from(percent(period.precipitation_probability)),
+                Cell::from(gusts(period.wind_gusts_max)),
+                Cell::from(clock(&period.sunrise).to_string()),
+                Cell::from(clock(&period.sunset).to_string()),
+                Cell::from(alert_names.join(", ")).fg(Theme::color(&theme.alert)),
+            ])
+        })
+        .collect();
+    let widths = [
+        Constraint::Length(3),
+        Constraint::Length(10),
+        Constraint::Fill(2),
+        Constraint::Length(6),
+        Constraint::Length(6),
+        Constraint::Length(13),
+        Constraint::Length(4),
+        Constraint::Length(5),
+        Constraint::Length(7),
+        Constraint::Length(6),
+        Constraint::Fill(1),
+    ];
+    let table = Table::new(rows, widths)
+        .header(header)
+        .column_spacing(1)
+        .row_highlight_style(Style::new().fg(Theme::color(&theme.selection)).reversed())
+        .block(
+            Block::bordered()
+                .title(
+                    Line::from(format!(
+                        " Daily (gusts in {}) ",
+                        config.units.wind_speed.symbol()
+                    ))
+                    .fg(Theme::color(&theme.forecast))
+                    .centered()
+                    .bold(),
+                )
+                .title_bottom(
+                    Line::from(" \u{2191}/\u{2193} day \u{b7} Enter details ")
+                        .centered()
+                        .dim(),
+                )
+                .padding(Padding::horizontal(1)),
+        );
+    // The table scrolls just far enough to keep the selected day in view
+    let mut state = TableState::default().with_selected(Some(selected));
+    frame.render_stateful_widget(table, area, &mut state);
+}
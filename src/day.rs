@@ -0,0 +1,369 @@
+use crate::alerts::Alerts;
+use crate::config::{Config, Theme};
+use crate::forecast::{Forecast, Hour, Period};
+use crate::{degrees, get_day_from_date, gusts, percent, weather_icon};
+use ratatui::{
+    Frame,
+    layout::{Constraint, Layout, Rect},
+    style::{Color, Style, Stylize},
+    symbols::Marker,
+    text::{Line, Span},
+    widgets::{
+        Axis, Block, Cell, Chart, Dataset, GraphType, LegendPosition, Padding, Paragraph, Row,
+        Table, Wrap,
+    },
+};
+
+/// Hour of the day of a local time (YYYY-MM-DDTHH:MM), for the charts' x axis
+fn hour_of_day(datetime: &str) -> f64 {
+    datetime
+        .get(11..13)
+        .and_then(|hour| hour.parse().ok())
+        .unwrap_or_default()
+}
+
+/// "HH:MM" of a local time, or "–" for the empty times of polar day and night
+fn clock(datetime: &str) -> &str {
+    datetime.get(11..16).unwrap_or("\u{2013}")
+}
+
+/// Axis labels for the y range, padded by `margin` and never below `floor`
+fn bounds(values: &[f64], margin: f64, floor: Option<f64>) -> ([f64; 2], Vec<String>) {
+    let min = values.iter().cloned().fold(f64::INFINITY, f64::min);
+    let max = values.iter().cloned().fold(f64::NEG_INFINITY, f64::max);
+    let (mut low, high) = if values.is_empty() {
+        (0.0, 1.0)
+    } else {
+        ((min - margin).floor(), (max + margin).ceil())
+    };
+    if let Some(floor) = floor {
+        low = low.max(floor);
+    }
+    let step = (high - low) / 2.0;
+    let labels = (0..3)
+        .map(|i| format!("{:.0}", low + i as f64 * step))
+        .collect();
+    ([low, high], labels)
+}
+
+/// A chart of one hourly value across the day, with the hours on the x axis
+fn hourly_chart<'a>(
+    title: String,
+    datasets: Vec<Dataset<'a>>,
+    y_bounds: ([f64; 2], Vec<String>),
+) -> Chart<'a> {
+    let ([low, high], labels) = y_bounds;
+    Chart::new(datasets)
+        .block(Block::bordered().title(Line::from(title).centered().bold()))
+        .y_axis(
+            Axis::default()
+                .bounds([low, high])
+                .style(Style::default().fg(Color::Gray))
+                .labels(labels),
+        )
+        .x_axis(
+            Axis::default()
+                .bounds([0., 23.])
+                .style(Style::default().fg(Color::Gray))
+                .labels(["00:00", "06:00", "12:00", "18:00", "23:00"]),
+        )
+        .legend_position(Some(LegendPosition::TopLeft))
+        .hidden_legend_constraints((Constraint::Ratio(1, 2), Constraint::Ratio(1, 2)))
+}
+
+/// Summary of the day: extremes with the hours they occur at, sun times, alerts and narrative
+/// Returns the paragraph and its height when wrapped to `width`
+fn create_summary<'a>(
+    period: &'a Period,
+    hours: &[&Hour],
+    alert_names: &[String],
+    width: u16,
+    config: &Config,
+) -> (Paragraph<'a>, u16) {
+    let units = &config.units;
+    let at = |hour: Option<&&Hour>| {
+        hour.map_or(String::new(), |hour| {
+            format!(" at {}", clock(&hour.datetime))
+        })
+    };
+    let warmest = hours
+        .iter()
+        .max_by(|a, b| a.temperature.total_cmp(&b.temperature));
+    let coldest = hours
+        .iter()
+        .min_by(|a, b| a.temperature.total_cmp(&b.temperature));
+    let gustiest = hours
+        .iter()
+        .filter_map(|hour| Some((hour, hour.wind_gusts?)))
+        .max_by(|a, b| a.1.total_cmp(&b.1))
+        .map(|(hour, _)| hour);
+
+    let row = |label: &'a str, value: String| {
+        Line::from(vec![
+            Span::raw(format!("{:<16}", label)).bold(),
+            Span::raw(value),
+        ])
+    };
+    let mut lines = vec![
+        row(
+            "Weather:",
+            format!("{} {}", weather_icon(period.weather_code), period.weather),
+        ),
+        row(
+            "High:",
+            format!(
+                "{}{} (feels like {})",
+                degrees(period.temperature_max),
+                at(warmest),
+                degrees(period.apparent_temperature_max)
+            ),
+        ),
+        row(
+            "Low:",
+            format!(
+                "{}{} (feels like {})",
+                degrees(period.temperature_min),
+                at(coldest),
+                degrees(period.apparent_temperature_min)
+            ),
+        ),
+        row("Chance of Rain:", percent(period.precipitation_probability)),
+        row(
+            "Wind Gusts:",
+            match period.wind_gusts_max {
+                Some(max) => format!(
+                    "up to {:.0} {}{}",
+                    max,
+                    units.wind_speed.symbol(),
+                    at(gustiest)
+                ),
+                None => gusts(None),
+            },
+        ),
+        row(
+            "Sunrise/Sunset:",
+            format!("{} / {}", clock(&period.sunrise), clock(&period.sunset)),
+        ),
+    ];
+    if !alert_names.is_empty() {
+        lines.push(
+            row("Alerts:", alert_names.join(", "))
+                .style(Style::new().fg(Theme::color(&config.theme.alert))),
+        );
+    }
+    if !period.narrative.is_empty() {
+        lines.push(Line::default());
+        lines.extend(period.narrative.lines().map(|line| Line::from(line).dim()));
+    }
+    let width = usize::from(width.max(1));
+    let height: usize = lines
+        .iter()
+        .map(|line| line.width().max(1).div_ceil(width))
+        .sum();
+    (
+        Paragraph::new(lines).wrap(Wrap { trim: true }),
+        height as u16 + 1,
+    )
+}
+
+/// Table with one row per hour of the day
+fn create_hourly_table<'a>(hours: &[&Hour], config: &Config) -> Table<'a> {
+    let header = Row::new(["Time", "Weather", "Temp", "Feels", "Rain", "Gusts"]).bold();
+    let rows: Vec<Row> = hours
+        .iter()
+        .map(|hour| {
+            Row::new(vec![
+                Cell::from(clock(&hour.datetime).to_string()),
+                Cell::from(format!(
+                    "{} {}",
+                    weather_icon(hour.weather_code),
+                    hour.weather
+                )),
+                Cell::from(degrees(hour.temperature)),
+                Cell::from(degrees(hour.apparent_temperature)),
+                Cell::from(percent(hour.precipitation_probability)),
+                Cell::from(gusts(hour.wind_gusts)),
+            ])
+        })
+        .collect();
+    let widths = [
+        Constraint::Length(5),
+        Constraint::Fill(1),
+        Constraint::Length(6),
+        Constraint::Length(6),
+        Constraint::Length(5),
+        Constraint::Length(5),
+    ];
+    Table::new(rows, widths)
+        .header(header)
+        .column_spacing(1)
+        .block(
+            Block::bordered().title(
+                Line::from(format!(
+                    " Hourly (gusts in {}) ",
+                    config.units.wind_speed.symbol()
+                ))
+                .centered()
+                .bold(),
+            ),
+        )
+}
+
+/// Renders the drill-down view of the day at `index` of the forecast's periods
+pub fn render(
+    frame: &mut Frame,
+    area: Rect,
+    forecast: &Forecast,
+    index: usize,
+    alerts: &Alerts,
+    config: &Config,
+) {
+    let theme = &config.theme;
+    let Some(period) = forecast.periods.get(index) else {
+        return;
+    };
+    let alert_names = alerts.days.get(index).map_or(&[][..], Vec::as_slice);
+    let hours: Vec<&Hour> = forecast
+        .hourly
+        .iter()
+        .filter(|hour| hour.datetime.starts_with(&period.date))
+        .collect();
+    let alert_hours: Vec<bool> = forecast
+        .hourly
+        .iter()
+        .zip(alerts.hours.iter().chain(std::iter::repeat(&false)))
+        .filter(|(hour, _)| hour.datetime.starts_with(&period.date))
+        .map(|(_, alert)| *alert)
+        .collect();
+
+    let block = Block::bordered()
+        .title(
+            Line::from(format!(
+                " {} {} ",
+                get_day_from_date(&period.date).unwrap_or_default(),
+                period.date
+            ))
+            .fg(Theme::color(&theme.forecast))
+            .centered()
+            .bold(),
+        )
+        .title_bottom(
+            Line::from(" \u{2190}/\u{2192} day \u{b7} Esc back ")
+                .centered()
+                .dim(),
+        )
+        .padding(Padding::horizontal(1));
+    let inner = block.inner(area);
+    frame.render_widget(block, area);
+
+    let [left, right] =
+        Layout::horizontal([Constraint::Ratio(3, 5), Constraint::Ratio(2, 5)]).areas(inner);
+    let (summary, summary_height) = create_summary(period, &hours, alert_names, left.width, config);
+    let [summary_area, charts_area] =
+        Layout::vertical([Constraint::Length(summary_height), Constraint::Fill(1)]).areas(left);
+    frame.render_widget(summary, summary_area);
+
+    if hours.is_empty() {
+        // e.g. MET Norway only forecasts the first days hour by hour
+        frame.render_widget(
+            Paragraph::new("No hourly forecast for this day")
+                .centered()
+                .dim(),
+            charts_area,
+        );
+        return;
+    }
+    frame.render_widget(create_hourly_table(&hours, config), right);
+
+    // Hours the provider has no value for are left out of the chart
+    let point = |value: fn(&Hour) -> Option<f32>| -> Vec<(f64, f64)> {
+        hours
+            .iter()
+            .filter_map(|hour| Some((hour_of_day(&hour.datetime), value(hour)? as f64)))
+            .collect()
+    };
+    let temperatures = point(|hour| Some(hour.temperature));
+    let apparent = point(|hour| Some(hour.apparent_temperature));
+    let rain = point(|hour| hour.precipitation_probability.map(|value| value as f32));
+    let gust_points = point(|hour| hour.wind_gusts);
+    let alert_points: Vec<(f64, f64)> = temperatures
+        .iter()
+        .zip(&alert_hours)
+        .filter(|(_, alert)| **alert)
+        .map(|(point, _)| *point)
+        .collect();
+
+    let [temperature_area, rain_area, wind_area] = Layout::vertical([
+        Constraint::Ratio(2, 4),
+        Constraint::Ratio(1, 4),
+        Constraint::Ratio(1, 4),
+    ])
+    .areas(charts_area);
+
+    let values: Vec<f64> = temperatures
+        .iter()
+        .chain(&apparent)
+        .map(|(_, value)| *value)
+        .collect();
+    let data = Theme::color(&theme.data);
+    frame.render_widget(
+        hourly_chart(
+            format!(" Temperature ({}) ", config.units.temperature.symbol()),
+            vec![
+                Dataset::default()
+                    .name("feels like")
+                    .marker(Marker::Braille)
+                    .graph_type(GraphType::Line)
+                    .style(Style::new().fg(Color::Gray))
+                    .data(&apparent),
+                Dataset::default()
+                    .name("temperature")
+                    .marker(Marker::Braille)
+                    .graph_type(GraphType::Line)
+                    .style(Style::new().fg(data))
+                    .data(&temperatures),
+                Dataset::default()
+                    .marker(Marker::Dot)
+                    .graph_type(GraphType::Scatter)
+                    .style(Style::new().fg(Theme::color(&theme.alert)))
+                    .data(&alert_points),
+            ],
+            bounds(&values, 2.0, None),
+        ),
+        temperature_area,
+    );
+
+    frame.render_widget(
+        hourly_chart(
+            String::from(" Chance of Rain (%) "),
+            vec![
+                Dataset::default()
+                    .marker(Marker::Braille)
+                    .graph_type(GraphType::Bar)
+                    .style(Style::new().fg(Color::LightBlue))
+                    .data(&rain),
+            ],
+            (
+                [0.0, 100.0],
+                vec![String::from("0"), String::from("50"), String::from("100")],
+            ),
+        ),
+        rain_area,
+    );
+
+    let gust_values: Vec<f64> = gust_points.iter().map(|(_, value)| *value).collect();
+    frame.render_widget(
+        hourly_chart(
+            format!(" Wind Gusts ({}) ", config.units.wind_speed.symbol()),
+            vec![
+                Dataset::default()
+                    .marker(Marker::Braille)
+                    .graph_type(GraphType::Line)
+                    .style(Style::new().fg(data))
+                    .data(&gust_points),
+            ],
+            bounds(&gust_values, 5.0, Some(0.0)),
+        ),
+        wind_area,
+    );
+}
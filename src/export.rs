@@ -0,0 +1,318 @@
+mod csv;
+mod ics;
+mod parquet;
+
+use crate::cli::{ExportArgs, ExportFormat};
+use crate::config::Config;
+use crate::forecast::Forecast;
+use chrono::{DateTime, FixedOffset, NaiveDate, NaiveDateTime, TimeZone};
+use std::fs::{self, File};
+use std::io::{self, BufWriter, IsTerminal, Write};
+use std::path::PathBuf;
+use std::process;
+
+/// Values of one exported column
+enum Values {
+    Date(Vec<NaiveDate>),
+    /// Missing for e.g. sunrise during polar night
+    Timestamp(Vec<Option<DateTime<FixedOffset>>>),
+    Float(Vec<f32>),
+    Integer(Vec<i32>),
+    /// Missing where the provider doesn't report the value
+    OptionalFloat(Vec<Option<f32>>),
+    OptionalInteger(Vec<Option<i32>>),
+    Text(Vec<String>),
+}
+
+/// Named column of a `Table`
+struct Column {
+    name: &'static str,
+    values: Values,
+}
+
+/// Column-oriented view of the daily or hourly forecast, shared by the CSV and Parquet writers
+struct Table {
+    /// "daily" or "hourly"
+    name: &'static str,
+    /// IANA time zone of the timestamps
+    timezone: String,
+    columns: Vec<Column>,
+    rows: usize,
+}
+
+/// Attaches the UTC offset to a local "YYYY-MM-DDTHH:MM" time from the forecast
+/// Uses the IANA zone when known so days around DST changes get the right offset
+fn local_time(forecast: &Forecast, local: &str) -> Option<DateTime<FixedOffset>> {
+    let naive = NaiveDateTime::parse_from_str(local, "%Y-%m-%dT%H:%M").ok()?;
+    if let Ok(timezone) = forecast.timezone.parse::<chrono_tz::Tz>()
+        && let Some(time) = timezone.from_local_datetime(&naive).earliest()
+    {
+        return Some(time.fixed_offset());
+    }
+    FixedOffset::east_opt(forecast.utc_offset_seconds)?
+        .from_local_datetime(&naive)
+        .single()
+}
+
+fn parse_date(date: &str) -> io::Result<NaiveDate> {
+    NaiveDate::parse_from_str(date, "%Y-%m-%d").map_err(|err| {
+        io::Error::new(
+            io::ErrorKind::InvalidData,
+            format!("the forecast has an invalid date {:?}: {}", date, err),
+        )
+    })
+}
+
+fn daily_table(forecast: &Forecast) -> io::Result<Table> {
+    let periods = &forecast.periods;
+    let dates = periods
+        .iter()
+        .map(|p| parse_date(&p.date))
+        .collect::<io::Result<_>>()?;
+    let column = |name, values| Column { name, values };
+    Ok(Table {
+        name: "daily",
+        timezone: forecast.timezone.clone(),
+        rows: periods.len(),
+        columns: vec![
+            column("date", Values::Date(dates)),
+            column(
+                "weather_code",
+                Values::Integer(periods.iter().map(|p| p.weather_code).collect()),
+            ),
+            column(
+                "weather",
+                Values::Text(periods.iter().map(|p| p.weather.clone()).collect()),
+            ),
+            column(
+                "temperature_max",
+                Values::Float(periods.iter().map(|p| p.temperature_max).collect()),
+            ),
+            column(
+                "temperature_min",
+                Values::Float(periods.iter().map(|p| p.temperature_min).collect()),
+            ),
+            column(
+                "apparent_temperature_max",
+                Values::Float(periods.iter().map(|p| p.apparent_temperature_max).collect()),
+            ),
+            column(
+                "apparent_temperature_min",
+                Values::Float(periods.iter().map(|p| p.apparent_temperature_min).collect()),
+            ),
+            column(
+                "precipitation_probability",
+                Values::OptionalInteger(
+                    periods
+                        .iter()
+                        .map(|p| p.precipitation_probability)
+                        .collect(),
+                ),
+            ),
+            column(
+                "wind_gusts_max",
+                Values::OptionalFloat(periods.iter().map(|p| p.wind_gusts_max).collect()),
+            ),
+            column(
+                "sunrise",
+                Values::Timestamp(
+                    periods
+                        .iter()
+                        .map(|p| local_time(forecast, &p.sunrise))
+                        .collect(),
+                ),
+            ),
+            column(
+                "sunset",
+                Values::Timestamp(
+                    periods
+                        .iter()
+                        .map(|p| local_time(forecast, &p.sunset))
+                        .collect(),
+                ),
+            ),
+            column(
+                "narrative",
+                Values::Text(periods.iter().map(|p| p.narrative.clone()).collect()),
+            ),
+        ],
+    })
+}
+
+fn hourly_table(forecast: &Forecast) -> Table {
+    let hourly = &forecast.hourly;
+    let column = |name, values| Column { name, values };
+    Table {
+        name: "hourly",
+        timezone: forecast.timezone.clone(),
+        rows: hourly.len(),
+        columns: vec![
+            column(
+                "time",
+                Values::Timestamp(
+                    hourly
+                        .iter()
+                        .map(|h| local_time(forecast, &h.datetime))
+                        .collect(),
+                ),
+            ),
+            column(
+                "temperature",
+                Values::Float(hourly.iter().map(|h| h.temperature).collect()),
+            ),
+            column(
+                "apparent_temperature",
+                Values::Float(hourly.iter().map(|h| h.apparent_temperature).collect()),
+            ),
+            column(
+                "precipitation_probability",
+                Values::OptionalInteger(
+                    hourly.iter().map(|h| h.precipitation_probability).collect(),
+                ),
+            ),
+            column(
+                "precipitation",
+                Values::Float(hourly.iter().map(|h| h.precipitation).collect()),
+            ),
+            column(
+                "wind_speed",
+                Values::Float(hourly.iter().map(|h| h.wind_speed).collect()),
+            ),
+            column(
+                "wind_gusts",
+                Values::OptionalFloat(hourly.iter().map(|h| h.wind_gusts).collect()),
+            ),
+            column(
+                "humidity",
+                Values::OptionalInteger(hourly.iter().map(|h| h.humidity).collect()),
+            ),
+            column(
+                "weather_code",
+                Values::Integer(hourly.iter().map(|h| h.weather_code).collect()),
+            ),
+            column(
+                "weather",
+                Values::Text(hourly.iter().map(|h| h.weather.clone()).collect()),
+            ),
+        ],
+    }
+}
+
+/// Writes the forecast in the requested format to `--output`, or stdout if it isn't given
+pub fn run(forecast: &Forecast, config: &Config, args: &ExportArgs) -> io::Result<()> {
+    if args.format == ExportFormat::Ics && args.hourly {
+        return Err(io::Error::new(
+            io::ErrorKind::InvalidInput,
+            "iCalendar export only supports the daily forecast",
+        ));
+    }
+    if args.format == ExportFormat::Parquet && args.output.is_none() && io::stdout().is_terminal() {
+        return Err(io::Error::new(
+            io::ErrorKind::InvalidInput,
+            "Parquet is binary; pass --output or redirect stdout to a file",
+        ));
+    }
+
+    let Some(path) = &args.output else {
+        let mut out = BufWriter::new(io::stdout());
+        write(&mut out, forecast, config, args)?;
+        return out.flush();
+    };
+
+    // Written next to `--output` and renamed into place, so a failed export doesn't leave a
+    // half-written file behind
+    let mut temporary = path.clone().into_os_string();
+    temporary.push(format!(".{}", process::id()));
+    let temporary = PathBuf::from(temporary);
+    let result = File::create(&temporary)
+        .and_then(|file| {
+            let mut out = BufWriter::new(file);
+            write(&mut out, forecast, config, args)?;
+            out.flush()
+        })
+        .and_then(|()| fs::rename(&temporary, path));
+    if result.is_err() {
+        let _ = fs::remove_file(&temporary);
+    }
+    result
+}
+
+fn write(
+    out: &mut (impl Write + Send),
+    forecast: &Forecast,
+    config: &Config,
+    args: &ExportArgs,
+) -> io::Result<()> {
+    let table = || {
+        if args.hourly {
+            Ok(hourly_table(forecast))
+        } else {
+            daily_table(forecast)
+        }
+    };
+
+    match args.format {
+        ExportFormat::Ics => ics::write(out, forecast, config, args.sun_events),
+        ExportFormat::Csv => csv::write(out, &table()?),
+        ExportFormat::Parquet => parquet::write(out, &table()?),
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::forecast::fixtures::period;
+
+    #[test]
+    fn local_times_follow_dst_changes() {
+        let forecast = Forecast {
+            timezone: String::from("Europe/Berlin"),
+            utc_offset_seconds: 7200,
+            ..Forecast::default()
+        };
+        let time = |local| local_time(&forecast, local).map(|time| time.to_rfc3339());
+        assert_eq!(
+            time("2026-10-24T12:00").as_deref(),
+            Some("2026-10-24T12:00:00+02:00")
+        );
+        assert_eq!(
+            time("2026-10-25T12:00").as_deref(),
+            Some("2026-10-25T12:00:00+01:00")
+        );
+        // The repeated hour is taken the first time round; the skipped one falls back to the
+        // offset at the time of the fetch
+        assert_eq!(
+            time("2026-10-25T02:30").as_deref(),
+            Some("2026-10-25T02:30:00+02:00")
+        );
+        assert_eq!(
+            time("2026-03-29T02:30").as_deref(),
+            Some("2026-03-29T02:30:00+02:00")
+        );
+        assert_eq!(time("sometime"), None);
+
+        // Without a known zone the offset at the time of the fetch is used
+        let forecast = Forecast {
+            timezone: String::from("GMT+2"),
+            ..forecast
+        };
+        assert_eq!(
+            local_time(&forecast, "2026-10-25T12:00").map(|time| time.to_rfc3339()),
+            Some(String::from("2026-10-25T12:00:00+02:00"))
+        );
+    }
+
+    #[test]
+    fn invalid_dates_fail_the_export() {
+        let mut forecast = Forecast {
+            periods: vec![period("2026-10-18", 4.0, 12.0)],
+            ..Forecast::default()
+        };
+        assert!(daily_table(&forecast).is_ok());
+
+        forecast.periods.push(period("Sunday", 5.0, 13.0));
+        let err = daily_table(&forecast).err().unwrap();
+        assert_eq!(err.kind(), io::ErrorKind::InvalidData);
+        assert!(err.to_string().contains("\"Sunday\""));
+    }
+}
@@ -0,0 +1,82 @@
+use super::{Table, Values};
+use std::io::{self, Write};
+
+/// Writes the table as CSV with a header row
+/// Dates are ISO 8601 (YYYY-MM-DD) and timestamps RFC 3339 with the location's UTC offset
+pub fn write(out: &mut impl Write, table: &Table) -> io::Result<()> {
+    let mut writer = ::csv::Writer::from_writer(out);
+    writer.write_record(table.columns.iter().map(|c| c.name))?;
+
+    for row in 0..table.rows {
+        let record = table.columns.iter().map(|column| match &column.values {
+            Values::Date(values) => values[row].format("%Y-%m-%d").to_string(),
+            Values::Timestamp(values) => values[row]
+                .map(|time| time.to_rfc3339())
+                .unwrap_or_default(),
+            Values::Float(values) => values[row].to_string(),
+            Values::Integer(values) => values[row].to_string(),
+            Values::OptionalFloat(values) => values[row]
+                .map(|value| value.to_string())
+                .unwrap_or_default(),
+            Values::OptionalInteger(values) => values[row]
+                .map(|value| value.to_string())
+                .unwrap_or_default(),
+            Values::Text(values) => values[row].clone(),
+        });
+        writer.write_record(record)?;
+    }
+
+    writer.flush()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::export::Column;
+    use chrono::{DateTime, NaiveDate};
+
+    #[test]
+    fn rows_follow_a_header() {
+        let table = Table {
+            name: "daily",
+            timezone: String::from("Europe/Berlin"),
+            rows: 2,
+            columns: vec![
+                Column {
+                    name: "date",
+                    values: Values::Date(vec![
+                        NaiveDate::from_ymd_opt(2026, 10, 24).unwrap(),
+                        NaiveDate::from_ymd_opt(2026, 10, 25).unwrap(),
+                    ]),
+                },
+                Column {
+                    name: "sunrise",
+                    values: Values::Timestamp(vec![
+                        DateTime::parse_from_rfc3339("2026-10-24T08:01:00+02:00").ok(),
+                        None,
+                    ]),
+                },
+                Column {
+                    name: "temperature_max",
+                    values: Values::Float(vec![12.5, 9.0]),
+                },
+                Column {
+                    name: "humidity",
+                    values: Values::OptionalInteger(vec![None, Some(80)]),
+                },
+                Column {
+                    name: "weather",
+                    values: Values::Text(vec![String::from("Rain, heavy"), String::from("Clear")]),
+                },
+            ],
+        };
+        let mut out = Vec::new();
+        write(&mut out, &table).unwrap();
+        assert_eq!(
+            String::from_utf8(out).unwrap(),
+            "date,sunrise,temperature_max,humidity,weather\n\
+             2026-10-24,2026-10-24T08:01:00+02:00,12.5,,\"Rain, heavy\"\n\
+             2026-10-25,,9,80,Clear\n"
+        );
+    }
+}
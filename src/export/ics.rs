@@ -0,0 +1,191 @@
+use super::local_time;
+use crate::config::Config;
+use crate::forecast::{Forecast, Period};
+use crate::{degrees, percent, weather_icon};
+use chrono::{Duration, NaiveDate, Utc};
+use std::io::{self, Write};
+
+/// Longest line RFC 5545 allows, in octets, before it has to be folded
+const MAX_LINE_LENGTH: usize = 75;
+
+/// Escapes TEXT values (RFC 5545, section 3.3.11)
+fn escape(text: &str) -> String {
+    text.replace('\\', "\\\\")
+        .replace(';', "\\;")
+        .replace(',', "\\,")
+        .replace('\n', "\\n")
+}
+
+/// Writes a content line, folding it so no line exceeds 75 octets
+/// Folds only between characters so multi-byte symbols like ☁ stay intact
+fn write_line(out: &mut impl Write, line: &str) -> io::Result<()> {
+    let mut length = 0;
+    for c in line.chars() {
+        if length + c.len_utf8() > MAX_LINE_LENGTH {
+            out.write_all(b"\r\n ")?;
+            // The leading space of a continuation line counts towards its length
+            length = 1;
+        }
+        write!(out, "{}", c)?;
+        length += c.len_utf8();
+    }
+    out.write_all(b"\r\n")
+}
+
+/// Short summary such as "☁ 12°/4° 40% rain"
+fn summary(period: &Period) -> String {
+    format!(
+        "{} {}/{} {} rain",
+        weather_icon(period.weather_code),
+        degrees(period.temperature_max),
+        degrees(period.temperature_min),
+        percent(period.precipitation_probability)
+    )
+}
+
+/// Converts a local "YYYY-MM-DDTHH:MM" time from the forecast into an iCalendar UTC time
+fn to_utc(forecast: &Forecast, local: &str) -> Option<String> {
+    let utc = local_time(forecast, local)?.with_timezone(&Utc);
+    Some(utc.format("%Y%m%dT%H%M%SZ").to_string())
+}
+
+/// Identifies the location in UIDs, so each location gets its own set of events
+fn location_id(config: &Config) -> String {
+    let location = config.location();
+    format!("{:.4}_{:.4}", location.latitude, location.longitude)
+}
+
+/// Writes the daily forecast as an iCalendar file with one all-day event per day
+/// UIDs only depend on the location, the date and the event kind, so re-importing or
+/// re-subscribing updates the existing events instead of duplicating them
+pub fn write(
+    out: &mut impl Write,
+    forecast: &Forecast,
+    config: &Config,
+    sun_events: bool,
+) -> io::Result<()> {
+    let location_id = location_id(config);
+    let stamp = Utc::now().format("%Y%m%dT%H%M%SZ").to_string();
+
+    write_line(out, "BEGIN:VCALENDAR")?;
+    write_line(out, "VERSION:2.0")?;
+    write_line(out, "PRODID:-//Raijin//Weather forecast//EN")?;
+    write_line(out, "CALSCALE:GREGORIAN")?;
+    write_line(out, "METHOD:PUBLISH")?;
+    write_line(
+        out,
+        &format!(
+            "X-WR-CALNAME:{}",
+            escape(&format!("Weather in {}", config.location().name))
+        ),
+    )?;
+
+    for period in &forecast.periods {
+        let Ok(date) = NaiveDate::parse_from_str(&period.date, "%Y-%m-%d") else {
+            continue;
+        };
+        let day = date.format("%Y%m%d").to_string();
+        let next_day = (date + Duration::days(1)).format("%Y%m%d").to_string();
+
+        let mut description = format!(
+            "{}\nHigh: {} (feels like {})\nLow: {} (feels like {})\nChance of Rain: {}",
+            period.weather,
+            degrees(period.temperature_max),
+            degrees(period.apparent_temperature_max),
+            degrees(period.temperature_min),
+            degrees(period.apparent_temperature_min),
+            percent(period.precipitation_probability)
+        );
+        if !period.narrative.is_empty() {
+            description.push_str("\n\n");
+            description.push_str(&period.narrative);
+        }
+
+        write_line(out, "BEGIN:VEVENT")?;
+        write_line(out, &format!("UID:{}-weather-{}@raijin", day, location_id))?;
+        write_line(out, &format!("DTSTAMP:{}", stamp))?;
+        write_line(out, &format!("LAST-MODIFIED:{}", stamp))?;
+        write_line(out, &format!("DTSTART;VALUE=DATE:{}", day))?;
+        write_line(out, &format!("DTEND;VALUE=DATE:{}", next_day))?;
+        write_line(out, &format!("SUMMARY:{}", escape(&summary(period))))?;
+        write_line(out, &format!("DESCRIPTION:{}", escape(&description)))?;
+        write_line(out, "TRANSP:TRANSPARENT")?;
+        write_line(out, "END:VEVENT")?;
+
+        if !sun_events {
+            continue;
+        }
+        for (kind, label, time) in [
+            ("sunrise", "Sunrise", &period.sunrise),
+            ("sunset", "Sunset", &period.sunset),
+        ] {
+            // Polar days and nights have no sunrise or sunset
+            let Some(start) = to_utc(forecast, time) else {
+                continue;
+            };
+            write_line(out, "BEGIN:VEVENT")?;
+            write_line(out, &format!("UID:{}-{}-{}@raijin", day, kind, location_id))?;
+            write_line(out, &format!("DTSTAMP:{}", stamp))?;
+            write_line(out, &format!("LAST-MODIFIED:{}", stamp))?;
+            write_line(out, &format!("DTSTART:{}", start))?;
+            write_line(out, &format!("SUMMARY:{}", label))?;
+            write_line(out, "TRANSP:TRANSPARENT")?;
+            write_line(out, "END:VEVENT")?;
+        }
+    }
+
+    write_line(out, "END:VCALENDAR")
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn folded(line: &str) -> String {
+        let mut out = Vec::new();
+        write_line(&mut out, line).unwrap();
+        String::from_utf8(out).unwrap()
+    }
+
+    #[test]
+    fn short_lines_are_not_folded() {
+        assert_eq!(folded("SUMMARY:Clear"), "SUMMARY:Clear\r\n");
+        let exact = "X".repeat(MAX_LINE_LENGTH);
+        assert_eq!(folded(&exact), format!("{}\r\n", exact));
+    }
+
+    #[test]
+    fn long_lines_fold_at_75_octets() {
+        let line = "X".repeat(160);
+        let output = folded(&line);
+        let lines: Vec<&str> = output.trim_end_matches("\r\n").split("\r\n").collect();
+        assert_eq!(lines.len(), 3);
+        assert_eq!(lines[0].len(), 75);
+        assert_eq!(lines[1].len(), 75);
+        assert!(lines[1].starts_with(' '));
+        assert_eq!(lines[2], format!(" {}", "X".repeat(11)));
+        // Unfolding gives back the original line
+        assert_eq!(output.replace("\r\n ", ""), format!("{}\r\n", line));
+    }
+
+    #[test]
+    fn folding_keeps_multi_byte_characters_whole() {
+        // 74 ASCII octets leave no room for the 3 octets of ☁ on the first line
+        let line = format!("{}☁☁", "X".repeat(74));
+        let output = folded(&line);
+        assert_eq!(output, format!("{}\r\n ☁☁\r\n", "X".repeat(74)));
+        assert!(
+            output
+                .split("\r\n")
+                .all(|line| line.len() <= MAX_LINE_LENGTH)
+        );
+    }
+
+    #[test]
+    fn text_values_are_escaped() {
+        assert_eq!(
+            escape("Rain; wind, gusts\\\nlater"),
+            r"Rain\; wind\, gusts\\\nlater"
+        );
+    }
+}
@@ -0,0 +1,215 @@
+use super::{Table, Values};
+use chrono::{Datelike, NaiveDate};
+use parquet::data_type::{ByteArray, ByteArrayType, FloatType, Int32Type, Int64Type};
+use parquet::errors::ParquetError;
+use parquet::file::metadata::KeyValue;
+use parquet::file::properties::WriterProperties;
+use parquet::file::writer::SerializedFileWriter;
+use parquet::schema::parser::parse_message_type;
+use std::io::{self, Write};
+use std::sync::Arc;
+
+/// Parquet type declaration of a column
+fn column_type(values: &Values) -> &'static str {
+    match values {
+        Values::Date(_) => "REQUIRED INT32 {} (DATE)",
+        // Parquet stores instants in UTC; the zone is kept in the file's metadata
+        Values::Timestamp(_) => "OPTIONAL INT64 {} (TIMESTAMP(MILLIS,true))",
+        Values::Float(_) => "REQUIRED FLOAT {}",
+        Values::Integer(_) => "REQUIRED INT32 {}",
+        Values::OptionalFloat(_) => "OPTIONAL FLOAT {}",
+        Values::OptionalInteger(_) => "OPTIONAL INT32 {}",
+        Values::Text(_) => "REQUIRED BYTE_ARRAY {} (STRING)",
+    }
+}
+
+fn to_io(err: ParquetError) -> io::Error {
+    io::Error::other(err)
+}
+
+/// Writes the table as a single row group Parquet file
+/// The IANA time zone of the timestamps is stored under the `timezone` metadata key
+pub fn write(out: &mut (impl Write + Send), table: &Table) -> io::Result<()> {
+    let fields: Vec<String> = table
+        .columns
+        .iter()
+        .map(|column| {
+            format!(
+                "{};",
+                column_type(&column.values).replace("{}", column.name)
+            )
+        })
+        .collect();
+    let message = format!("message {} {{ {} }}", table.name, fields.join(" "));
+    let schema = Arc::new(parse_message_type(&message).map_err(to_io)?);
+
+    let properties = WriterProperties::builder()
+        .set_key_value_metadata(Some(vec![KeyValue::new(
+            String::from("timezone"),
+            table.timezone.clone(),
+        )]))
+        .build();
+    let mut writer = SerializedFileWriter::new(out, schema, Arc::new(properties)).map_err(to_io)?;
+
+    let mut row_group = writer.next_row_group().map_err(to_io)?;
+    for column in &table.columns {
+        let mut column_writer = row_group
+            .next_column()
+            .map_err(to_io)?
+            .expect("schema has a column for every table column");
+
+        match &column.values {
+            Values::Date(values) => {
+                let epoch = NaiveDate::from_ymd_opt(1970, 1, 1).unwrap();
+                let days: Vec<i32> = values
+                    .iter()
+                    .map(|date| date.num_days_from_ce() - epoch.num_days_from_ce())
+                    .collect();
+                column_writer
+                    .typed::<Int32Type>()
+                    .write_batch(&days, None, None)
+            }
+            Values::Timestamp(values) => {
+                let millis: Vec<i64> = values
+                    .iter()
+                    .flatten()
+                    .map(|time| time.timestamp_millis())
+                    .collect();
+                let definition_levels: Vec<i16> =
+                    values.iter().map(|time| time.is_some() as i16).collect();
+                column_writer.typed::<Int64Type>().write_batch(
+                    &millis,
+                    Some(&definition_levels),
+                    None,
+                )
+            }
+            Values::Float(values) => column_writer
+                .typed::<FloatType>()
+                .write_batch(values, None, None),
+            Values::Integer(values) => column_writer
+                .typed::<Int32Type>()
+                .write_batch(values, None, None),
+            Values::OptionalFloat(values) => {
+                let present: Vec<f32> = values.iter().flatten().copied().collect();
+                let definition_levels: Vec<i16> =
+                    values.iter().map(|value| value.is_some() as i16).collect();
+                column_writer.typed::<FloatType>().write_batch(
+                    &present,
+                    Some(&definition_levels),
+                    None,
+                )
+            }
+            Values::OptionalInteger(values) => {
+                let present: Vec<i32> = values.iter().flatten().copied().collect();
+                let definition_levels: Vec<i16> =
+                    values.iter().map(|value| value.is_some() as i16).collect();
+                column_writer.typed::<Int32Type>().write_batch(
+                    &present,
+                    Some(&definition_levels),
+                    None,
+                )
+            }
+            Values::Text(values) => {
+                let bytes: Vec<ByteArray> = values
+                    .iter()
+                    .map(|value| ByteArray::from(value.as_str()))
+                    .collect();
+                column_writer
+                    .typed::<ByteArrayType>()
+                    .write_batch(&bytes, None, None)
+            }
+        }
+        .map_err(to_io)?;
+
+        column_writer.close().map_err(to_io)?;
+    }
+    row_group.close().map_err(to_io)?;
+    writer.close().map_err(to_io)?;
+    Ok(())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::export::Column;
+    use chrono::DateTime;
+    use parquet::basic::Type as PhysicalType;
+    use parquet::file::reader::{FileReader, SerializedFileReader};
+    use std::fs::{self, File};
+
+    #[test]
+    fn files_read_back_with_the_table_schema() {
+        let table = Table {
+            name: "hourly",
+            timezone: String::from("Europe/Berlin"),
+            rows: 2,
+            columns: vec![
+                Column {
+                    name: "time",
+                    values: Values::Timestamp(vec![
+                        DateTime::parse_from_rfc3339("2026-10-25T01:00:00+02:00").ok(),
+                        None,
+                    ]),
+                },
+                Column {
+                    name: "temperature",
+                    values: Values::Float(vec![7.5, 7.0]),
+                },
+                Column {
+                    name: "humidity",
+                    values: Values::OptionalInteger(vec![Some(90), None]),
+                },
+                Column {
+                    name: "weather",
+                    values: Values::Text(vec![String::from("Fog"), String::from("Clear")]),
+                },
+            ],
+        };
+        let path =
+            std::env::temp_dir().join(format!("raijin-{}-export.parquet", std::process::id()));
+        write(&mut File::create(&path).unwrap(), &table).unwrap();
+
+        let reader = SerializedFileReader::new(File::open(&path).unwrap()).unwrap();
+        let metadata = reader.metadata().file_metadata();
+        assert_eq!(metadata.num_rows(), 2);
+        assert_eq!(
+            metadata
+                .key_value_metadata()
+                .and_then(|pairs| pairs.iter().find(|pair| pair.key == "timezone"))
+                .and_then(|pair| pair.value.as_deref()),
+            Some("Europe/Berlin")
+        );
+        let schema = metadata.schema_descr();
+        assert_eq!(schema.name(), "hourly");
+        let columns: Vec<(&str, PhysicalType, bool)> = schema
+            .columns()
+            .iter()
+            .map(|column| {
+                (
+                    column.name(),
+                    column.physical_type(),
+                    column.self_type().is_optional(),
+                )
+            })
+            .collect();
+        assert_eq!(
+            columns,
+            [
+                ("time", PhysicalType::INT64, true),
+                ("temperature", PhysicalType::FLOAT, false),
+                ("humidity", PhysicalType::INT32, true),
+                ("weather", PhysicalType::BYTE_ARRAY, false),
+            ]
+        );
+
+        let rows: Vec<String> = reader
+            .get_row_iter(None)
+            .unwrap()
+            .map(|row| row.unwrap().to_string())
+            .collect();
+        fs::remove_file(&path).unwrap();
+        assert_eq!(rows.len(), 2);
+        assert!(rows[0].contains("temperature: 7.5"));
+        assert!(rows[1].contains("humidity: null"));
+    }
+}
@@ -0,0 +1,194 @@
+use serde::{Deserialize, Serialize};
+
+/// Conditions right now
+/// `weather` is filled in from `weather_code` after fetching
+#[derive(Serialize, Deserialize, Debug, Default, Clone)]
+pub struct Current {
+    /// Local time of the observation (YYYY-MM-DDTHH:MM)
+    #[serde(default)]
+    pub time: String,
+    // Named after Open-Meteo's variable, which the JSON schema started out with
+    #[serde(rename = "temperature_2m")]
+    pub temperature: f32,
+    pub apparent_temperature: f32,
+    pub weather_code: i32,
+    #[serde(default)]
+    pub weather: String,
+    /// Weather station the conditions were observed at, for providers that report observations
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub station: Option<Station>,
+    /// Where the conditions come from
+    #[serde(default)]
+    pub source: Source,
+}
+
+/// Origin of the current conditions
+#[derive(Serialize, Deserialize, Debug, Default, Clone, Copy, PartialEq)]
+#[serde(rename_all = "kebab-case")]
+pub enum Source {
+    /// The provider's model, for the current hour or interpolated
+    #[default]
+    Model,
+    /// An observation the provider reported from a station near the location
+    Station,
+    /// The user's own station, see `config::PersonalStation`
+    PersonalStation,
+}
+
+impl Source {
+    pub fn label(&self) -> &'static str {
+        match self {
+            Source::Model => "Forecast model",
+            Source::Station => "Nearby station",
+            Source::PersonalStation => "Personal station",
+        }
+    }
+}
+
+/// A weather station near the location
+#[derive(Serialize, Deserialize, Debug, Clone)]
+pub struct Station {
+    pub name: String,
+    /// Distance from the location in kilometres, if the provider says
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub distance: Option<f32>,
+}
+
+impl Station {
+    /// e.g. "Berlin-Tempelhof (4.1 km)"
+    pub fn describe(&self) -> String {
+        match self.distance {
+            Some(distance) => format!("{} ({:.1} km)", self.name, distance),
+            None => self.name.clone(),
+        }
+    }
+}
+
+/// Single day/weather condition
+/// NOTE: Temperatures are plain numbers; use `degrees` to display them
+#[derive(Serialize, Deserialize, Debug, Clone)]
+pub struct Period {
+    /// Local date (YYYY-MM-DD)
+    pub date: String,
+    pub weather_code: i32,
+    pub weather: String,
+    pub temperature_max: f32,
+    pub temperature_min: f32,
+    pub apparent_temperature_max: f32,
+    pub apparent_temperature_min: f32,
+    /// None when the provider has no chance of rain for the day
+    pub precipitation_probability: Option<i32>,
+    /// Local time (YYYY-MM-DDTHH:MM); empty in polar day and night
+    #[serde(default)]
+    pub sunrise: String,
+    #[serde(default)]
+    pub sunset: String,
+    /// None when the provider doesn't forecast gusts
+    pub wind_gusts_max: Option<f32>,
+    /// Forecaster-written text for the day, from providers that publish one
+    #[serde(default, skip_serializing_if = "String::is_empty")]
+    pub narrative: String,
+    /// Largest difference between the providers' highs or lows in a consensus forecast
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub disagreement: Option<f32>,
+}
+
+/// Forecast data by the hour
+#[derive(Serialize, Deserialize, Debug, Clone)]
+pub struct Hour {
+    /// Local time (YYYY-MM-DDTHH:MM)
+    pub datetime: String,
+    pub temperature: f32,
+    pub apparent_temperature: f32,
+    pub weather_code: i32,
+    pub weather: String,
+    pub precipitation_probability: Option<i32>,
+    /// Amount expected during the hour
+    #[serde(default)]
+    pub precipitation: f32,
+    #[serde(default)]
+    pub wind_speed: f32,
+    pub wind_gusts: Option<f32>,
+    /// Relative humidity in %
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub humidity: Option<i32>,
+    /// Lowest and highest temperature among the providers of a consensus forecast
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub temperature_range: Option<[f32; 2]>,
+}
+
+/// Forecast with daily, hourly and current weather, independent of the provider it came from
+/// Values are in the configured units and weather is described by WMO codes
+#[derive(Serialize, Deserialize, Debug, Default, Clone)]
+pub struct Forecast {
+    /// IANA time zone of the local times below
+    #[serde(default)]
+    pub timezone: String,
+    /// Offset of the local times below from UTC at the time of the fetch
+    #[serde(default)]
+    pub utc_offset_seconds: i32,
+    /// One entry per day, starting today
+    pub periods: Vec<Period>,
+    pub current: Current,
+    /// One entry per hour, starting at midnight today, or at the current hour for providers that
+    /// don't report past hours
+    pub hourly: Vec<Hour>,
+}
+
+impl Forecast {
+    /// Fills in the `weather` descriptions the provider left empty from the WMO codes
+    pub fn describe_weather(&mut self, weather_codes: &serde_json::Value) {
+        let describe = |weather: &mut String, code| {
+            if weather.is_empty() {
+                *weather = crate::describe_weather_code(weather_codes, code);
+            }
+        };
+        describe(&mut self.current.weather, self.current.weather_code);
+        for period in &mut self.periods {
+            describe(&mut period.weather, period.weather_code);
+        }
+        for hour in &mut self.hourly {
+            describe(&mut hour.weather, hour.weather_code);
+        }
+    }
+}
+
+/// Hours and days for the tests, with everything but the time and temperature left empty
+#[cfg(test)]
+pub mod fixtures {
+    use super::*;
+
+    pub fn hour(datetime: &str, temperature: f32) -> Hour {
+        Hour {
+            datetime: datetime.to_string(),
+            temperature,
+            apparent_temperature: temperature,
+            weather_code: 0,
+            weather: String::new(),
+            precipitation_probability: None,
+            precipitation: 0.0,
+            wind_speed: 0.0,
+            wind_gusts: None,
+            humidity: None,
+            temperature_range: None,
+        }
+    }
+
+    pub fn period(date: &str, temperature_min: f32, temperature_max: f32) -> Period {
+        Period {
+            date: date.to_string(),
+            weather_code: 0,
+            weather: String::new(),
+            temperature_max,
+            temperature_min,
+            apparent_temperature_max: temperature_max,
+            apparent_temperature_min: temperature_min,
+            precipitation_probability: None,
+            sunrise: String::new(),
+            sunset: String::new(),
+            wind_gusts_max: None,
+            narrative: String::new(),
+            disagreement: None,
+        }
+    }
+}
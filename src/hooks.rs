@@ -0,0 +1,389 @@
+use crate::alerts::{self, AlertWindow};
+use crate::config::Config;
+use crate::{cache, fetch_all, mqtt};
+use serde::{Deserialize, Serialize};
+use std::fs::File;
+use std::path::{Path, PathBuf};
+use std::process::{self, ExitStatus, Stdio};
+use std::time::Duration;
+use std::{fmt, fs, io, thread};
+use ureq::Agent;
+
+/// Fired alerts are remembered for longer than the longest forecast reaches
+const FORGET_AFTER: u64 = 17 * 24 * 60 * 60;
+
+/// Alerts that already ran the hooks, shared by every Raijin process so the TUI and
+/// `raijin daemon` don't both announce the same alert
+#[derive(Serialize, Deserialize, Default)]
+struct Fired {
+    alerts: Vec<FiredAlert>,
+}
+
+/// An alert that ran the hooks
+#[derive(Serialize, Deserialize)]
+struct FiredAlert {
+    /// The rule and place, see `alert_key`
+    key: String,
+    /// First and last hour the alert has covered so far (local YYYY-MM-DDTHH:MM)
+    start: String,
+    end: String,
+    /// Unix timestamp it fired at
+    fired_at: u64,
+}
+
+impl Fired {
+    /// Forgets the alerts that fired before `cutoff`
+    fn forget_before(&mut self, cutoff: u64) {
+        self.alerts.retain(|alert| alert.fired_at >= cutoff);
+    }
+
+    /// Records the window as fired at `now`, unless an alert of the same rule already covers
+    /// some of its hours; returns whether the window is a new alert
+    /// Windows start at the current hour and grow or shrink as the forecast changes, so the
+    /// alert's hours are widened to the window's to keep recognising it on later refreshes
+    fn claim(&mut self, key: String, window: &AlertWindow, now: u64) -> bool {
+        let start = window.start.as_str();
+        let end = window.end.as_str();
+        let known = self.alerts.iter_mut().find(|alert| {
+            alert.key == key && alert.start.as_str() <= end && start <= alert.end.as_str()
+        });
+        if let Some(alert) = known {
+            if start < alert.start.as_str() {
+                alert.start = window.start.clone();
+            }
+            if end > alert.end.as_str() {
+                alert.end = window.end.clone();
+            }
+            return false;
+        }
+        self.alerts.push(FiredAlert {
+            key,
+            start: window.start.clone(),
+            end: window.end.clone(),
+            fired_at: now,
+        });
+        true
+    }
+}
+
+#[derive(Debug)]
+pub enum HookError {
+    /// The command couldn't be started
+    Command(io::Error),
+    /// The command exited unsuccessfully, with the last line it wrote to stderr
+    CommandStatus(ExitStatus, String),
+    Webhook(ureq::Error),
+}
+
+impl fmt::Display for HookError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            HookError::Command(err) => write!(f, "could not run the alert command: {}", err),
+            HookError::CommandStatus(status, stderr) if stderr.is_empty() => {
+                write!(f, "the alert command failed ({})", status)
+            }
+            HookError::CommandStatus(status, stderr) => {
+                write!(f, "the alert command failed ({}): {}", status, stderr)
+            }
+            HookError::Webhook(err) => write!(f, "could not post the alert: {}", err),
+        }
+    }
+}
+
+impl std::error::Error for HookError {}
+
+/// Body of the webhook request, also passed to the command as `RAIJIN_ALERT_JSON`
+#[derive(Serialize)]
+struct Payload<'a> {
+    location: &'a str,
+    #[serde(flatten)]
+    window: &'a AlertWindow,
+    unit: &'static str,
+    message: String,
+}
+
+fn fired_path() -> Option<PathBuf> {
+    Some(dirs::cache_dir()?.join("raijin").join("fired-alerts.json"))
+}
+
+/// Locks the fired alerts until the returned file is dropped, so processes refreshing at the
+/// same time take turns claiming alerts
+/// A separate file, as `store_fired` replaces the fired alerts' one; without a lock the alerts
+/// may fire twice
+fn lock_fired(path: &Path) -> Option<File> {
+    let path = path.with_extension("lock");
+    fs::create_dir_all(path.parent()?).ok()?;
+    let file = File::options()
+        .create(true)
+        .truncate(false)
+        .write(true)
+        .open(path)
+        .ok()?;
+    file.lock().ok()?;
+    Some(file)
+}
+
+fn load_fired(path: &Path) -> Fired {
+    fs::read_to_string(path)
+        .ok()
+        .and_then(|contents| serde_json::from_str(&contents).ok())
+        .unwrap_or_default()
+}
+
+fn store_fired(path: &Path, fired: &Fired) -> io::Result<()> {
+    if let Some(parent) = path.parent() {
+        fs::create_dir_all(parent)?;
+    }
+    let temporary = path.with_extension(format!("json.{}", process::id()));
+    fs::write(&temporary, serde_json::to_string(fired)?)?;
+    fs::rename(&temporary, path)
+}
+
+/// The rule and place an alert belongs to; `Fired::claim` tells its windows apart by their hours
+fn alert_key(config: &Config, window: &AlertWindow) -> String {
+    let location = config.location();
+    format!(
+        "{:.4},{:.4}/{}",
+        location.latitude, location.longitude, window.name
+    )
+}
+
+fn run_command(command: &str, payload: &Payload) -> Result<(), HookError> {
+    let json = serde_json::to_string(payload).unwrap_or_default();
+    let mut shell = if cfg!(windows) {
+        let mut shell = process::Command::new("cmd");
+        shell.arg("/C");
+        shell
+    } else {
+        let mut shell = process::Command::new("sh");
+        shell.arg("-c");
+        shell
+    };
+
+    // Output is kept off the terminal the TUI owns; stderr explains a failure
+    let output = shell
+        .arg(command)
+        .env("RAIJIN_LOCATION", payload.location)
+        .env("RAIJIN_ALERT_NAME", &payload.window.name)
+        .env("RAIJIN_ALERT_MESSAGE", &payload.message)
+        .env("RAIJIN_ALERT_START", &payload.window.start)
+        .env("RAIJIN_ALERT_END", &payload.window.end)
+        .env("RAIJIN_ALERT_VALUE", payload.window.extreme.to_string())
+        .env("RAIJIN_ALERT_UNIT", payload.unit)
+        .env("RAIJIN_ALERT_JSON", json)
+        .stdin(Stdio::null())
+        .stdout(Stdio::null())
+        .stderr(Stdio::piped())
+        .output()
+        .map_err(HookError::Command)?;
+
+    if output.status.success() {
+        Ok(())
+    } else {
+        let stderr = String::from_utf8_lossy(&output.stderr);
+        let last_line = stderr.lines().rfind(|line| !line.trim().is_empty());
+        Err(HookError::CommandStatus(
+            output.status,
+            last_line.unwrap_or_default().trim().to_string(),
+        ))
+    }
+}
+
+/// Runs the configured hooks for every alert window that hasn't fired yet
+/// Windows that ended before `now` (local "YYYY-MM-DDTHH:MM") are skipped
+/// An alert counts as fired even if a hook fails, so a broken hook doesn't repeat on every refresh
+pub fn fire(agent: &Agent, config: &Config, windows: &[AlertWindow], now: &str) -> Vec<HookError> {
+    let mut errors = Vec::new();
+    if config.hooks.is_empty() || windows.is_empty() {
+        return errors;
+    }
+
+    let path = fired_path();
+    let lock = path.as_deref().and_then(lock_fired);
+    let mut fired = path.as_deref().map(load_fired).unwrap_or_default();
+    let now_secs = cache::now();
+    fired.forget_before(now_secs.saturating_sub(FORGET_AFTER));
+
+    // Compare whole hours: the last matching hour lasts until the next one starts
+    let current_hour = now.get(..13).unwrap_or(now);
+    let mut pending: Vec<&AlertWindow> = Vec::new();
+    for window in windows {
+        if window.end.get(..13).unwrap_or(&window.end) < current_hour {
+            continue;
+        }
+        if fired.claim(alert_key(config, window), window, now_secs) {
+            pending.push(window);
+        }
+    }
+
+    // Claim the alerts before running anything, so another Raijin process refreshing at the
+    // same time sees them as fired; saved even without new alerts, as their hours may have grown
+    // Failing to save only means the alerts may fire again on the next refresh
+    if let Some(path) = &path {
+        let _ = store_fired(path, &fired);
+    }
+    drop(lock);
+
+    for window in pending {
+        let payload = Payload {
+            location: &config.location().name,
+            window,
+            unit: window.metric.unit(&config.units),
+            message: alerts::describe_window(window, &config.units),
+        };
+        if let Some(command) = &config.hooks.command
+            && let Err(err) = run_command(command, &payload)
+        {
+            errors.push(err);
+        }
+        if let Some(url) = &config.hooks.webhook
+            && let Err(err) = agent.post(url).send_json(&payload)
+        {
+            errors.push(HookError::Webhook(err));
+        }
+    }
+    errors
+}
+
+/// Checks the forecast every `refresh_interval` minutes, runs the hooks and publishes to MQTT,
+/// without a TUI
+pub fn daemon(agent: &Agent, config: &Config, weather_codes: &serde_json::Value) -> ! {
+    eprintln!(
+        "Watching {} alert rule(s) for {}",
+        config.alerts.len(),
+        config.location().name
+    );
+    loop {
+        match fetch_all(agent, config, weather_codes) {
+            Ok((forecast, _)) => {
+                let alerts = alerts::evaluate(&config.alerts, &forecast);
+                for err in fire(agent, config, &alerts.windows, &forecast.current.time) {
+                    eprintln!("Alert hook failed: {}", err);
+                }
+                if let Err(err) = mqtt::publish(config, &forecast, &alerts.windows) {
+                    eprintln!("Could not publish the forecast: {}", err);
+                }
+            }
+            Err(err) => eprintln!("Could not refresh the forecast: {}", err),
+        }
+        thread::sleep(Duration::from_secs(config.refresh_interval * 60));
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::config::{AlertCondition, AlertMetric};
+
+    fn window() -> AlertWindow {
+        AlertWindow {
+            name: String::from("Frost"),
+            metric: AlertMetric::Temperature,
+            condition: AlertCondition::Below,
+            start: String::from("2026-10-19T03:00"),
+            end: String::from("2026-10-19T07:00"),
+            extreme: -2.0,
+        }
+    }
+
+    #[test]
+    #[cfg(unix)]
+    fn failing_commands_say_why() {
+        let window = window();
+        let payload = Payload {
+            location: "Home",
+            window: &window,
+            unit: "°C",
+            message: String::from("Frost"),
+        };
+        assert!(run_command("test \"$RAIJIN_ALERT_NAME\" = Frost", &payload).is_ok());
+
+        let err = run_command(
+            "echo starting; echo 'no route to host' >&2; exit 3",
+            &payload,
+        )
+        .unwrap_err();
+        assert!(matches!(
+            &err,
+            HookError::CommandStatus(status, stderr)
+                if status.code() == Some(3) && stderr == "no route to host"
+        ));
+        assert!(err.to_string().ends_with("): no route to host"));
+    }
+
+    fn window_between(start: &str, end: &str) -> AlertWindow {
+        AlertWindow {
+            start: start.to_string(),
+            end: end.to_string(),
+            ..window()
+        }
+    }
+
+    #[test]
+    fn alerts_fire_once_across_refreshes() {
+        let mut fired = Fired::default();
+        let key = String::from("52.5200,13.4050/Frost");
+        assert!(fired.claim(
+            key.clone(),
+            &window_between("2026-10-18T22:00", "2026-10-19T03:00"),
+            0
+        ));
+
+        // Later refreshes start the window at the current hour, past midnight, and the
+        // forecast stretches it
+        assert!(!fired.claim(
+            key.clone(),
+            &window_between("2026-10-18T23:00", "2026-10-19T05:00"),
+            3600
+        ));
+        assert!(!fired.claim(
+            key.clone(),
+            &window_between("2026-10-19T04:00", "2026-10-19T07:00"),
+            21600
+        ));
+
+        // Another night, or another rule, is a new alert
+        assert!(fired.claim(
+            key.clone(),
+            &window_between("2026-10-19T22:00", "2026-10-20T02:00"),
+            21600
+        ));
+        assert!(fired.claim(String::from("52.5200,13.4050/Storm"), &window(), 21600));
+        assert_eq!(fired.alerts.len(), 3);
+    }
+
+    #[test]
+    fn forgotten_alerts_fire_again() {
+        let mut fired = Fired::default();
+        let key = String::from("52.5200,13.4050/Frost");
+        assert!(fired.claim(key.clone(), &window(), 100));
+
+        fired.forget_before(100);
+        assert!(!fired.claim(key.clone(), &window(), 100 + FORGET_AFTER));
+        fired.forget_before(101);
+        assert!(fired.claim(key, &window(), 101 + FORGET_AFTER));
+    }
+
+    #[test]
+    fn fired_alerts_are_locked_and_kept() {
+        let dir = std::env::temp_dir().join(format!("raijin-{}-fired", process::id()));
+        let path = dir.join("fired-alerts.json");
+        let lock = lock_fired(&path).unwrap();
+        let other = File::options()
+            .write(true)
+            .open(path.with_extension("lock"))
+            .unwrap();
+        assert!(other.try_lock().is_err());
+
+        let mut fired = Fired::default();
+        fired.claim(String::from("52.5200,13.4050/Frost"), &window(), 100);
+        store_fired(&path, &fired).unwrap();
+        drop(lock);
+        assert!(other.try_lock().is_ok());
+
+        let mut stored = load_fired(&path);
+        assert!(!stored.claim(String::from("52.5200,13.4050/Frost"), &window(), 200));
+        assert_eq!(stored.alerts[0].fired_at, 100);
+        fs::remove_dir_all(dir).unwrap();
+    }
+}
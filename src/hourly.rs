@@ -0,0 +1,273 @@
+use crate::alerts::Alerts;
+use crate::config::{Config, PrecipitationUnit, Theme};
+use crate::forecast::{Forecast, Hour};
+use crate::{degrees, get_day_from_date, gusts, percent, weather_icon};
+use ratatui::{
+    Frame,
+    layout::{Constraint, Margin, Rect},
+    style::{Style, Stylize},
+    text::Line,
+    widgets::{Block, Cell, Padding, Row, Scrollbar, ScrollbarOrientation, ScrollbarState, Table},
+};
+
+/// A line of the hourly table
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub enum Entry {
+    /// Heading of the day starting with the hour at this index of `forecast.hourly`
+    Day(usize),
+    /// Index into `forecast.hourly`
+    Hour(usize),
+}
+
+/// The table's lines: every hour of the forecast, with a heading before each day
+pub fn entries(forecast: &Forecast) -> Vec<Entry> {
+    let mut entries = Vec::new();
+    let mut date = "";
+    for (i, hour) in forecast.hourly.iter().enumerate() {
+        let day = hour.datetime.get(..10).unwrap_or_default();
+        if day != date {
+            entries.push(Entry::Day(i));
+            date = day;
+        }
+        entries.push(Entry::Hour(i));
+    }
+    entries
+}
+
+/// Index into `forecast.hourly` of the hour the current conditions fall into
+fn current_hour(forecast: &Forecast) -> Option<usize> {
+    let now = forecast.current.time.get(..13)?;
+    forecast
+        .hourly
+        .iter()
+        .position(|hour| hour.datetime.starts_with(now))
+}
+
+/// The first line to show for the current hour to be at the top, under its day's heading if it
+/// starts the day; the first line if the forecast doesn't cover the current hour
+pub fn now_offset(forecast: &Forecast, entries: &[Entry]) -> usize {
+    let Some(hour) = current_hour(forecast) else {
+        return 0;
+    };
+    let row = entries
+        .iter()
+        .position(|entry| *entry == Entry::Hour(hour))
+        .unwrap_or_default();
+    match row.checked_sub(1).map(|previous| entries[previous]) {
+        Some(Entry::Day(_)) => row - 1,
+        _ => row,
+    }
+}
+
+/// The first line of the next day's heading after `offset`, or of the heading before it
+pub fn day_offset(entries: &[Entry], offset: usize, forward: bool) -> usize {
+    let is_day = |row: &usize| matches!(entries[*row], Entry::Day(_));
+    if forward {
+        (offset + 1..entries.len()).find(is_day).unwrap_or(offset)
+    } else {
+        (0..offset).rev().find(is_day).unwrap_or(0)
+    }
+}
+
+fn hour_row<'a>(hour: &Hour, config: &Config) -> Row<'a> {
+    Row::new(vec![
+        Cell::from(hour.datetime.get(11..16).unwrap_or_default().to_string()),
+        Cell::from(format!(
+            "{} {}",
+            weather_icon(hour.weather_code),
+            hour.weather
+        )),
+        Cell::from(degrees(hour.temperature)),
+        Cell::from(degrees(hour.apparent_temperature)),
+        Cell::from(percent(hour.precipitation_probability)),
+        Cell::from(match config.units.precipitation {
+            PrecipitationUnit::Mm => format!("{:.1}", hour.precipitation),
+            PrecipitationUnit::Inch => format!("{:.2}", hour.precipitation),
+        }),
+        Cell::from(format!("{:.0}", hour.wind_speed)),
+        Cell::from(gusts(hour.wind_gusts)),
+        Cell::from(hour.humidity.map_or(String::from("\u{2013}"), |humidity| {
+            format!("{}%", humidity)
+        })),
+    ])
+}
+
+fn day_row<'a>(forecast: &Forecast, hour: &Hour) -> Row<'a> {
+    let date = hour.datetime.get(..10).unwrap_or_default();
+    let summary = forecast
+        .periods
+        .iter()
+        .find(|period| period.date == date)
+        .map_or(String::new(), |period| {
+            format!(
+                " \u{b7} {} / {} \u{b7} {}",
+                degrees(period.temperature_max),
+                degrees(period.temperature_min),
+                period.weather
+            )
+        });
+    Row::new(vec![
+        Cell::from(get_day_from_date(date).unwrap_or_default()),
+        Cell::from(format!("{}{}", date, summary)),
+    ])
+}
+
+/// Renders the hourly table from line `offset` on
+/// Returns how many lines fit, for scrolling by page
+pub fn render(
+    frame: &mut Frame,
+    area: Rect,
+    forecast: &Forecast,
+    entries: &[Entry],
+    offset: usize,
+    alerts: &Alerts,
+    config: &Config,
+) -> usize {
+    let theme = &config.theme;
+    let units = &config.units;
+    let now = current_hour(forecast);
+
+    let block = Block::bordered()
+        .title(
+            Line::from(format!(
+                " Hourly ({}, {}, {}) ",
+                units.temperature.symbol(),
+                units.precipitation.symbol(),
+                units.wind_speed.symbol()
+            ))
+            .fg(Theme::color(&theme.forecast))
+            .centered()
+            .bold(),
+        )
+        .title_bottom(
+            Line::from(" \u{2191}/\u{2193} hour \u{b7} PgUp/PgDn page \u{b7} \u{2190}/\u{2192} day \u{b7} n now \u{b7} Esc back ")
+                .centered()
+                .dim(),
+        )
+        .padding(Padding::horizontal(1));
+    let inner = block.inner(area);
+    frame.render_widget(block, area);
+
+    if entries.is_empty() {
+        frame.render_widget(Line::from("No hourly forecast").centered().dim(), inner);
+        return 0;
+    }
+
+    // One line goes to the header
+    let page = usize::from(inner.height.saturating_sub(1)).max(1);
+    let rows: Vec<Row> = entries
+        .iter()
+        .skip(offset)
+        .take(page)
+        .map(|entry| match *entry {
+            Entry::Day(i) => day_row(forecast, &forecast.hourly[i])
+                .style(Style::new().fg(Theme::color(&theme.forecast)).bold()),
+            Entry::Hour(i) => {
+                let row = hour_row(&forecast.hourly[i], config);
+                if now == Some(i) {
+                    row.style(Style::new().fg(Theme::color(&theme.selection)).bold())
+                } else if alerts.hours.get(i).copied().unwrap_or_default() {
+                    row.style(Style::new().fg(Theme::color(&theme.alert)))
+                } else {
+                    row
+                }
+            }
+        })
+        .collect();
+    let header = Row::new([
+        "Time", "Weather", "Temp", "Feels", "Rain", "Amount", "Wind", "Gusts", "Humidity",
+    ])
+    .bold();
+    let widths = [
+        Constraint::Length(5),
+        Constraint::Fill(1),
+        Constraint::Length(6),
+        Constraint::Length(6),
+        Constraint::Length(5),
+        Constraint::Length(6),
+        Constraint::Length(5),
+        Constraint::Length(5),
+        Constraint::Length(8),
+    ];
+    frame.render_widget(
+        Table::new(rows, widths).header(header).column_spacing(1),
+        inner,
+    );
+
+    if entries.len() > page {
+        let mut scrollbar_state = ScrollbarState::new(entries.len() - page + 1)
+            .viewport_content_length(page)
+            .position(offset);
+        frame.render_stateful_widget(
+            Scrollbar::new(ScrollbarOrientation::VerticalRight),
+            area.inner(Margin::new(0, 1)),
+            &mut scrollbar_state,
+        );
+    }
+    page
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::forecast::fixtures::hour;
+
+    /// 22:00 on the 18th to 01:00 on the 19th
+    fn forecast(now: &str) -> Forecast {
+        let mut forecast = Forecast {
+            hourly: [
+                "2026-10-18T22:00",
+                "2026-10-18T23:00",
+                "2026-10-19T00:00",
+                "2026-10-19T01:00",
+            ]
+            .iter()
+            .map(|datetime| hour(datetime, 10.0))
+            .collect(),
+            ..Default::default()
+        };
+        forecast.current.time = now.to_string();
+        forecast
+    }
+
+    #[test]
+    fn days_get_a_heading() {
+        assert_eq!(
+            entries(&forecast("")),
+            [
+                Entry::Day(0),
+                Entry::Hour(0),
+                Entry::Hour(1),
+                Entry::Day(2),
+                Entry::Hour(2),
+                Entry::Hour(3),
+            ]
+        );
+    }
+
+    #[test]
+    fn the_current_hour_starts_the_view() {
+        let at = |now| {
+            let forecast = forecast(now);
+            now_offset(&forecast, &entries(&forecast))
+        };
+        assert_eq!(at("2026-10-18T23:15"), 2);
+        // An hour that starts its day is shown under the heading
+        assert_eq!(at("2026-10-19T00:40"), 3);
+        assert_eq!(at("2026-10-18T22:00"), 0);
+        assert_eq!(at("2026-10-20T12:00"), 0);
+        assert_eq!(at(""), 0);
+    }
+
+    #[test]
+    fn days_are_jumped_between() {
+        let entries = entries(&forecast(""));
+        assert_eq!(day_offset(&entries, 0, true), 3);
+        assert_eq!(day_offset(&entries, 2, true), 3);
+        // No day after the last one
+        assert_eq!(day_offset(&entries, 3, true), 3);
+        assert_eq!(day_offset(&entries, 5, false), 3);
+        assert_eq!(day_offset(&entries, 3, false), 0);
+        assert_eq!(day_offset(&entries, 0, false), 0);
+    }
+}
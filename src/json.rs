@@ -0,0 +1,114 @@
+use crate::MoonPhase;
+use crate::config::{Config, Location, Units};
+use crate::forecast::{Current, Forecast, Hour, Period};
+use serde::Serialize;
+
+/// Version of the JSON layout below
+/// Bump it whenever a field is renamed, removed or changes its type; adding fields is fine
+pub const SCHEMA_VERSION: u32 = 2;
+
+/// Part of the forecast a report is limited to
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub enum Section {
+    All,
+    Current,
+    Daily,
+    Hourly,
+    Moon,
+}
+
+/// Everything `--json` prints and `raijin serve` responds with
+/// Sections that weren't asked for are left out entirely
+#[derive(Serialize, Debug)]
+struct JsonReport<'a> {
+    schema_version: u32,
+    /// RFC 3339 timestamp of when the report was generated
+    generated_at: String,
+    location: &'a Location,
+    units: &'a Units,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    current: Option<&'a Current>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    daily: Option<&'a [Period]>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    hourly: Option<&'a [Hour]>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    moon: Option<&'a MoonPhase>,
+}
+
+/// Serializes the forecast, limited to `section`
+pub fn report(
+    forecast: &Forecast,
+    moon_phase: &MoonPhase,
+    config: &Config,
+    section: Section,
+) -> String {
+    let wants = |wanted: Section| section == Section::All || section == wanted;
+
+    let report = JsonReport {
+        schema_version: SCHEMA_VERSION,
+        generated_at: chrono::Local::now().to_rfc3339(),
+        location: config.location(),
+        units: &config.units,
+        current: wants(Section::Current).then_some(&forecast.current),
+        daily: wants(Section::Daily).then_some(&forecast.periods[..]),
+        hourly: wants(Section::Hourly).then_some(&forecast.hourly[..]),
+        moon: wants(Section::Moon).then_some(moon_phase),
+    };
+
+    serde_json::to_string_pretty(&report).expect("Forecast could not be serialized")
+}
+
+/// Prints the forecast as JSON on stdout
+pub fn print(forecast: &Forecast, moon_phase: &MoonPhase, config: &Config, section: Section) {
+    println!("{}", report(forecast, moon_phase, config, section));
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::forecast::fixtures::{hour, period};
+
+    #[test]
+    fn reports_hold_only_the_asked_for_section() {
+        let forecast = Forecast {
+            periods: vec![period("2026-10-18", 4.0, 16.0)],
+            hourly: vec![hour("2026-10-18T00:00", 6.0), hour("2026-10-18T01:00", 5.5)],
+            ..Forecast::default()
+        };
+        let mut config = Config::default();
+        config.locations.push(Location {
+            name: String::from("Berlin"),
+            latitude: 52.52,
+            longitude: 13.41,
+            timezone: String::from("Europe/Berlin"),
+            warning_area: None,
+            geocodes: Vec::new(),
+        });
+        let moon_phase = MoonPhase::default();
+        let fields = |section| {
+            let report: serde_json::Value =
+                serde_json::from_str(&report(&forecast, &moon_phase, &config, section)).unwrap();
+            assert_eq!(report["schema_version"], SCHEMA_VERSION);
+            assert_eq!(report["location"]["name"], "Berlin");
+            for always in ["generated_at", "units"] {
+                assert!(report.get(always).is_some(), "{} is missing", always);
+            }
+            ["current", "daily", "hourly", "moon"]
+                .into_iter()
+                .filter(|field| report.get(field).is_some())
+                .collect::<Vec<_>>()
+        };
+
+        assert_eq!(fields(Section::Current), ["current"]);
+        assert_eq!(fields(Section::Daily), ["daily"]);
+        assert_eq!(fields(Section::Hourly), ["hourly"]);
+        assert_eq!(fields(Section::Moon), ["moon"]);
+        assert_eq!(fields(Section::All), ["current", "daily", "hourly", "moon"]);
+
+        let all: serde_json::Value =
+            serde_json::from_str(&report(&forecast, &moon_phase, &config, Section::All)).unwrap();
+        assert_eq!(all["daily"][0]["date"], "2026-10-18");
+        assert_eq!(all["hourly"].as_array().map(Vec::len), Some(2));
+    }
+}
@@ -1,186 +1,304 @@
+mod air;
+mod alerts;
+mod astronomy;
+mod bar;
+mod cache;
+mod cli;
+mod config;
+mod daily;
+mod day;
+mod export;
+mod forecast;
+mod hooks;
+mod hourly;
+mod json;
+mod mqtt;
+mod provider;
+mod report;
+mod serve;
+mod station;
+mod warnings;
+
+use alerts::Alerts;
 use chrono::{Datelike, NaiveDate};
-use crossterm::event::{self, Event, KeyCode, KeyEvent, KeyEventKind};
-use dirs;
-use dotenv;
+use clap::Parser;
+use cli::{Cli, Command};
+use config::{Config, OverviewLayout, Panel, Theme};
+use crossterm::event::{
+    self, DisableMouseCapture, EnableMouseCapture, Event, KeyCode, KeyEvent, KeyEventKind,
+    MouseButton, MouseEvent, MouseEventKind,
+};
+use crossterm::execute;
+use forecast::{Forecast, Hour, Period};
 use include_dir::{Dir, include_dir};
+use json::Section;
+use provider::ProviderError;
 use ratatui::{
     DefaultTerminal, Frame,
-    layout::{Constraint, Layout, Rect},
+    layout::{Constraint, Direction, Layout, Margin, Position, Rect},
     prelude::Alignment,
     style::{Color, Style, Stylize},
     symbols::Marker,
-    text::{Line, Text},
+    text::{Line, Span, Text},
     widgets::{
-        Axis, Block, Borders, Cell, Chart, Dataset, GraphType, Padding, Paragraph, Row, Table,
+        Axis, Block, BorderType, Borders, Cell, Chart, Clear, Dataset, GraphType, Padding,
+        Paragraph, Row, Scrollbar, ScrollbarOrientation, ScrollbarState, Table, Tabs, Wrap,
     },
 };
 use serde::{Deserialize, Serialize};
-use std::path::PathBuf;
-use std::{env, fs, io};
+use station::StationError;
+use std::sync::mpsc::{self, Sender};
+use std::thread;
+use std::time::{Duration, Instant};
+use std::{io, process};
 use ureq::Agent;
-use urlencoding::encode;
+use warnings::Warning;
 
-static MOON_PHASE_ART_DIR: Dir<'_> = include_dir!("$CARGO_MANIFEST_DIR/moon-phase-art");
+/// Narrowest a 4-cast card gets before fewer cards are shown
+const MIN_CARD_WIDTH: u16 = 30;
 
-/// Daily forecast data
-#[derive(Serialize, Deserialize, Debug)]
-struct OpenMeteoTimeAndCode {
-    time: Vec<String>,
-    weather_code: Vec<i32>,
-    temperature_2m_max: Vec<f32>,
-    temperature_2m_min: Vec<f32>,
-    apparent_temperature_max: Vec<f32>,
-    apparent_temperature_min: Vec<f32>,
-    precipitation_probability_mean: Vec<i32>,
-}
-
-/// Raw hourly data
-#[derive(Serialize, Deserialize, Debug)]
-struct OpenMeteoHourlyData {
-    time: Vec<String>,
-    weather_code: Vec<i32>,
-    temperature_2m: Vec<f32>,
-}
-
-/// Today's weather data
-#[derive(Serialize, Deserialize, Debug, Default)]
-struct CurrentWeatherData {
-    temperature_2m: f32,
-    apparent_temperature: f32,
-    weather_code: i32,
-}
-
-/// Combination forecast including daily, hourly, and current
-#[derive(Serialize, Deserialize, Debug)]
-struct OpenMeteoRawForecast {
-    daily: OpenMeteoTimeAndCode,
-    hourly: OpenMeteoHourlyData,
-    current: CurrentWeatherData,
-}
-
-/// Single day/weather condition
-/// NOTE: Temperature values already include a degree symbol (U+00B0)
-#[derive(Serialize, Deserialize, Debug)]
-struct OpenMeteoPeriod {
-    date: String,
-    weather: String,
-    temperature_max: String,
-    temperature_min: String,
-    apparent_temperature_max: String,
-    apparent_temperature_min: String,
-    precipitation_probability: String,
-}
+/// Lines of the hourly table one notch of the mouse wheel scrolls
+const WHEEL_LINES: isize = 3;
 
-/// Forecast data by the hour
-#[derive(Serialize, Deserialize, Debug)]
-struct OpenMeteoHourly {
-    datetime: String,
-    temperature: String,
-    weather: String,
-}
+/// How often the TUI checks on its background fetches while any are running
+const UPDATE_INTERVAL: Duration = Duration::from_millis(100);
 
-/// Final, reformatted forecast with daily and current weather
-#[derive(Serialize, Deserialize, Debug, Default)]
-struct OpenMeteoForecast {
-    periods: Vec<OpenMeteoPeriod>,
-    current: CurrentWeatherData,
-    hourly: Vec<OpenMeteoHourly>,
-}
+/// Below either size the Overview stacks its panels in a single column
+const COMPACT_WIDTH: u16 = 100;
+const COMPACT_HEIGHT: u16 = 30;
+
+/// From both sizes on the Overview gets a sidebar with every day and the air quality
+const EXPANDED_WIDTH: u16 = 180;
+const EXPANDED_HEIGHT: u16 = 40;
+const SIDEBAR_WIDTH: u16 = 40;
+
+/// Height of the 4-cast when it's stacked under the other panels
+const COMPACT_FORECAST_HEIGHT: u16 = 14;
+const MIN_CHART_HEIGHT: u16 = 8;
+
+static MOON_PHASE_ART_DIR: Dir<'_> = include_dir!("$CARGO_MANIFEST_DIR/moon-phase-art");
 
 /// Moon phase data for a given date
-#[derive(Serialize, Deserialize, Debug)]
+#[derive(Serialize, Deserialize, Debug, Default, Clone)]
 struct MoonPhase {
     date: String,
     phase: String,
     illumination: String,
 }
 
-/// Raw phase data from ViewBits
-#[derive(Serialize, Deserialize, Debug)]
-struct RawMoonPhaseData {
-    phases: Vec<MoonPhase>,
+/// Formats a temperature with a degree symbol (U+00B0)
+fn degrees(temperature: f32) -> String {
+    format!("{}\u{00B0}", temperature)
+}
+
+/// Format a chance of rain, with a dash when the provider has none
+fn percent(probability: Option<i32>) -> String {
+    probability.map_or(String::from("\u{2013}"), |value| format!("{}%", value))
+}
+
+/// Format a wind gust speed, with a dash when the provider has none
+fn gusts(speed: Option<f32>) -> String {
+    speed.map_or(String::from("\u{2013}"), |value| format!("{:.0}", value))
 }
 
 /// Create the "Right Now" weather table
-fn create_right_now_table(forecast: &OpenMeteoForecast) -> Table {
+/// The compact variant drops the padding above and below the rows
+fn create_right_now_table<'a>(
+    forecast: &Forecast,
+    station_error: Option<&str>,
+    theme: &Theme,
+    compact: bool,
+) -> Table<'a> {
     let widths = [Constraint::Length(15), Constraint::Fill(1)];
 
-    let rows = [
+    let mut rows = vec![
         Row::new(vec![
             Cell::from("Current Temp:"),
-            Cell::from(
-                Text::from(format!("{}\u{00B0}", forecast.current.temperature_2m)).right_aligned(),
-            ),
+            Cell::from(Text::from(degrees(forecast.current.temperature)).right_aligned()),
         ]),
         Row::new(vec![
             Cell::from("Feels Like:"),
-            Cell::from(
-                Text::from(format!("{}\u{00B0}", forecast.current.apparent_temperature))
-                    .right_aligned(),
-            ),
+            Cell::from(Text::from(degrees(forecast.current.apparent_temperature)).right_aligned()),
         ]),
         Row::new(vec![
             Cell::from("High:"),
-            Cell::from(
-                Text::from(format!("{}", forecast.periods[0].temperature_max)).right_aligned(),
-            ),
+            Cell::from(Text::from(degrees(forecast.periods[0].temperature_max)).right_aligned()),
         ]),
         Row::new(vec![
             Cell::from("Low:"),
-            Cell::from(
-                Text::from(format!("{}", forecast.periods[0].temperature_min)).right_aligned(),
-            ),
+            Cell::from(Text::from(degrees(forecast.periods[0].temperature_min)).right_aligned()),
         ]),
         Row::new(vec![
             Cell::from("Weather Summary:"),
-            Cell::from(Text::from(format!("{}", forecast.periods[0].weather)).right_aligned()),
+            Cell::from(Text::from(forecast.periods[0].weather.clone()).right_aligned()),
         ]),
         Row::new(vec![
             Cell::from("Chance of Rain:"),
             Cell::from(
-                Text::from(format!(
-                    "{}%",
-                    forecast.periods[0].precipitation_probability
-                ))
-                .right_aligned(),
+                Text::from(percent(forecast.periods[0].precipitation_probability)).right_aligned(),
             ),
         ]),
     ];
+    // Observations name the station they come from
+    if let Some(station) = &forecast.current.station {
+        rows.push(Row::new(vec![
+            Cell::from("Station:"),
+            Cell::from(Text::from(station.describe()).right_aligned()),
+        ]));
+    }
+    if let Some(error) = station_error {
+        rows.push(
+            Row::new(vec![
+                Cell::from("Station Error:"),
+                Cell::from(Text::from(error.to_string()).right_aligned()),
+            ])
+            .dim(),
+        );
+    }
 
-    return Table::new(rows, widths).column_spacing(1).block(
+    Table::new(rows, widths).column_spacing(1).block(
         Block::default()
             .borders(Borders::ALL)
-            .padding(Padding::new(1, 1, 2, 1)) //uniform(1))
-            .title(Line::from(" Right Now ").light_blue().centered().bold()),
+            .padding(if compact {
+                Padding::horizontal(1)
+            } else {
+                Padding::new(1, 1, 2, 1)
+            })
+            // The title says where the conditions come from, so it's never cut off
+            .title(
+                Line::from(format!(
+                    " Right Now \u{b7} {} ",
+                    forecast.current.source.label()
+                ))
+                .fg(Theme::color(&theme.right_now))
+                .centered()
+                .bold(),
+            ),
+    )
+}
+
+/// Where a bordered `Chart` plots its data, as ratatui lays it out: the y labels (or the part of
+/// the first x label left of the y axis) and the axis on the left, the x labels and axis below
+fn plot_area(area: Rect, y_labels: &[String], first_x_label: &str) -> Rect {
+    let inner = Block::bordered().inner(area);
+    let labels_width = y_labels
+        .iter()
+        .map(String::len)
+        .max()
+        .unwrap_or_default()
+        .max(first_x_label.len().saturating_sub(1)) as u16;
+    let left = labels_width.min(inner.width / 3) + 1;
+    Rect::new(
+        inner.x + left,
+        inner.y,
+        inner.width.saturating_sub(left),
+        inner.height.saturating_sub(2),
+    )
+}
+
+/// Index of the point plotted nearest to the pointer, if it's over the plot
+fn hovered_point(
+    pointer: Option<Position>,
+    plot: Rect,
+    x_bounds: [f64; 2],
+    points: &[(f64, f64)],
+) -> Option<usize> {
+    let pointer = pointer.filter(|pointer| plot.contains(*pointer))?;
+    let share = f64::from(pointer.x - plot.x) / f64::from(plot.width.saturating_sub(1).max(1));
+    let x = x_bounds[0] + share * (x_bounds[1] - x_bounds[0]);
+    points
+        .iter()
+        .enumerate()
+        .min_by(|(_, a), (_, b)| (a.0 - x).abs().total_cmp(&(b.0 - x).abs()))
+        .map(|(i, _)| i)
+}
+
+/// A box beside the pointer with the time, temperature and weather of an hour, kept within
+/// the chart's `area`
+fn render_tooltip(frame: &mut Frame, area: Rect, pointer: Position, hour: &Hour) {
+    // Without a valid date the time can't be shown, so the box only has the weather
+    let date = hour.datetime.get(..10).unwrap_or_default();
+    let time = get_day_from_date(date).map(|day| {
+        Line::from(format!(
+            "{} {} {}",
+            day,
+            date.get(5..).unwrap_or_default(),
+            hour.datetime.get(11..16).unwrap_or_default()
+        ))
+        .bold()
+    });
+    let lines: Vec<Line> = time
+        .into_iter()
+        .chain([
+            Line::from(degrees(hour.temperature)),
+            Line::from(format!(
+                "{} {}",
+                weather_icon(hour.weather_code),
+                hour.weather
+            )),
+        ])
+        .collect();
+    let width = lines.iter().map(Line::width).max().unwrap_or_default() as u16 + 4;
+    let height = lines.len() as u16 + 2;
+    // Right of the pointer, or left of it where the chart ends
+    let x = if pointer.x + 2 + width <= area.right() {
+        pointer.x + 2
+    } else {
+        pointer.x.saturating_sub(width + 1).max(area.x)
+    };
+    let y = pointer
+        .y
+        .min(area.bottom().saturating_sub(height))
+        .max(area.y);
+    let tooltip = Rect::new(x, y, width, height).intersection(area);
+    frame.render_widget(Clear, tooltip);
+    frame.render_widget(
+        Paragraph::new(lines).block(Block::bordered().padding(Padding::horizontal(1))),
+        tooltip,
     );
 }
 
+/// Range of a temperature chart's y axis, 5 degrees beyond the lowest and highest
+/// Without any hours the range is fixed, like `day::bounds` does, instead of infinite
+fn temperature_bounds(temps: &[f64]) -> [f64; 2] {
+    if temps.is_empty() {
+        return [0.0, 20.0];
+    }
+    let min_temp = temps.iter().cloned().fold(f64::INFINITY, f64::min);
+    let max_temp = temps.iter().cloned().fold(f64::NEG_INFINITY, f64::max);
+    [(min_temp - 5.0).floor(), (max_temp + 5.0).ceil()]
+}
+
 /// Renders the scatterplot to show the temperature for the rest of the current day
-fn render_temperature_scatterplot(frame: &mut Frame, area: Rect, hourly: &Vec<OpenMeteoHourly>) {
-    let mut today_hourly: [(f64, f64); 24] = [(0., 0.); 24];
-    let mut count: usize = 0;
-    for i in hourly {
+/// The hour nearest the pointer is highlighted and described in a tooltip
+fn render_temperature_scatterplot(
+    frame: &mut Frame,
+    area: Rect,
+    hourly: &[Hour],
+    alert_hours: &[bool],
+    pointer: Option<Position>,
+    config: &Config,
+) {
+    // Providers that start at the current hour have fewer than 24 hours left today
+    let today = hourly
+        .first()
+        .map_or("", |hour| hour.datetime.get(..10).unwrap_or_default());
+    let mut today_hourly: Vec<(f64, f64)> = Vec::new();
+    for i in hourly
+        .iter()
+        .take_while(|hour| hour.datetime.starts_with(today))
+    {
         let time_split = i.datetime.split("T");
         let time_pieces = time_split.collect::<Vec<_>>();
         let hour_split = time_pieces[1].split(":");
         let hour_pieces = hour_split.collect::<Vec<_>>();
         let hour_as_float = hour_pieces[0].parse::<f64>().unwrap();
-        let mut temp_clone = i.temperature.clone();
-        temp_clone.pop();
-        let temp_as_float = temp_clone.parse::<f64>().unwrap();
-        today_hourly[count] = (hour_as_float, temp_as_float);
-        count += 1;
-        if count == 24 {
-            break;
-        }
+        let temp_as_float = i.temperature as f64;
+        today_hourly.push((hour_as_float, temp_as_float));
     }
 
     let temps: Vec<f64> = today_hourly.iter().map(|(_, temp)| *temp).collect();
-    let min_temp = temps.iter().cloned().fold(f64::INFINITY, f64::min);
-    let max_temp = temps.iter().cloned().fold(f64::NEG_INFINITY, f64::max);
-
-    let y_min = (min_temp - 5.0).floor();
-    let y_max = (max_temp + 5.0).ceil();
+    let [y_min, y_max] = temperature_bounds(&temps);
 
     let step = (y_max - y_min) / 4.0;
     let y_labels = (0..5)
@@ -190,14 +308,43 @@ fn render_temperature_scatterplot(frame: &mut Frame, area: Rect, hourly: &Vec<Op
     let dataset = Dataset::default()
         .marker(Marker::Dot)
         .graph_type(GraphType::Scatter)
-        .style(Style::new().yellow())
+        .style(Style::new().fg(Theme::color(&config.theme.data)))
         .data(&today_hourly);
 
-    let chart = Chart::new(vec![dataset])
-        .block(Block::bordered().title(Line::from(" Today's Temps ").cyan().centered().bold()))
+    // Hours matching an alert rule are drawn again on top in the alert colour
+    let alert_points: Vec<(f64, f64)> = today_hourly
+        .iter()
+        .zip(alert_hours)
+        .filter(|(_, alert)| **alert)
+        .map(|(point, _)| *point)
+        .collect();
+    let alert_dataset = Dataset::default()
+        .marker(Marker::Dot)
+        .graph_type(GraphType::Scatter)
+        .style(Style::new().fg(Theme::color(&config.theme.alert)))
+        .data(&alert_points);
+
+    let plot = plot_area(area, &y_labels, "00:00");
+    let hovered = hovered_point(pointer, plot, [0., 23.], &today_hourly);
+    let hovered_points: Vec<(f64, f64)> = hovered.map(|i| today_hourly[i]).into_iter().collect();
+    let hovered_dataset = Dataset::default()
+        .marker(Marker::Block)
+        .graph_type(GraphType::Scatter)
+        .style(Style::new().fg(Theme::color(&config.theme.selection)))
+        .data(&hovered_points);
+
+    let chart = Chart::new(vec![dataset, alert_dataset, hovered_dataset])
+        .block(
+            Block::bordered().title(
+                Line::from(" Today's Temps ")
+                    .fg(Theme::color(&config.theme.today_chart))
+                    .centered()
+                    .bold(),
+            ),
+        )
         .y_axis(
             Axis::default()
-                .title("Temp (\u{00B0}C)")
+                .title(format!("Temp ({})", config.units.temperature.symbol()))
                 .bounds([y_min, y_max])
                 .style(Style::default().fg(Color::Gray))
                 .labels(y_labels),
@@ -212,270 +359,1177 @@ fn render_temperature_scatterplot(frame: &mut Frame, area: Rect, hourly: &Vec<Op
         .hidden_legend_constraints((Constraint::Ratio(1, 2), Constraint::Ratio(1, 2)));
 
     frame.render_widget(chart, area);
+    if let (Some(i), Some(pointer)) = (hovered, pointer) {
+        render_tooltip(frame, area, pointer, &hourly[i]);
+    }
+}
+
+/// Where each day starts on the fortnight chart: the index of its first hour, with its "MM-DD"
+/// Days without hours, e.g. past MET Norway's hourly steps, are left out
+fn day_ticks(hourly: &[Hour], daily: &[Period]) -> Vec<(usize, String)> {
+    daily
+        .iter()
+        .filter_map(|period| {
+            let index = hourly
+                .iter()
+                .position(|hour| hour.datetime.starts_with(&period.date))?;
+            Some((
+                index,
+                period.date.get(5..).unwrap_or(&period.date).to_string(),
+            ))
+        })
+        .collect()
 }
 
+/// Renders the scatterplot of every hour of the forecast, with a tooltip like the day's
 fn render_fortnight_scatterplot(
     frame: &mut Frame,
     area: Rect,
-    hourly: &Vec<OpenMeteoHourly>,
-    daily: &Vec<OpenMeteoPeriod>,
+    hourly: &[Hour],
+    daily: &[Period],
+    alert_hours: &[bool],
+    pointer: Option<Position>,
+    config: &Config,
 ) {
-    const DATA_LENGTH: usize = 336;
-
-    let mut fortnight_hourly: [(f64, f64); DATA_LENGTH] = [(0., 0.); DATA_LENGTH];
-    let mut count: usize = 0;
-    for i in hourly {
-        let mut temp_clone = i.temperature.clone();
-        temp_clone.pop();
-        let temp_as_float = temp_clone.parse::<f64>().unwrap();
-        // Scale the x-coordinate to fit within 0..336 for 14 days of hourly data
+    // One point per fetched hour (336 for the default 14 days)
+    let data_length = hourly.len();
+
+    let mut fortnight_hourly: Vec<(f64, f64)> = Vec::with_capacity(data_length);
+    for (count, i) in hourly.iter().enumerate() {
+        let temp_as_float = i.temperature as f64;
         let x_position = count as f64;
-        fortnight_hourly[count] = (x_position, temp_as_float);
-        count += 1;
+        fortnight_hourly.push((x_position, temp_as_float));
     }
 
-    let days: Vec<String> = daily
+    // A consensus forecast also shows the lowest and highest provider around each mean
+    let spread_points: Vec<(f64, f64)> = hourly
         .iter()
-        .map(|l| {
-            let parts: Vec<&str> = l.date.split('-').collect();
-            if parts.len() == 3 {
-                format!("{}-{}", parts[1], parts[2]) // MM-DD
-            } else {
-                l.date.clone() // fallback in case the format is unexpected
-            }
-        })
+        .enumerate()
+        .filter_map(|(count, hour)| Some((count as f64, hour.temperature_range?)))
+        .flat_map(|(x_position, [low, high])| [(x_position, low as f64), (x_position, high as f64)])
         .collect();
 
-    let x_labels: Vec<Line> = (0..14) // 14 days in total
-        .map(|i| {
-            let day = &days[i];
-            Line::from(day.as_str())
-        })
+    let temps: Vec<f64> = fortnight_hourly
+        .iter()
+        .chain(&spread_points)
+        .map(|(_, temp)| *temp)
         .collect();
-
-    let temps: Vec<f64> = fortnight_hourly.iter().map(|(_, temp)| *temp).collect();
-    let min_temp = temps.iter().cloned().fold(f64::INFINITY, f64::min);
-    let max_temp = temps.iter().cloned().fold(f64::NEG_INFINITY, f64::max);
-
-    let y_min = (min_temp - 5.0).floor();
-    let y_max = (max_temp + 5.0).ceil();
+    let [y_min, y_max] = temperature_bounds(&temps);
 
     let step = (y_max - y_min) / 4.0;
     let y_labels = (0..5)
         .map(|i| format!("{:.0}", y_min + i as f64 * step))
         .collect::<Vec<_>>();
 
+    let spread_dataset = Dataset::default()
+        .marker(Marker::Braille)
+        .graph_type(GraphType::Scatter)
+        .style(Style::new().fg(Theme::color(&config.theme.spread)))
+        .data(&spread_points);
+
     let dataset = Dataset::default()
         .marker(Marker::Dot)
         .graph_type(GraphType::Scatter)
-        .style(Style::new().yellow())
+        .style(Style::new().fg(Theme::color(&config.theme.data)))
         .data(&fortnight_hourly);
 
-    let chart = Chart::new(vec![dataset])
-        .block(Block::bordered().title(Line::from(" Fortnights's Temps ").cyan().centered().bold()))
-        .y_axis(
-            Axis::default()
-                .title("Temp (\u{00B0}C)")
-                .bounds([y_min, y_max])
-                .style(Style::default().fg(Color::Gray))
-                .labels(y_labels),
-        )
-        .x_axis(
-            Axis::default()
-                .title("Days")
-                .bounds([0., DATA_LENGTH as f64])
-                .style(Style::default().fg(Color::Gray))
-                .labels(x_labels),
-        )
-        .hidden_legend_constraints((Constraint::Ratio(1, 2), Constraint::Ratio(1, 2)));
+    let alert_points: Vec<(f64, f64)> = fortnight_hourly
+        .iter()
+        .zip(alert_hours)
+        .filter(|(_, alert)| **alert)
+        .map(|(point, _)| *point)
+        .collect();
+    let alert_dataset = Dataset::default()
+        .marker(Marker::Dot)
+        .graph_type(GraphType::Scatter)
+        .style(Style::new().fg(Theme::color(&config.theme.alert)))
+        .data(&alert_points);
+
+    // An empty axis would have no width
+    let x_bounds = [0., data_length.max(1) as f64];
+    let plot = plot_area(area, &y_labels, "");
+    let hovered = hovered_point(pointer, plot, x_bounds, &fortnight_hourly);
+    let hovered_points: Vec<(f64, f64)> =
+        hovered.map(|i| fortnight_hourly[i]).into_iter().collect();
+    let hovered_dataset = Dataset::default()
+        .marker(Marker::Block)
+        .graph_type(GraphType::Scatter)
+        .style(Style::new().fg(Theme::color(&config.theme.selection)))
+        .data(&hovered_points);
+
+    let chart = Chart::new(vec![
+        spread_dataset,
+        dataset,
+        alert_dataset,
+        hovered_dataset,
+    ])
+    .block(
+        Block::bordered().title(
+            Line::from(" Fortnights's Temps ")
+                .fg(Theme::color(&config.theme.fortnight_chart))
+                .centered()
+                .bold(),
+        ),
+    )
+    .y_axis(
+        Axis::default()
+            .title(format!("Temp ({})", config.units.temperature.symbol()))
+            .bounds([y_min, y_max])
+            .style(Style::default().fg(Color::Gray))
+            .labels(y_labels),
+    )
+    .x_axis(
+        Axis::default()
+            .title("Days")
+            .bounds(x_bounds)
+            .style(Style::default().fg(Color::Gray))
+            // Only keeps the row free for the days drawn below
+            .labels(["", ""]),
+    )
+    .hidden_legend_constraints((Constraint::Ratio(1, 2), Constraint::Ratio(1, 2)));
 
     frame.render_widget(chart, area);
+    // ratatui spreads axis labels evenly, but a day starts wherever its first hour is plotted
+    let labels_row = plot.bottom() + 1;
+    let mut free_from = plot.x;
+    for (index, label) in day_ticks(hourly, daily) {
+        let share = index as f64 / (x_bounds[1] - x_bounds[0]);
+        let x = plot.x + (share * f64::from(plot.width.saturating_sub(1))).round() as u16;
+        let end = x + label.len() as u16;
+        // Labels that would overlap the previous one or the border are left out
+        if labels_row >= area.bottom().saturating_sub(1)
+            || x < free_from
+            || end > area.right().saturating_sub(1)
+        {
+            continue;
+        }
+        frame
+            .buffer_mut()
+            .set_string(x, labels_row, &label, Style::default().fg(Color::Gray));
+        free_from = end + 1;
+    }
+    if let (Some(i), Some(pointer)) = (hovered, pointer) {
+        render_tooltip(frame, area, pointer, &hourly[i]);
+    }
 }
 
-/// Creates the cards for the 4-cast section
-fn create_weather_card(period: &OpenMeteoPeriod) -> Table {
+/// Renders a card of the 4-cast section
+/// The selected card, whose day Enter opens, gets a thick border
+/// Days matching alert rules get a border in the alert colour and list the rules
+/// Days on which the providers of a consensus differ by more than its threshold are marked
+/// The provider's narrative, if it has one, is wrapped below the table
+fn render_weather_card(
+    frame: &mut Frame,
+    area: Rect,
+    period: &Period,
+    alert_names: &[String],
+    selected: bool,
+    config: &Config,
+) {
+    let theme = &config.theme;
     let widths = [Constraint::Length(15), Constraint::Fill(1)];
 
-    let rows = [
+    let mut rows = vec![
         Row::new(vec![
             Cell::from("High:"),
-            Cell::from(Text::from(format!("{}", period.temperature_max)).right_aligned()),
+            Cell::from(Text::from(degrees(period.temperature_max)).right_aligned()),
         ]),
         Row::new(vec![
             Cell::from("Apparent High:"),
-            Cell::from(Text::from(format!("{}", period.apparent_temperature_max)).right_aligned()),
+            Cell::from(Text::from(degrees(period.apparent_temperature_max)).right_aligned()),
         ]),
         Row::new(vec![
             Cell::from("Low:"),
-            Cell::from(Text::from(format!("{}", period.temperature_min)).right_aligned()),
+            Cell::from(Text::from(degrees(period.temperature_min)).right_aligned()),
         ]),
         Row::new(vec![
             Cell::from("Apparent Low:"),
-            Cell::from(Text::from(format!("{}", period.apparent_temperature_min)).right_aligned()),
+            Cell::from(Text::from(degrees(period.apparent_temperature_min)).right_aligned()),
         ]),
         Row::new(vec![
             Cell::from("Weather:"),
-            Cell::from(Text::from(format!("{}", period.weather)).right_aligned()),
+            Cell::from(Text::from(period.weather.clone()).right_aligned()),
         ]),
         Row::new(vec![
             Cell::from("Chance of Rain:"),
-            Cell::from(
-                Text::from(format!("{}%", period.precipitation_probability)).right_aligned(),
-            ),
+            Cell::from(Text::from(percent(period.precipitation_probability)).right_aligned()),
         ]),
     ];
 
-    let day = get_day_from_date(&period.date);
+    if let Some(disagreement) = period
+        .disagreement
+        .filter(|disagreement| *disagreement > config.consensus.threshold)
+    {
+        rows.push(
+            Row::new(vec![
+                Cell::from("Providers:"),
+                Cell::from(
+                    Text::from(format!("disagree by {}", degrees(disagreement))).right_aligned(),
+                ),
+            ])
+            .style(Style::new().fg(Theme::color(&theme.disagreement))),
+        );
+    }
+
+    let mut border_style = Style::default();
+    if !alert_names.is_empty() {
+        let alert_color = Theme::color(&theme.alert);
+        rows.push(
+            Row::new(vec![
+                Cell::from("Alerts:"),
+                Cell::from(Text::from(alert_names.join(", ")).right_aligned()),
+            ])
+            .style(Style::new().fg(alert_color)),
+        );
+        border_style = border_style.fg(alert_color);
+    }
 
-    return Table::new(rows, widths).column_spacing(1).block(
-        Block::default()
-            .borders(Borders::ALL)
-            .padding(Padding::new(0, 0, 1, 0)) //(2,2,3,0))
-            .title(
-                Line::from(format!(" ({}) {} ", day, period.date))
+    // The selected card keeps an alert colour but gets a thick border either way
+    let mut border_type = BorderType::Plain;
+    if selected {
+        border_type = BorderType::Thick;
+        if alert_names.is_empty() {
+            border_style = border_style.fg(Theme::color(&theme.selection));
+        }
+    }
+
+    let day = get_day_from_date(&period.date).unwrap_or_default();
+    let block = Block::default()
+        .borders(Borders::ALL)
+        .border_type(border_type)
+        .border_style(border_style)
+        .padding(Padding::new(0, 0, 1, 0)) //(2,2,3,0))
+        .title(
+            Line::from(format!(" ({}) {} ", day, period.date))
+                .centered()
+                .bold(),
+        );
+    let inner_area = block.inner(area);
+    frame.render_widget(block, area);
+
+    let [table_area, narrative_area] = Layout::vertical([
+        Constraint::Length(rows.len() as u16 + 1),
+        Constraint::Fill(1),
+    ])
+    .areas(inner_area);
+    frame.render_widget(Table::new(rows, widths).column_spacing(1), table_area);
+    if !period.narrative.is_empty() {
+        frame.render_widget(
+            Paragraph::new(period.narrative.as_str())
+                .wrap(Wrap { trim: true })
+                .dim(),
+            narrative_area,
+        );
+    }
+}
+
+/// Returns day (Monday, Tuesday, etc) for given date (YYYY-MM-DD), None if it isn't a valid date
+fn get_day_from_date(date: &str) -> Option<String> {
+    let date = NaiveDate::parse_from_str(date, "%Y-%m-%d").ok()?;
+    Some(date.weekday().to_string())
+}
+
+/// Creates the panel listing active alerts with the time window they apply to
+/// Hooks that failed the last time they ran come first, where the alerts can't push them out of
+/// view
+fn create_alerts_panel<'a>(
+    alerts: &Alerts,
+    hook_errors: &[String],
+    config: &Config,
+) -> Paragraph<'a> {
+    let mut lines: Vec<Line> = hook_errors
+        .iter()
+        .map(|err| Line::from(format!("Hook: {}", err)).dim())
+        .collect();
+    lines.extend(alerts.windows.iter().map(|window| {
+        Line::from(format!(
+            "\u{26A0} {}",
+            alerts::describe_window(window, &config.units)
+        ))
+    }));
+
+    Paragraph::new(lines)
+        .style(Style::new().fg(Theme::color(&config.theme.alert)))
+        .wrap(Wrap { trim: true })
+        .block(
+            Block::bordered().title(
+                Line::from(" Alerts ")
+                    .fg(Theme::color(&config.theme.alert))
                     .centered()
                     .bold(),
             ),
+        )
+}
+
+/// Most warnings shown in the banner before the rest are summarised
+const MAX_BANNER_WARNINGS: usize = 3;
+
+/// Creates the banner of official warnings, one line each with a severity badge, and its height
+fn create_warnings_banner<'a>(
+    warnings: &[&Warning],
+    error: Option<&str>,
+    theme: &Theme,
+) -> (Paragraph<'a>, u16) {
+    let now = chrono::Utc::now();
+    let mut lines: Vec<Line> = warnings
+        .iter()
+        .take(MAX_BANNER_WARNINGS)
+        .map(|warning| {
+            let color = warning.severity.color(theme);
+            Line::from(vec![
+                Span::styled(
+                    format!(" {} ", warning.severity.label()),
+                    Style::new().bg(color).fg(Color::Black).bold(),
+                ),
+                Span::raw(format!(
+                    " {}: {} \u{2014} {}, {}",
+                    warning.event,
+                    warning.headline,
+                    warning.area,
+                    warning.period(now)
+                )),
+            ])
+        })
+        .collect();
+    if warnings.len() > MAX_BANNER_WARNINGS {
+        lines.push(Line::from(format!(
+            "+{} more",
+            warnings.len() - MAX_BANNER_WARNINGS
+        )));
+    }
+    if let Some(error) = error {
+        lines.push(Line::from(format!("Could not load warnings: {}", error)).dim());
+    }
+
+    // The border takes the colour of the most severe warning
+    let border = warnings
+        .first()
+        .map_or(Color::Reset, |warning| warning.severity.color(theme));
+    let height = lines.len() as u16 + 2;
+    let banner = Paragraph::new(lines).block(
+        Block::bordered()
+            .border_style(Style::new().fg(border))
+            .title(Line::from(" Warnings ").centered().bold()),
     );
+    (banner, height)
 }
 
-/// Returns day (Monday, Tuesday, etc) for given date (YYYY-MM-DD)
-fn get_day_from_date(date: &String) -> String {
-    let date_pieces: Vec<&str> = date.split('-').collect();
-    // Parse the day, month, and year as integers
-    let year: i32 = date_pieces[0].parse().expect("Invalid year");
-    let month: u32 = date_pieces[1].parse().expect("Invalid month");
-    let day: u32 = date_pieces[2].parse().expect("Invalid day");
+/// Layouts of the Overview tab, picked by the space it gets
+#[derive(Debug, Clone, Copy, PartialEq)]
+enum Breakpoint {
+    /// Panels stacked in a single column
+    Compact,
+    Medium,
+    /// The medium layout with a sidebar
+    Expanded,
+}
 
-    // Create a NaiveDate object with the provided input using from_ymd_opt
-    match NaiveDate::from_ymd_opt(year, month, day) {
-        Some(date) => {
-            return date.weekday().to_string();
+impl Breakpoint {
+    fn of(area: Rect) -> Breakpoint {
+        if area.width < COMPACT_WIDTH || area.height < COMPACT_HEIGHT {
+            Breakpoint::Compact
+        } else if area.width >= EXPANDED_WIDTH && area.height >= EXPANDED_HEIGHT {
+            Breakpoint::Expanded
+        } else {
+            Breakpoint::Medium
         }
-        None => {
-            println!("Invalid date provided. Please ensure the date is valid.");
-            return String::from("");
+    }
+}
+
+/// The TUI's screens, switched between with Tab or their number
+#[derive(Serialize, Debug, Default, Clone, Copy, PartialEq)]
+enum Tab {
+    #[default]
+    Overview,
+    Hourly,
+    Daily,
+    Astronomy,
+    Air,
+}
+
+impl Tab {
+    const ALL: [Tab; 5] = [
+        Tab::Overview,
+        Tab::Hourly,
+        Tab::Daily,
+        Tab::Astronomy,
+        Tab::Air,
+    ];
+
+    fn title(&self) -> &'static str {
+        match self {
+            Tab::Overview => "Overview",
+            Tab::Hourly => "Hourly",
+            Tab::Daily => "Daily",
+            Tab::Astronomy => "Astronomy",
+            Tab::Air => "Air",
         }
     }
+
+    fn index(&self) -> usize {
+        Tab::ALL
+            .iter()
+            .position(|tab| tab == self)
+            .unwrap_or_default()
+    }
+
+    /// The tab `step` places to the right, wrapping around
+    fn cycle(&self, step: isize) -> Tab {
+        let count = Tab::ALL.len() as isize;
+        Tab::ALL[(self.index() as isize + step).rem_euclid(count) as usize]
+    }
+}
+
+/// What a fetch `App` ran in the background came back with
+enum Update {
+    /// The warnings that could be fetched, and why the others couldn't
+    Warnings(Vec<Warning>, Option<String>),
+    Air(Result<air::AirQuality, String>),
+    /// None when the forecast couldn't be fetched; the shown one is kept then
+    Forecast(Option<Box<Refresh>>),
+    /// Why the alert hooks failed, if they did
+    Hooks(Vec<String>),
+}
+
+/// A refreshed forecast with the station's conditions
+struct Refresh {
+    forecast: Forecast,
+    moon_phase: MoonPhase,
+    /// Why the station couldn't be read; the provider's conditions are shown instead
+    station_error: Option<String>,
+    /// Whether this is the refresh interval's fetch, which fires the hooks, rather than the
+    /// station's
+    full: bool,
 }
 
 /// Application state data
 #[derive(Serialize, Debug, Default)]
 #[serde(rename_all = "camelCase")]
 struct App {
-    open_meteo_forecast: OpenMeteoForecast,
+    config: Config,
+    forecast: Forecast,
     todays_weather_description: String,
+    moon_phase: MoonPhase,
     moon_phase_art: String,
+    #[serde(skip)]
+    alerts: Alerts,
+    /// Official warnings for the location, most severe first
+    #[serde(skip)]
+    warnings: Vec<Warning>,
+    /// Why the provider's alerts or the feed couldn't be fetched at the last refresh
+    #[serde(skip)]
+    warnings_error: Option<String>,
+    /// Why the alert hooks failed the last time they ran
+    #[serde(skip)]
+    hook_errors: Vec<String>,
+    /// Why the station couldn't be read at the last refresh
+    #[serde(skip)]
+    station_error: Option<String>,
+    /// How many background fetches haven't come back yet
+    #[serde(skip)]
+    pending: usize,
+    /// Whether a forecast refresh is running; the next one waits for it
+    #[serde(skip)]
+    refreshing: bool,
+    #[serde(skip)]
+    air: Option<air::AirQuality>,
+    /// Why the last air quality refresh failed; the previous data is kept meanwhile
+    #[serde(skip)]
+    air_error: Option<String>,
+    /// When the air quality was last requested; it's only fetched while it's on screen
+    #[serde(skip)]
+    air_fetched: Option<Instant>,
+    /// Whether the last draw showed the air quality
+    #[serde(skip)]
+    air_shown: bool,
+    tab: Tab,
+    /// The Overview's layout from the config; the built-in one if unset or invalid
+    #[serde(skip)]
+    layout: Option<OverviewLayout>,
+    /// Why the configured layout was rejected
+    #[serde(skip)]
+    layout_warning: Option<String>,
+    /// Index into `forecast.periods` of the selected day: a 4-cast card, or the day shown in
+    /// the drill-down view
+    selected_day: usize,
+    /// Whether the drill-down view of the selected day replaces the tab's screen
+    day_view: bool,
+    /// Index into `forecast.periods` of the leftmost 4-cast card; kept in view of the selection
+    /// while drawing, as only then the number of cards fitting the width is known
+    first_card: usize,
+    /// First line of the hourly table that's shown
+    hourly_offset: usize,
+    /// How many lines of the hourly table fit, as of the last draw
+    hourly_page: usize,
+    /// How many 4-cast cards fit, as of the last draw
+    cards: usize,
+    /// Where each 4-cast card was last drawn, with the index of its day, for clicks
+    #[serde(skip)]
+    card_areas: Vec<(Rect, usize)>,
+    /// Where the mouse pointer last moved to, for the charts' tooltips
+    #[serde(skip)]
+    pointer: Option<Position>,
     exit: bool,
 }
 
 /// Main Ratatui app for Raijin
 impl App {
     /// Runs the application's main loop until the user quits
+    /// The forecast is refetched every `refresh_interval` minutes; failed refreshes keep the old data
     fn run(
         &mut self,
         terminal: &mut DefaultTerminal,
-        forecast: OpenMeteoForecast,
-        moon_phase_art: String,
+        agent: &Agent,
+        weather_codes: &serde_json::Value,
+        forecast: Forecast,
+        moon_phase: MoonPhase,
     ) -> io::Result<()> {
-        self.open_meteo_forecast = forecast;
-        self.moon_phase_art = moon_phase_art;
+        let (updates, received) = mpsc::channel();
+        self.set_forecast(forecast, moon_phase);
+        self.fire_hooks(agent, &updates);
+        self.publish();
+        self.refresh_warnings(agent, &updates);
+
+        let refresh_interval = Duration::from_secs(self.config.refresh_interval * 60);
+        let mut last_refresh = Instant::now();
+        // The station is read more often; the forecast comes from the cache in between
+        let station_interval = if self.config.station.is_configured() {
+            station::REFRESH_INTERVAL
+        } else {
+            refresh_interval
+        };
+        let mut last_station = Instant::now();
         while !self.exit {
             terminal.draw(|frame| self.draw(frame))?;
-            self.handle_events()?;
+            if self.air_shown
+                && self
+                    .air_fetched
+                    .is_none_or(|fetched| fetched.elapsed() >= refresh_interval)
+            {
+                self.refresh_air(agent, &updates);
+            }
+
+            // Refreshes that are due wait for the running one
+            let mut timeout = if self.refreshing {
+                UPDATE_INTERVAL
+            } else {
+                refresh_interval
+                    .saturating_sub(last_refresh.elapsed())
+                    .min(station_interval.saturating_sub(last_station.elapsed()))
+            };
+            if self.pending > 0 {
+                timeout = timeout.min(UPDATE_INTERVAL);
+            }
+            if event::poll(timeout)? {
+                self.handle_events()?;
+            }
+            for update in received.try_iter() {
+                self.apply(agent, &updates, update);
+            }
+
+            if self.refreshing {
+                // Waiting for the running refresh
+            } else if last_refresh.elapsed() >= refresh_interval {
+                self.refresh_forecast(agent, weather_codes, &updates, true);
+                self.refresh_warnings(agent, &updates);
+                last_refresh = Instant::now();
+                last_station = last_refresh;
+            } else if last_station.elapsed() >= station_interval {
+                self.refresh_forecast(agent, weather_codes, &updates, false);
+                last_station = Instant::now();
+            }
         }
         Ok(())
     }
 
-    fn draw(&self, frame: &mut Frame) {
-        use Constraint::{Percentage, Ratio};
+    /// Replaces the shown forecast and re-evaluates the alert rules against it
+    fn set_forecast(&mut self, forecast: Forecast, moon_phase: MoonPhase) {
+        self.alerts = alerts::evaluate(&self.config.alerts, &forecast);
+        self.forecast = forecast;
+        self.moon_phase_art = get_moon_phase_art(&moon_phase.phase);
+        self.moon_phase = moon_phase;
+        self.select_day(self.selected_day);
+    }
+
+    /// Days the selection can move between: every day in the drill-down view and the daily
+    /// table, otherwise the 4-cast's days from tomorrow on
+    fn selectable_days(&self) -> (usize, usize) {
+        let last = self.forecast.periods.len().saturating_sub(1);
+        if self.day_view || self.tab == Tab::Daily {
+            (0, last)
+        } else {
+            (1.min(last), last)
+        }
+    }
+
+    fn select_day(&mut self, day: usize) {
+        let (first, last) = self.selectable_days();
+        self.selected_day = day.clamp(first, last);
+    }
+
+    /// Runs the alert hooks in the background so slow commands or webhooks don't freeze the UI
+    /// Failures are listed in the Alerts panel
+    fn fire_hooks(&mut self, agent: &Agent, updates: &Sender<Update>) {
+        if self.config.hooks.is_empty() || self.alerts.windows.is_empty() {
+            return;
+        }
+        let agent = agent.clone();
+        let config = self.config.clone();
+        let windows = self.alerts.windows.clone();
+        let now = self.forecast.current.time.clone();
+        let updates = updates.clone();
+        self.pending += 1;
+        thread::spawn(move || {
+            let mut errors: Vec<String> = hooks::fire(&agent, &config, &windows, &now)
+                .iter()
+                .map(ToString::to_string)
+                .collect();
+            // A broken hook fails the same way for every alert
+            errors.dedup();
+            let _ = updates.send(Update::Hooks(errors));
+        });
+    }
+
+    /// Publishes to the MQTT broker in the background, dropping failures as there's nowhere to
+    /// show them while the TUI owns the terminal
+    fn publish(&self) {
+        if self.config.mqtt.host.is_none() {
+            return;
+        }
+        let config = self.config.clone();
+        let forecast = self.forecast.clone();
+        let windows = self.alerts.windows.clone();
+        thread::spawn(move || mqtt::publish(&config, &forecast, &windows));
+    }
+
+    /// Fetches the forecast and reads the station in the background; a `full` refresh also
+    /// fires the hooks once it's in
+    fn refresh_forecast(
+        &mut self,
+        agent: &Agent,
+        weather_codes: &serde_json::Value,
+        updates: &Sender<Update>,
+        full: bool,
+    ) {
+        let agent = agent.clone();
+        let config = self.config.clone();
+        let weather_codes = weather_codes.clone();
+        let updates = updates.clone();
+        self.pending += 1;
+        self.refreshing = true;
+        thread::spawn(move || {
+            let refresh = fetch_observed(&agent, &config, &weather_codes).ok().map(
+                |(forecast, moon_phase, station_error)| {
+                    Box::new(Refresh {
+                        forecast,
+                        moon_phase,
+                        station_error: station_error.map(|err| err.to_string()),
+                        full,
+                    })
+                },
+            );
+            let _ = updates.send(Update::Forecast(refresh));
+        });
+    }
+
+    /// Fetches the official warnings in the background; `apply` shows them once they're in
+    fn refresh_warnings(&mut self, agent: &Agent, updates: &Sender<Update>) {
+        let agent = agent.clone();
+        let config = self.config.clone();
+        let updates = updates.clone();
+        self.pending += 1;
+        thread::spawn(move || {
+            let (warnings, errors) = warnings::fetch(&agent, &config);
+            let errors: Vec<String> = errors.iter().map(ToString::to_string).collect();
+            let error = (!errors.is_empty()).then(|| errors.join("; "));
+            let _ = updates.send(Update::Warnings(warnings, error));
+        });
+    }
+
+    /// Takes in the result of a background fetch
+    fn apply(&mut self, agent: &Agent, updates: &Sender<Update>, update: Update) {
+        self.pending = self.pending.saturating_sub(1);
+        match update {
+            // If nothing came back because of an error, the previous warnings are kept
+            Update::Warnings(warnings, error) => {
+                if error.is_none() || !warnings.is_empty() {
+                    self.warnings = warnings;
+                }
+                self.warnings_error = error;
+            }
+            Update::Air(Ok(air)) => {
+                self.air = Some(air);
+                self.air_error = None;
+            }
+            Update::Air(Err(err)) => self.air_error = Some(err),
+            Update::Hooks(errors) => self.hook_errors = errors,
+            Update::Forecast(refresh) => {
+                self.refreshing = false;
+                if let Some(refresh) = refresh {
+                    let Refresh {
+                        forecast,
+                        moon_phase,
+                        station_error,
+                        full,
+                    } = *refresh;
+                    self.set_forecast(forecast, moon_phase);
+                    self.station_error = station_error;
+                    if full {
+                        self.fire_hooks(agent, updates);
+                    }
+                    self.publish();
+                }
+            }
+        }
+    }
+
+    /// Fetches the air quality in the background, like `refresh_warnings`
+    fn refresh_air(&mut self, agent: &Agent, updates: &Sender<Update>) {
+        let agent = agent.clone();
+        let config = self.config.clone();
+        let updates = updates.clone();
+        self.pending += 1;
+        self.air_fetched = Some(Instant::now());
+        thread::spawn(move || {
+            let result = air::fetch(&agent, &config).map_err(|err| err.to_string());
+            let _ = updates.send(Update::Air(result));
+        });
+    }
+
+    fn draw(&mut self, frame: &mut Frame) {
+        use Constraint::{Fill, Length};
+
+        // Only cards drawn this time can be clicked
+        self.card_areas.clear();
+        self.air_shown = false;
+
+        let theme = &self.config.theme;
+        let [tabs_area, mut main_area] = Layout::vertical([Length(1), Fill(1)]).areas(frame.area());
+        frame.render_widget(
+            Tabs::new(
+                Tab::ALL
+                    .iter()
+                    .enumerate()
+                    .map(|(i, tab)| format!("{} {}", i + 1, tab.title())),
+            )
+            .select(self.tab.index())
+            .style(Style::new().dim())
+            .highlight_style(
+                Style::new()
+                    .fg(Theme::color(&theme.selection))
+                    .not_dim()
+                    .bold()
+                    .underlined(),
+            ),
+            tabs_area,
+        );
+        if let Some(warning) = &self.layout_warning {
+            // Beside the tab titles, each padded by a space on both sides and divided by a line
+            let titles_width: usize = Tab::ALL.iter().map(|tab| tab.title().len() + 5).sum();
+            let [_, warning_area] =
+                Layout::horizontal([Length(titles_width as u16), Fill(1)]).areas(tabs_area);
+            frame.render_widget(
+                Line::from(format!("{} \u{2013} using the default layout ", warning))
+                    .fg(Theme::color(&theme.alert))
+                    .right_aligned(),
+                warning_area,
+            );
+        }
 
-        // Not the best naming; change when better ideas
-        let vertical = Layout::vertical([Percentage(70), Percentage(30)]);
-        let [today_area, forecast_area] = vertical.areas(frame.area());
+        // Official warnings get a banner above every tab while any are active
+        let now = chrono::Utc::now();
+        let active: Vec<&Warning> = self
+            .warnings
+            .iter()
+            .filter(|warning| warning.is_active(now))
+            .collect();
+        if self.config.panels.warnings && (!active.is_empty() || self.warnings_error.is_some()) {
+            let (banner, height) =
+                create_warnings_banner(&active, self.warnings_error.as_deref(), theme);
+            let [banner_area, rest] = Layout::vertical([Length(height), Fill(1)]).areas(main_area);
+            frame.render_widget(banner, banner_area);
+            main_area = rest;
+        }
+
+        if self.day_view {
+            day::render(
+                frame,
+                main_area,
+                &self.forecast,
+                self.selected_day,
+                &self.alerts,
+                &self.config,
+            );
+            return;
+        }
+
+        match self.tab {
+            Tab::Overview => self.draw_overview(frame, main_area),
+            Tab::Hourly => {
+                let entries = hourly::entries(&self.forecast);
+                self.hourly_page = hourly::render(
+                    frame,
+                    main_area,
+                    &self.forecast,
+                    &entries,
+                    self.hourly_offset,
+                    &self.alerts,
+                    &self.config,
+                );
+            }
+            Tab::Daily => daily::render(
+                frame,
+                main_area,
+                &self.forecast,
+                self.selected_day,
+                &self.alerts,
+                &self.config,
+            ),
+            Tab::Astronomy => astronomy::render(
+                frame,
+                main_area,
+                &self.forecast,
+                &self.moon_phase,
+                &self.moon_phase_art,
+                &self.config,
+            ),
+            Tab::Air => {
+                self.air_shown = true;
+                air::render(
+                    frame,
+                    main_area,
+                    self.air.as_ref(),
+                    self.air_error.as_deref(),
+                    &self.config,
+                );
+            }
+        }
+    }
 
-        let horizontal = Layout::horizontal([Ratio(2, 3), Ratio(1, 3)]);
-        let [top_left, top_right] = horizontal.areas(today_area);
+    /// The panels of the Overview tab, laid out as `layout` says and the space suits
+    fn draw_overview(&mut self, frame: &mut Frame, mut main_area: Rect) {
+        use Constraint::{Fill, Length};
+
+        let layout = match (Breakpoint::of(main_area), &self.layout) {
+            (Breakpoint::Compact, _) => return self.draw_compact(frame, main_area),
+            (_, Some(layout)) => layout.clone(),
+            (Breakpoint::Expanded, None) => {
+                let [rest, sidebar] =
+                    Layout::horizontal([Fill(1), Length(SIDEBAR_WIDTH)]).areas(main_area);
+                self.draw_sidebar(frame, sidebar);
+                main_area = rest;
+                OverviewLayout::default()
+            }
+            (Breakpoint::Medium, None) => OverviewLayout::default(),
+        };
+        let alerts_in_logo = !layout.contains(Panel::Alerts);
+        self.render_layout(frame, main_area, &layout, alerts_in_logo);
+    }
 
-        let top = Layout::vertical([Percentage(40), Percentage(60)]);
-        let [today_info, fortnight_graph] = top.areas(top_left);
+    /// Whether a panel takes space; hidden panels leave theirs to their neighbours
+    fn shows(&self, panel: Panel, alerts_in_logo: bool) -> bool {
+        let panels = &self.config.panels;
+        let alerts_active = panels.alerts && !self.alerts.windows.is_empty();
+        match panel {
+            Panel::RightNow => panels.right_now,
+            Panel::Moon => panels.moon,
+            Panel::Logo => panels.logo || (alerts_in_logo && alerts_active),
+            Panel::TodayChart => panels.today_chart,
+            Panel::FortnightChart => panels.fortnight_chart,
+            Panel::Forecast => panels.forecast,
+            Panel::Alerts => alerts_active,
+            Panel::Days | Panel::Air => true,
+        }
+    }
 
-        let top2 = Layout::vertical([Ratio(3, 4), Ratio(1, 4)]);
-        let [today, logo_area] = top2.areas(top_right);
+    fn layout_shows(&self, layout: &OverviewLayout, alerts_in_logo: bool) -> bool {
+        match layout.panel {
+            Some(panel) => self.shows(panel, alerts_in_logo),
+            None => layout
+                .rows
+                .iter()
+                .chain(&layout.columns)
+                .any(|child| self.layout_shows(child, alerts_in_logo)),
+        }
+    }
 
-        let topest = Layout::horizontal([Ratio(1, 2), Ratio(1, 2)]);
-        let [quick_stats, mid_top] = topest.areas(today_info);
+    /// Splits the area between the shown rows or columns by their sizes, down to the panels
+    fn render_layout(
+        &mut self,
+        frame: &mut Frame,
+        area: Rect,
+        layout: &OverviewLayout,
+        alerts_in_logo: bool,
+    ) {
+        if let Some(panel) = layout.panel {
+            return self.render_panel(frame, area, panel, alerts_in_logo);
+        }
+        let (direction, children) = if layout.rows.is_empty() {
+            (Direction::Horizontal, &layout.columns)
+        } else {
+            (Direction::Vertical, &layout.rows)
+        };
+        let shown: Vec<&OverviewLayout> = children
+            .iter()
+            .filter(|child| self.layout_shows(child, alerts_in_logo))
+            .collect();
+        let areas = Layout::new(
+            direction,
+            shown.iter().map(|child| Constraint::Fill(child.size)),
+        )
+        .split(area);
+        for (child, child_area) in shown.into_iter().zip(areas.iter()) {
+            self.render_layout(frame, *child_area, child, alerts_in_logo);
+        }
+    }
 
+    fn render_panel(&mut self, frame: &mut Frame, area: Rect, panel: Panel, alerts_in_logo: bool) {
+        let theme = &self.config.theme;
+        match panel {
+            Panel::RightNow => {
+                // Render forecast summary details for right now
+                frame.render_widget(
+                    create_right_now_table(
+                        &self.forecast,
+                        self.station_error.as_deref(),
+                        theme,
+                        false,
+                    ),
+                    area,
+                );
+            }
+            Panel::Moon => {
+                frame.render_widget(Block::bordered(), area);
+
+                // Render the current moon phase for tonight (they store the current moon phase in
+                // the third position):
+                frame.render_widget(
+                    Paragraph::new(self.moon_phase_art.clone())
+                        .alignment(Alignment::Center)
+                        .block(
+                            Block::new().title(
+                                Line::from(" Tonight's Moon Phase ")
+                                    .fg(Theme::color(&theme.moon))
+                                    .centered()
+                                    .bold(),
+                            ),
+                        ),
+                    area,
+                );
+            }
+            Panel::Logo if alerts_in_logo && self.shows(Panel::Alerts, alerts_in_logo) => {
+                self.render_panel(frame, area, Panel::Alerts, alerts_in_logo)
+            }
+            Panel::Logo => {
+                // Render the logo into the middle of the screen
+                let logo = include_str!("./logo.txt");
+                frame.render_widget(
+                    Paragraph::new(logo)
+                        .alignment(Alignment::Center)
+                        .style(Style::new().fg(Theme::color(&theme.logo))),
+                    area,
+                );
+            }
+            Panel::TodayChart => render_temperature_scatterplot(
+                frame,
+                area,
+                &self.forecast.hourly,
+                &self.alerts.hours,
+                self.pointer,
+                &self.config,
+            ),
+            Panel::FortnightChart => render_fortnight_scatterplot(
+                frame,
+                area,
+                &self.forecast.hourly,
+                &self.forecast.periods,
+                &self.alerts.hours,
+                self.pointer,
+                &self.config,
+            ),
+            Panel::Forecast => self.render_forecast_cards(frame, area),
+            Panel::Alerts => frame.render_widget(
+                create_alerts_panel(&self.alerts, &self.hook_errors, &self.config),
+                area,
+            ),
+            Panel::Days => frame.render_widget(
+                daily::create_summary_table(&self.forecast, &self.alerts, &self.config),
+                area,
+            ),
+            Panel::Air => {
+                self.air_shown = true;
+                let block = Block::bordered()
+                    .title(
+                        Line::from(" Air Quality ")
+                            .fg(Theme::color(&theme.forecast))
+                            .centered()
+                            .bold(),
+                    )
+                    .padding(Padding::horizontal(1));
+                match &self.air {
+                    Some(air) => frame
+                        .render_widget(air::create_current_table(&air.current).block(block), area),
+                    None => frame.render_widget(
+                        Paragraph::new("No air quality data").dim().block(block),
+                        area,
+                    ),
+                }
+            }
+        }
+    }
+
+    /// The 4-cast, scrolled to keep the selected day in view
+    fn render_forecast_cards(&mut self, frame: &mut Frame, forecast_area: Rect) {
+        use Constraint::Ratio;
+
+        let theme = &self.config.theme;
+        let days = self.forecast.periods.len().saturating_sub(1);
+        let inner_width = forecast_area.width.saturating_sub(4);
+        let cards = usize::from((inner_width / MIN_CARD_WIDTH).max(1));
+        self.cards = cards;
+
+        // Scroll just far enough to keep the selected day in view
+        self.first_card = self
+            .first_card
+            .clamp(1, days.saturating_sub(cards).max(1))
+            .clamp(
+                self.selected_day.saturating_sub(cards - 1).max(1),
+                self.selected_day.max(1),
+            );
+        let first = self.first_card;
+        let shown = cards.min(days.saturating_sub(first - 1));
+
+        let mut title = String::from(" 4-cast ");
+        if days > cards {
+            title = format!(
+                " 4-cast \u{b7} {}\u{2013}{} of {} ",
+                first,
+                first + shown - 1,
+                days
+            );
+        }
         let outer_block = Block::bordered()
-            .title(Line::from(" 4-cast ").light_magenta().centered().bold())
+            .title(
+                Line::from(title)
+                    .fg(Theme::color(&theme.forecast))
+                    .centered()
+                    .bold(),
+            )
             .padding(Padding::new(0, 0, 1, 0));
         let inner_block = Block::bordered();
         let inner_area = outer_block.inner(forecast_area);
 
-        let upcoming_weather =
-            Layout::horizontal([Ratio(1, 4), Ratio(1, 4), Ratio(1, 4), Ratio(1, 4)]);
-        let [slot1, slot2, slot3, slot4] = upcoming_weather.areas(inner_area);
+        let upcoming_weather = Layout::horizontal(vec![Ratio(1, cards as u32); cards]);
+        let slots = upcoming_weather.split(inner_area);
 
         frame.render_widget(outer_block, forecast_area);
         frame.render_widget(inner_block, inner_area);
 
-        frame.render_widget(Block::bordered(), mid_top);
-        frame.render_widget(Block::new(), fortnight_graph);
-
-        // Render the current moon phase for tonight (they store the current moon phase in the
-        // third position):
-        frame.render_widget(
-            Paragraph::new(self.moon_phase_art.clone())
-                .alignment(Alignment::Center)
-                .block(
-                    Block::new().title(
-                        Line::from(" Tonight's Moon Phase ")
-                            .light_yellow()
-                            .centered()
-                            .bold(),
-                    ),
-                ),
-            mid_top,
-        );
+        // Populate the 4-cast; shorter forecasts (--days) leave the remaining slots empty
+        for (i, (period, render_area)) in self
+            .forecast
+            .periods
+            .iter()
+            .enumerate()
+            .skip(first)
+            .zip(slots.iter())
+            .map(|((i, period), area)| (i, (period, *area)))
+        {
+            let alert_names = self.alerts.days.get(i).map_or(&[][..], Vec::as_slice);
+            self.card_areas.push((render_area, i));
+            render_weather_card(
+                frame,
+                render_area,
+                period,
+                alert_names,
+                self.selected_day == i,
+                &self.config,
+            );
+        }
 
-        // Render the logo into the middle of the screen
-        let logo = include_str!("./logo.txt");
-        frame.render_widget(
-            Paragraph::new(logo)
-                .alignment(Alignment::Center)
-                .style(Style::new().red()),
-            logo_area,
-        );
+        // Where the cards are within the whole forecast, on the bottom border
+        if days > cards {
+            let mut scrollbar_state = ScrollbarState::new(days - shown + 1)
+                .viewport_content_length(shown)
+                .position(first - 1);
+            frame.render_stateful_widget(
+                Scrollbar::new(ScrollbarOrientation::HorizontalBottom)
+                    .begin_symbol(Some("\u{25c0}"))
+                    .end_symbol(Some("\u{25b6}")),
+                forecast_area.inner(Margin::new(1, 0)),
+                &mut scrollbar_state,
+            );
+        }
+    }
 
-        render_fortnight_scatterplot(
-            frame,
-            fortnight_graph,
-            &self.open_meteo_forecast.hourly,
-            &self.open_meteo_forecast.periods,
-        );
+    /// The current conditions over the 4-cast for small terminals, with today's temperatures
+    /// below if there's room; the other panels are a tab away
+    fn draw_compact(&mut self, frame: &mut Frame, area: Rect) {
+        use Constraint::{Fill, Length};
+
+        let panels = &self.config.panels;
+        let right_now_height = if !panels.right_now {
+            0
+        } else if self.forecast.current.station.is_some() {
+            9
+        } else {
+            8
+        };
+        let forecast_height = if panels.forecast {
+            COMPACT_FORECAST_HEIGHT
+        } else {
+            0
+        };
+        // Charts squeezed below this are unreadable
+        let chart_shown = panels.today_chart
+            && area.height >= right_now_height + forecast_height + MIN_CHART_HEIGHT;
+        let [right_now_area, forecast_area, chart_area] = if chart_shown {
+            Layout::vertical([Length(right_now_height), Length(forecast_height), Fill(1)])
+                .areas(area)
+        } else {
+            Layout::vertical([Length(right_now_height), Fill(1), Length(0)]).areas(area)
+        };
 
-        // Render forecast summary details for right now
-        frame.render_widget(
-            create_right_now_table(&self.open_meteo_forecast),
-            quick_stats,
-        );
-        render_temperature_scatterplot(frame, today, &self.open_meteo_forecast.hourly);
-
-        // Populate the 4-cast
-        for i in 1..5 {
-            let mut render_area: Rect = slot1;
-            if i == 2 {
-                render_area = slot2;
-            } else if i == 3 {
-                render_area = slot3;
-            } else if i == 4 {
-                render_area = slot4;
-            }
+        if panels.right_now {
             frame.render_widget(
-                create_weather_card(&self.open_meteo_forecast.periods[i]),
-                render_area,
+                create_right_now_table(
+                    &self.forecast,
+                    self.station_error.as_deref(),
+                    &self.config.theme,
+                    true,
+                ),
+                right_now_area,
             );
         }
+        if panels.forecast {
+            self.render_forecast_cards(frame, forecast_area);
+        }
+        if chart_shown {
+            render_temperature_scatterplot(
+                frame,
+                chart_area,
+                &self.forecast.hourly,
+                &self.alerts.hours,
+                self.pointer,
+                &self.config,
+            );
+        }
+    }
+
+    /// Every day of the forecast above the air quality, beside the medium layout on large
+    /// terminals
+    fn draw_sidebar(&mut self, frame: &mut Frame, area: Rect) {
+        use Constraint::{Fill, Length};
+
+        // The air quality is fetched for the sidebar; until it's in, the days take the
+        // whole height
+        self.air_shown = true;
+        let air_height = if self.air.is_some() { 11 } else { 0 };
+        let [days_area, air_area] = Layout::vertical([Fill(1), Length(air_height)]).areas(area);
+        self.render_panel(frame, days_area, Panel::Days, true);
+        if self.air.is_some() {
+            self.render_panel(frame, air_area, Panel::Air, true);
+        }
     }
 
     /// Updates the application's state based on user input
@@ -486,158 +1540,501 @@ impl App {
             Event::Key(key_event) if key_event.kind == KeyEventKind::Press => {
                 self.handle_key_event(key_event)
             }
+            Event::Mouse(mouse_event) => self.handle_mouse_event(mouse_event),
             _ => {}
         };
         Ok(())
     }
 
+    fn handle_mouse_event(&mut self, mouse_event: MouseEvent) {
+        let pointer = Position::new(mouse_event.column, mouse_event.row);
+        match mouse_event.kind {
+            MouseEventKind::Moved | MouseEventKind::Drag(_) => self.pointer = Some(pointer),
+            MouseEventKind::Down(MouseButton::Left) => {
+                let clicked = self
+                    .card_areas
+                    .iter()
+                    .find(|(area, _)| area.contains(pointer))
+                    .map(|(_, day)| *day);
+                // Clicking the selected card again opens it, like Enter
+                match clicked {
+                    Some(day) if day == self.selected_day => self.day_view = true,
+                    Some(day) => self.select_day(day),
+                    None => {}
+                }
+            }
+            MouseEventKind::ScrollDown => self.scroll(1),
+            MouseEventKind::ScrollUp => self.scroll(-1),
+            _ => {}
+        }
+    }
+
+    /// Scrolls the current tab by a notch of the mouse wheel: the 4-cast by a card, keeping the
+    /// selection in view, the hourly table by a few lines and the daily table by a day
+    fn scroll(&mut self, step: isize) {
+        if self.day_view {
+            return;
+        }
+        match self.tab {
+            Tab::Overview => {
+                let days = self.forecast.periods.len().saturating_sub(1);
+                let cards = self.cards.max(1);
+                self.first_card = self
+                    .first_card
+                    .saturating_add_signed(step)
+                    .clamp(1, days.saturating_sub(cards).max(1));
+                self.select_day(
+                    self.selected_day
+                        .clamp(self.first_card, self.first_card + cards - 1),
+                );
+            }
+            Tab::Hourly => {
+                let entries = hourly::entries(&self.forecast);
+                self.hourly_offset = self
+                    .hourly_offset
+                    .saturating_add_signed(step * WHEEL_LINES)
+                    .min(entries.len().saturating_sub(self.hourly_page.max(1)));
+            }
+            Tab::Daily => self.select_day(self.selected_day.saturating_add_signed(step)),
+            Tab::Astronomy | Tab::Air => {}
+        }
+    }
+
     fn handle_key_event(&mut self, key_event: KeyEvent) {
-        match key_event.code {
-            KeyCode::Char('q') => self.exit(),
+        let code = key_event.code;
+        match code {
+            KeyCode::Char('q') => return self.exit(),
+            KeyCode::Tab => return self.switch_tab(self.tab.cycle(1)),
+            KeyCode::BackTab => return self.switch_tab(self.tab.cycle(-1)),
+            KeyCode::Char(digit @ '1'..='9') => {
+                let index = digit as usize - '1' as usize;
+                if let Some(tab) = Tab::ALL.get(index) {
+                    return self.switch_tab(*tab);
+                }
+            }
+            _ => {}
+        }
+
+        if self.day_view {
+            match code {
+                KeyCode::Left | KeyCode::Char('h') => {
+                    self.select_day(self.selected_day.saturating_sub(1))
+                }
+                KeyCode::Right | KeyCode::Char('l') => self.select_day(self.selected_day + 1),
+                KeyCode::Esc | KeyCode::Backspace => {
+                    self.day_view = false;
+                    // Back on the 4-cast, today or a day past the cards selects the nearest card
+                    self.select_day(self.selected_day);
+                }
+                _ => {}
+            }
+            return;
+        }
+
+        match (self.tab, code) {
+            (Tab::Overview, KeyCode::Left | KeyCode::Char('h'))
+            | (Tab::Daily, KeyCode::Up | KeyCode::Char('k')) => {
+                self.select_day(self.selected_day.saturating_sub(1))
+            }
+            (Tab::Overview, KeyCode::Right | KeyCode::Char('l'))
+            | (Tab::Daily, KeyCode::Down | KeyCode::Char('j')) => {
+                self.select_day(self.selected_day + 1)
+            }
+            (Tab::Daily, KeyCode::Home | KeyCode::Char('g')) => self.select_day(0),
+            (Tab::Daily, KeyCode::End | KeyCode::Char('G')) => self.select_day(usize::MAX),
+            (Tab::Overview | Tab::Daily, KeyCode::Enter) if !self.forecast.periods.is_empty() => {
+                self.day_view = true
+            }
+            (Tab::Hourly, _) => self.handle_hourly_key(code),
+            (Tab::Overview, _) => {}
+            (_, KeyCode::Esc | KeyCode::Backspace) => self.switch_tab(Tab::Overview),
             _ => {}
         }
     }
 
-    fn exit(&mut self) {
-        self.exit = true;
+    fn switch_tab(&mut self, tab: Tab) {
+        self.tab = tab;
+        self.day_view = false;
+        self.select_day(self.selected_day);
+        if tab == Tab::Hourly {
+            self.scroll_hourly_to_now();
+        }
     }
-}
 
-/// Get the forecast for the next 7 days as well as today's weather conditions
-/// Using this API: <https://api.open-meteo.com/v1/forecast>
-fn get_open_meteo_weather(
-    agent: &Agent,
-    weather_codes: serde_json::Value,
-) -> Result<OpenMeteoForecast, ureq::Error> {
-    let latitude = env::var("LATITUDE").unwrap();
-    let longitude = env::var("LONGITUDE").unwrap();
-    let mut timezone = env::var("TIMEZONE").unwrap().to_string();
-    timezone = encode(&timezone).to_string();
-
-    let url = format!(
-        "https://api.open-meteo.com/v1/forecast?latitude={}&longitude={}&daily=temperature_2m_max,temperature_2m_min,apparent_temperature_max,apparent_temperature_min,weather_code,precipitation_probability_mean&hourly=temperature_2m,weather_code&current=temperature_2m,apparent_temperature,weather_code&timezone={}&forecast_days=14",
-        latitude.to_string(),
-        longitude.to_string(),
-        timezone
-    );
+    /// Keys of the hourly table: scrolling by hour, page and day, and back to the current hour
+    fn handle_hourly_key(&mut self, code: KeyCode) {
+        let entries = hourly::entries(&self.forecast);
+        let page = self.hourly_page.max(1);
+        let offset = self.hourly_offset;
+        self.hourly_offset = match code {
+            KeyCode::Esc | KeyCode::Backspace => {
+                self.switch_tab(Tab::Overview);
+                offset
+            }
+            KeyCode::Up | KeyCode::Char('k') => offset.saturating_sub(1),
+            KeyCode::Down | KeyCode::Char('j') => offset + 1,
+            KeyCode::PageUp => offset.saturating_sub(page),
+            KeyCode::PageDown | KeyCode::Char(' ') => offset + page,
+            KeyCode::Left | KeyCode::Char('h') => hourly::day_offset(&entries, offset, false),
+            KeyCode::Right | KeyCode::Char('l') => hourly::day_offset(&entries, offset, true),
+            KeyCode::Home | KeyCode::Char('g') => 0,
+            KeyCode::End | KeyCode::Char('G') => entries.len(),
+            KeyCode::Char('n') => hourly::now_offset(&self.forecast, &entries),
+            _ => offset,
+        }
+        // The last page stays full
+        .min(entries.len().saturating_sub(page));
+    }
 
-    let json = agent
-        .get(url)
-        .call()?
-        .body_mut()
-        .read_json::<OpenMeteoRawForecast>()?;
-
-    let mut periods: Vec<OpenMeteoPeriod> = Vec::new();
-    let mut count: usize = 0;
-    for i in &json.daily.time {
-        periods.push(OpenMeteoPeriod {
-            date: i.to_string(),
-            weather: weather_codes[json.daily.weather_code[count].to_string()].to_string(),
-            temperature_max: format!(
-                "{}\u{00B0}",
-                json.daily.temperature_2m_max[count].to_string()
-            ),
-            temperature_min: format!(
-                "{}\u{00B0}",
-                json.daily.temperature_2m_min[count].to_string()
-            ),
-            apparent_temperature_max: format!(
-                "{}\u{00B0}",
-                json.daily.apparent_temperature_max[count].to_string()
-            ),
-            apparent_temperature_min: format!(
-                "{}\u{00B0}",
-                json.daily.apparent_temperature_min[count].to_string()
-            ),
-            precipitation_probability: json.daily.precipitation_probability_mean[count].to_string(),
-        });
-        count += 1;
+    fn scroll_hourly_to_now(&mut self) {
+        let entries = hourly::entries(&self.forecast);
+        self.hourly_offset = hourly::now_offset(&self.forecast, &entries);
     }
 
-    let mut hourly: Vec<OpenMeteoHourly> = Vec::new();
-    let mut count: usize = 0;
-    for i in &json.hourly.time {
-        hourly.push(OpenMeteoHourly {
-            datetime: i.to_string(),
-            temperature: format!("{}\u{00B0}", json.hourly.temperature_2m[count].to_string()),
-            weather: weather_codes[json.hourly.weather_code[count].to_string()].to_string(),
-        });
-        count += 1;
+    fn exit(&mut self) {
+        self.exit = true;
     }
+}
 
-    return Ok(OpenMeteoForecast {
-        periods: periods,
-        current: json.current,
-        hourly: hourly,
-    });
+/// Looks up the description for a WMO weather code
+fn describe_weather_code(weather_codes: &serde_json::Value, code: i32) -> String {
+    weather_codes[code.to_string()]
+        .as_str()
+        .unwrap_or("Unknown")
+        .to_string()
+}
+
+/// Picks an icon for a WMO weather code
+fn weather_icon(code: i32) -> &'static str {
+    match code {
+        0 => "\u{2600}",                  // ☀
+        1 => "\u{1F324}",                 // 🌤
+        2 => "\u{26C5}",                  // ⛅
+        3 => "\u{2601}",                  // ☁
+        45 | 48 => "\u{1F32B}",           // 🌫
+        51..=57 => "\u{1F326}",           // 🌦
+        61..=67 | 80..=82 => "\u{1F327}", // 🌧
+        71..=77 | 85 | 86 => "\u{1F328}", // 🌨
+        95..=99 => "\u{26C8}",            // ⛈
+        _ => "?",
+    }
 }
 
 /// Get the phases of the moon for today and the next 3 days
 /// Using this API: <https://api.viewbits.com/v1/moonphase>
-fn get_moon_phases(agent: &Agent, date: String) -> Result<Vec<MoonPhase>, ureq::Error> {
-    let url = format!("https://api.viewbits.com/v1/moonphase?startdate={}", date);
+fn get_moon_phases(
+    agent: &Agent,
+    config: &Config,
+    date: &str,
+) -> Result<Vec<MoonPhase>, ureq::Error> {
+    let url = format!("{}?startdate={}", config.endpoints.moon_phase, date);
     let moon_phases = agent
         .get(url)
         .call()?
         .body_mut()
         .read_json::<Vec<MoonPhase>>()?;
 
-    return Ok(moon_phases);
+    Ok(moon_phases)
 }
 
-fn main() -> io::Result<()> {
-    let folder: PathBuf = dirs::home_dir()
-        .expect("Could not find home directory")
-        .join(".config")
-        .join("Raijin");
+/// Fetches the forecast and tonight's moon phase, with the personal station's current conditions
+/// A forecast another Raijin process fetched within the refresh interval is reused
+fn fetch_all(
+    agent: &Agent,
+    config: &Config,
+    weather_codes: &serde_json::Value,
+) -> Result<(Forecast, MoonPhase), ProviderError> {
+    fetch_observed(agent, config, weather_codes)
+        .map(|(forecast, moon_phase, _)| (forecast, moon_phase))
+}
 
-    let file = folder.join(".env");
+/// Like `fetch_all`, but also says why the station couldn't be read, for the TUI to show
+fn fetch_observed(
+    agent: &Agent,
+    config: &Config,
+    weather_codes: &serde_json::Value,
+) -> Result<(Forecast, MoonPhase, Option<StationError>), ProviderError> {
+    let (mut forecast, moon_phase) = fetch_forecast(agent, config, weather_codes)?;
+    // A station that can't be read leaves the provider's conditions, which the source shows
+    let station_error = match station::apply(agent, config, &mut forecast) {
+        Ok(()) => {
+            forecast.describe_weather(weather_codes);
+            None
+        }
+        Err(err) => Some(err),
+    };
+    Ok((forecast, moon_phase, station_error))
+}
 
-    if !folder.exists() {
-        fs::create_dir(folder)?;
+/// Fetches the provider's forecast and tonight's moon phase, or takes them from the cache
+fn fetch_forecast(
+    agent: &Agent,
+    config: &Config,
+    weather_codes: &serde_json::Value,
+) -> Result<(Forecast, MoonPhase), ProviderError> {
+    if let Some(cached) = cache::load(config) {
+        return Ok((cached.forecast, cached.moon_phase));
     }
 
-    if !file.exists() {
-        fs::write(
-            &file,
-            "ZONE=\"TNZ069\"\nSTATE=\"TN\"\nLATITUDE=\"35.9626444\"\nLONGITUDE=\"-83.9167239\"\nTIMEZONE=\"America/New_York\"\n",
-        )?;
-    }
+    let mut forecast = provider::from_config(config).fetch(agent, config)?;
+    forecast.describe_weather(weather_codes);
+    let today = forecast
+        .periods
+        .first()
+        .ok_or_else(|| ProviderError::Incomplete(String::from("the forecast has no days")))?;
+    let all_moon_phases = get_moon_phases(agent, config, &today.date)?;
+
+    // ViewBits stores the current moon phase in the fourth position
+    let moon_phase = all_moon_phases.into_iter().nth(3).ok_or_else(|| {
+        ProviderError::Incomplete(String::from(
+            "ViewBits returned fewer moon phases than expected",
+        ))
+    })?;
+
+    // Failing to write the cache only means the next run fetches again
+    let _ = cache::store(config, &forecast, &moon_phase);
+
+    Ok((forecast, moon_phase))
+}
+
+/// Returns the ASCII art for a moon phase name such as "Full Moon", empty for unknown phases
+fn get_moon_phase_art(phase: &str) -> String {
+    MOON_PHASE_ART_DIR
+        .get_file(format!("{}.txt", phase))
+        .and_then(|file| file.contents_utf8())
+        .unwrap_or_default()
+        .to_string()
+}
 
-    let _ = dotenv::from_path(&file).expect("Could not find .env file");
+/// Unwraps a fetch result, exiting with a readable message instead of a panic
+fn or_exit<T>(result: Result<T, ProviderError>) -> T {
+    result.unwrap_or_else(|err| {
+        eprintln!("Could not fetch the forecast: {}", err);
+        process::exit(1);
+    })
+}
 
-    let data = include_str!("./weather-codes.json");
-    let weather_codes: serde_json::Value = serde_json::from_str(&data).expect("JSON was malformed");
+fn main() -> io::Result<()> {
+    let cli = Cli::parse();
+
+    // An explicitly passed config has to exist; only the default one is created on demand
+    let config_path = cli.config_path();
+    let loaded = if cli.config.is_some() {
+        Config::load(&config_path)
+    } else {
+        Config::load_or_init(&config_path, cli.coordinates())
+    };
+    let config = match loaded.and_then(|mut config| cli.apply(&mut config).map(|_| config)) {
+        Ok(config) => config,
+        Err(err) => {
+            eprintln!("Could not load {}: {}", config_path.display(), err);
+            process::exit(1);
+        }
+    };
 
-    // This is used as part of the thin authentication that the NWS API uses
-    // I'm hardcoding it because it doesn't really matter and you won't get blocked even with heavy
-    // use (I pinged this thing constantly during development and never hit a limit)
-    let user_agent = "Mozilla/5.0 (X11; Linux x86_64; rv:139.0) Gecko/20100101 Firefox/139.0";
+    let data = include_str!("./weather-codes.json");
+    let weather_codes: serde_json::Value = serde_json::from_str(data).expect("JSON was malformed");
 
-    let config = Agent::config_builder()
-        .user_agent(user_agent)
+    // None of the services need authentication, only a suitable user agent
+    let agent_config = Agent::config_builder()
+        .user_agent(config.user_agent())
         .timeout_global(Some(std::time::Duration::from_secs(20)))
         .build();
 
-    let agent = Agent::new_with_config(config);
+    let agent = Agent::new_with_config(agent_config);
 
-    let open_meteo_forecast = get_open_meteo_weather(&agent, weather_codes).unwrap();
-    let all_moon_phases =
-        get_moon_phases(&agent, open_meteo_forecast.periods[0].date.clone()).unwrap();
+    match &cli.command {
+        Some(Command::Bar { waybar, .. }) => {
+            let (forecast, moon_phase) = or_exit(fetch_all(&agent, &config, &weather_codes));
+            bar::print(&forecast, &moon_phase, &config, *waybar);
+            Ok(())
+        }
+        Some(Command::Export(args)) => {
+            let (forecast, _) = or_exit(fetch_all(&agent, &config, &weather_codes));
+            if let Err(err) = export::run(&forecast, &config, args) {
+                eprintln!("Could not export the forecast: {}", err);
+                process::exit(1);
+            }
+            Ok(())
+        }
+        Some(Command::Serve { port }) => {
+            station::listen(&config);
+            let latest = or_exit(fetch_all(&agent, &config, &weather_codes));
+            serve::run(agent, config, weather_codes, *port, latest)
+        }
+        Some(Command::Publish) => {
+            if config.mqtt.host.is_none() {
+                eprintln!("Nowhere to publish: configure a host in [mqtt]");
+                process::exit(1);
+            }
+            let (forecast, _) = or_exit(fetch_all(&agent, &config, &weather_codes));
+            let alerts = alerts::evaluate(&config.alerts, &forecast);
+            if let Err(err) = mqtt::publish(&config, &forecast, &alerts.windows) {
+                eprintln!("Could not publish the forecast: {}", err);
+                process::exit(1);
+            }
+            Ok(())
+        }
+        Some(Command::Daemon) => {
+            let hooks = !config.alerts.is_empty() && !config.hooks.is_empty();
+            if !hooks && config.mqtt.host.is_none() {
+                eprintln!(
+                    "Nothing to watch: configure [[alerts]] and a command or webhook in [hooks], or [mqtt]"
+                );
+                process::exit(1);
+            }
+            station::listen(&config);
+            hooks::daemon(&agent, &config, &weather_codes)
+        }
+        Some(Command::Now) => {
+            print_section(&cli, &agent, &config, &weather_codes, Section::Current)
+        }
+        Some(Command::Daily) => {
+            print_section(&cli, &agent, &config, &weather_codes, Section::Daily)
+        }
+        Some(Command::Hourly) => {
+            print_section(&cli, &agent, &config, &weather_codes, Section::Hourly)
+        }
+        None if cli.json || cli.once => {
+            print_section(&cli, &agent, &config, &weather_codes, Section::All)
+        }
+        None => run_tui(&agent, config, &weather_codes),
+    }
+}
 
-    let thing = MOON_PHASE_ART_DIR
-        .get_file(format!("{}.txt", all_moon_phases[3].phase))
-        .unwrap();
-    let moon_phase_art = thing.contents_utf8().unwrap();
+/// Prints a section of the forecast: as JSON with --json, as the short summary with --once, and
+/// otherwise as the subcommand's text
+fn print_section(
+    cli: &Cli,
+    agent: &Agent,
+    config: &Config,
+    weather_codes: &serde_json::Value,
+    section: Section,
+) -> io::Result<()> {
+    let (forecast, moon_phase) = or_exit(fetch_all(agent, config, weather_codes));
+    if cli.json {
+        json::print(&forecast, &moon_phase, config, section);
+    } else {
+        match section {
+            _ if cli.once => report::print_once(&forecast, &moon_phase, config),
+            Section::Current => report::print_now(&forecast, config),
+            Section::Daily => report::print_daily(&forecast, config),
+            Section::Hourly => report::print_hourly(&forecast, config),
+            Section::All | Section::Moon => report::print_once(&forecast, &moon_phase, config),
+        }
+    }
+    Ok(())
+}
+
+/// Starts the TUI for the configured location and runs it until the user quits
+fn run_tui(agent: &Agent, config: Config, weather_codes: &serde_json::Value) -> io::Result<()> {
+    station::listen(&config);
+    let (forecast, moon_phase, station_error) =
+        or_exit(fetch_observed(agent, &config, weather_codes));
+
+    // A broken layout shouldn't keep the TUI from starting
+    let (layout, layout_warning) = config.overview_layout();
 
     // Initialize the TUI
     let mut terminal = ratatui::init();
-    let app_result = App::default().run(
-        &mut terminal,
-        open_meteo_forecast,
-        moon_phase_art.to_string(),
-    );
-    // Restore the terminal before we leave
+    // `init` restores the terminal on a panic; the mouse is released before that
+    let restore_terminal = std::panic::take_hook();
+    std::panic::set_hook(Box::new(move |info| {
+        let _ = execute!(io::stdout(), DisableMouseCapture);
+        restore_terminal(info);
+    }));
+    let app_result = execute!(io::stdout(), EnableMouseCapture).and_then(|()| {
+        App {
+            config,
+            layout,
+            layout_warning,
+            station_error: station_error.map(|err| err.to_string()),
+            ..App::default()
+        }
+        .run(&mut terminal, agent, weather_codes, forecast, moon_phase)
+    });
+    // Restore the terminal before we leave, even if the mouse couldn't be captured
+    // Like `restore`, carry on if the terminal won't listen
+    let _ = execute!(io::stdout(), DisableMouseCapture);
     ratatui::restore();
     app_result
 }
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use forecast::fixtures::{hour, period};
+
+    #[test]
+    fn days_are_labelled_where_their_hours_start() {
+        // Like NWS, starting at the current hour, and like MET Norway, with hours for fewer
+        // days than there are periods
+        let hourly: Vec<Hour> = [
+            "2026-10-18T21:00",
+            "2026-10-18T23:00",
+            "2026-10-19T00:00",
+            "2026-10-19T06:00",
+            "2026-10-20T00:00",
+        ]
+        .into_iter()
+        .map(|datetime| hour(datetime, 10.0))
+        .collect();
+        let daily: Vec<Period> = ["2026-10-18", "2026-10-19", "2026-10-20", "2026-10-21"]
+            .into_iter()
+            .map(|date| period(date, 5.0, 15.0))
+            .collect();
+        assert_eq!(
+            day_ticks(&hourly, &daily),
+            [
+                (0, String::from("10-18")),
+                (2, String::from("10-19")),
+                (4, String::from("10-20"))
+            ]
+        );
+    }
+
+    #[test]
+    fn temperature_charts_have_finite_bounds() {
+        assert_eq!(temperature_bounds(&[3.2, -1.5, 12.0]), [-7.0, 17.0]);
+        assert_eq!(temperature_bounds(&[]), [0.0, 20.0]);
+    }
+
+    #[test]
+    fn temperature_charts_draw_without_hours() {
+        let config = Config::default();
+        let backend = ratatui::backend::TestBackend::new(60, 20);
+        let mut terminal = ratatui::Terminal::new(backend).unwrap();
+        let pointer = Some(Position::new(30, 10));
+        for fortnight in [false, true] {
+            let buffer = terminal
+                .draw(|frame| {
+                    let area = frame.area();
+                    if fortnight {
+                        render_fortnight_scatterplot(frame, area, &[], &[], &[], pointer, &config);
+                    } else {
+                        render_temperature_scatterplot(frame, area, &[], &[], pointer, &config);
+                    }
+                })
+                .unwrap()
+                .buffer
+                .clone();
+            let text: String = buffer.content.iter().map(|cell| cell.symbol()).collect();
+            assert!(text.contains("20"), "{}", text);
+            assert!(!text.contains("inf") && !text.contains("NaN"), "{}", text);
+        }
+    }
+
+    #[test]
+    fn invalid_dates_have_no_day() {
+        assert_eq!(get_day_from_date("2026-10-18").as_deref(), Some("Sun"));
+        assert_eq!(get_day_from_date("2026-10"), None);
+        assert_eq!(get_day_from_date("2026-02-30"), None);
+        assert_eq!(get_day_from_date(""), None);
+    }
+
+    #[test]
+    fn unknown_moon_phases_have_no_art() {
+        assert!(!get_moon_phase_art("Full Moon").is_empty());
+        assert_eq!(get_moon_phase_art("Blue Moon"), "");
+    }
+}
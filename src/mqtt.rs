@@ -0,0 +1,485 @@
+use crate::alerts::{AlertWindow, AlertsReport};
+use crate::config::Config;
+use crate::forecast::{Current, Forecast, Period};
+use rumqttc::{Client, ClientError, ConnectionError, Event, MqttOptions, Outgoing, Packet, QoS};
+use serde::Serialize;
+use std::fmt;
+use std::time::Duration;
+
+#[derive(Debug)]
+pub enum MqttError {
+    /// A message couldn't be handed to the client
+    Client(ClientError),
+    /// Boxed as it's much larger than the other errors
+    Connection(Box<ConnectionError>),
+}
+
+impl fmt::Display for MqttError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            MqttError::Client(err) => write!(f, "could not queue an MQTT message: {}", err),
+            MqttError::Connection(err) => write!(f, "could not publish to the broker: {}", err),
+        }
+    }
+}
+
+impl std::error::Error for MqttError {}
+
+/// Published to `mqtt.current_topic`
+#[derive(Serialize)]
+struct CurrentPayload<'a> {
+    location: &'a str,
+    #[serde(flatten)]
+    current: &'a Current,
+    temperature_unit: &'static str,
+}
+
+/// Published to `mqtt.today_topic`
+#[derive(Serialize)]
+struct TodayPayload<'a> {
+    location: &'a str,
+    #[serde(flatten)]
+    period: &'a Period,
+    temperature_unit: &'static str,
+    wind_speed_unit: &'static str,
+}
+
+/// The device all of a location's sensors belong to in Home Assistant
+#[derive(Serialize)]
+struct Device {
+    identifiers: [String; 1],
+    name: String,
+    manufacturer: &'static str,
+    sw_version: &'static str,
+}
+
+/// A Home Assistant MQTT discovery config
+/// Using this spec: <https://www.home-assistant.io/integrations/sensor.mqtt/>
+#[derive(Serialize)]
+struct Sensor<'a> {
+    /// Distinguishes the sensor in its discovery topic
+    #[serde(skip)]
+    id: &'static str,
+    name: &'static str,
+    unique_id: String,
+    state_topic: &'a str,
+    value_template: &'static str,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    unit_of_measurement: Option<&'static str>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    device_class: Option<&'static str>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    state_class: Option<&'static str>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    json_attributes_topic: Option<&'a str>,
+    device: &'a Device,
+}
+
+impl Sensor<'_> {
+    fn temperature(self, unit: &'static str) -> Self {
+        Sensor {
+            unit_of_measurement: Some(unit),
+            device_class: Some("temperature"),
+            state_class: Some("measurement"),
+            ..self
+        }
+    }
+}
+
+fn json(value: &impl Serialize) -> String {
+    serde_json::to_string(value).expect("MQTT payload could not be serialized")
+}
+
+/// The location's name as it appears in topics and IDs, e.g. "New York" -> "new_york"
+fn slug(name: &str) -> String {
+    name.chars()
+        .map(|c| {
+            if c.is_ascii_alphanumeric() {
+                c.to_ascii_lowercase()
+            } else {
+                '_'
+            }
+        })
+        .collect()
+}
+
+/// Every topic and payload to publish for the forecast
+fn messages(
+    config: &Config,
+    forecast: &Forecast,
+    windows: &[AlertWindow],
+) -> Vec<(String, String)> {
+    let mqtt = &config.mqtt;
+    let units = &config.units;
+    let location = &config.location().name;
+    let slug = slug(location);
+    let topic = |template: &str| template.replace("{location}", &slug);
+
+    let current_topic = topic(&mqtt.current_topic);
+    let today_topic = topic(&mqtt.today_topic);
+    let alerts_topic = topic(&mqtt.alerts_topic);
+    let temperature_unit = units.temperature.symbol();
+    let wind_speed_unit = units.wind_speed.symbol();
+
+    let mut messages = Vec::new();
+    if mqtt.discovery {
+        let device = Device {
+            identifiers: [format!("raijin_{}", slug)],
+            name: format!("Raijin {}", location),
+            manufacturer: "Raijin",
+            sw_version: env!("CARGO_PKG_VERSION"),
+        };
+        let sensor = |id, name, state_topic, value_template| Sensor {
+            id,
+            name,
+            unique_id: format!("raijin_{}_{}", slug, id),
+            state_topic,
+            value_template,
+            unit_of_measurement: None,
+            device_class: None,
+            state_class: None,
+            json_attributes_topic: None,
+            device: &device,
+        };
+        let sensors = [
+            sensor(
+                "temperature",
+                "Temperature",
+                &current_topic,
+                "{{ value_json.temperature_2m }}",
+            )
+            .temperature(temperature_unit),
+            sensor(
+                "apparent_temperature",
+                "Feels like",
+                &current_topic,
+                "{{ value_json.apparent_temperature }}",
+            )
+            .temperature(temperature_unit),
+            sensor(
+                "weather",
+                "Weather",
+                &current_topic,
+                "{{ value_json.weather }}",
+            ),
+            sensor(
+                "high",
+                "High today",
+                &today_topic,
+                "{{ value_json.temperature_max }}",
+            )
+            .temperature(temperature_unit),
+            sensor(
+                "low",
+                "Low today",
+                &today_topic,
+                "{{ value_json.temperature_min }}",
+            )
+            .temperature(temperature_unit),
+            Sensor {
+                unit_of_measurement: Some("%"),
+                ..sensor(
+                    "rain_chance",
+                    "Chance of rain today",
+                    &today_topic,
+                    "{{ value_json.precipitation_probability }}",
+                )
+            },
+            Sensor {
+                unit_of_measurement: Some(wind_speed_unit),
+                device_class: Some("wind_speed"),
+                ..sensor(
+                    "wind_gusts",
+                    "Wind gusts today",
+                    &today_topic,
+                    "{{ value_json.wind_gusts_max }}",
+                )
+            },
+            // The alert windows become the sensor's attributes
+            Sensor {
+                json_attributes_topic: Some(&alerts_topic),
+                ..sensor("alerts", "Alerts", &alerts_topic, "{{ value_json.count }}")
+            },
+        ];
+        for sensor in sensors {
+            messages.push((
+                format!(
+                    "{}/sensor/raijin_{}/{}/config",
+                    mqtt.discovery_prefix, slug, sensor.id
+                ),
+                json(&sensor),
+            ));
+        }
+    }
+
+    messages.push((
+        current_topic.clone(),
+        json(&CurrentPayload {
+            location,
+            current: &forecast.current,
+            temperature_unit,
+        }),
+    ));
+    messages.push((
+        today_topic.clone(),
+        json(&TodayPayload {
+            location,
+            period: &forecast.periods[0],
+            temperature_unit,
+            wind_speed_unit,
+        }),
+    ));
+    messages.push((
+        alerts_topic.clone(),
+        json(&AlertsReport::new(location, windows, units)),
+    ));
+    messages
+}
+
+/// Publishes the current conditions, today's summary and the alert windows, and the Home
+/// Assistant discovery configs if enabled
+/// Each call connects, publishes and disconnects, so a broker that's down only fails that refresh
+pub fn publish(
+    config: &Config,
+    forecast: &Forecast,
+    windows: &[AlertWindow],
+) -> Result<(), MqttError> {
+    let mqtt = &config.mqtt;
+    let Some(host) = &mqtt.host else {
+        return Ok(());
+    };
+
+    // Brokers drop the older of two connections with the same ID
+    let client_id = format!("{}-{}", mqtt.client_id, std::process::id());
+    let mut options = MqttOptions::new(client_id, host, mqtt.port);
+    options.set_keep_alive(Duration::from_secs(30));
+    if let Some(username) = &mqtt.username {
+        options.set_credentials(username, mqtt.password.clone().unwrap_or_default());
+    }
+
+    let messages = messages(config, forecast, windows);
+    let count = messages.len();
+    let (client, mut connection) = Client::new(options, count + 1);
+    for (topic, payload) in messages {
+        client
+            .publish(topic, QoS::AtLeastOnce, mqtt.retain, payload)
+            .map_err(MqttError::Client)?;
+    }
+
+    // Messages are only sent while the connection is polled; once the broker acknowledged all of
+    // them the client disconnects, which ends the polling
+    let mut acknowledged = 0;
+    for event in connection.iter() {
+        match event.map_err(|err| MqttError::Connection(Box::new(err)))? {
+            Event::Incoming(Packet::PubAck(_)) => {
+                acknowledged += 1;
+                if acknowledged == count {
+                    client.disconnect().map_err(MqttError::Client)?;
+                }
+            }
+            Event::Outgoing(Outgoing::Disconnect) => break,
+            _ => {}
+        }
+    }
+    Ok(())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::config::{AlertCondition, AlertMetric, Location};
+    use crate::forecast::fixtures::period;
+    use serde_json::Value;
+
+    fn config(name: &str) -> Config {
+        let mut config = Config::default();
+        config.locations.push(Location {
+            name: name.to_string(),
+            latitude: 40.71,
+            longitude: -74.01,
+            timezone: String::from("America/New_York"),
+            warning_area: None,
+            geocodes: Vec::new(),
+        });
+        config
+    }
+
+    fn forecast() -> Forecast {
+        let mut forecast = Forecast {
+            periods: vec![period("2026-10-18", 4.0, 16.0)],
+            ..Default::default()
+        };
+        forecast.current.temperature = 12.3;
+        forecast
+    }
+
+    fn frost() -> AlertWindow {
+        AlertWindow {
+            name: String::from("Frost"),
+            metric: AlertMetric::Temperature,
+            condition: AlertCondition::Below,
+            start: String::from("2026-10-22T05:00"),
+            end: String::from("2026-10-22T08:00"),
+            extreme: -2.4,
+        }
+    }
+
+    fn payload(messages: &[(String, String)], topic: &str) -> Value {
+        let (_, payload) = messages
+            .iter()
+            .find(|(t, _)| t == topic)
+            .unwrap_or_else(|| panic!("nothing published to {}", topic));
+        serde_json::from_str(payload).unwrap()
+    }
+
+    #[test]
+    fn names_become_slugs() {
+        assert_eq!(slug("New York"), "new_york");
+        assert_eq!(slug("St. Louis-2"), "st__louis_2");
+        assert_eq!(slug("Zürich"), "z_rich");
+    }
+
+    #[test]
+    fn states_go_to_the_location_topics() {
+        let mut config = config("New York");
+        config.mqtt.discovery = false;
+        config.mqtt.current_topic = String::from("weather/{location}/now");
+        let messages = messages(&config, &forecast(), &[frost()]);
+
+        let topics: Vec<&str> = messages.iter().map(|(topic, _)| topic.as_str()).collect();
+        assert_eq!(
+            topics,
+            [
+                "weather/new_york/now",
+                "raijin/new_york/today",
+                "raijin/new_york/alerts"
+            ]
+        );
+
+        let current = payload(&messages, "weather/new_york/now");
+        assert_eq!(current["location"], "New York");
+        assert_eq!(current["temperature_2m"], 12.3);
+        assert_eq!(current["temperature_unit"], "°C");
+
+        let today = payload(&messages, "raijin/new_york/today");
+        assert_eq!(today["temperature_max"], 16.0);
+        assert_eq!(today["precipitation_probability"], Value::Null);
+        assert_eq!(today["wind_speed_unit"], "km/h");
+
+        let alerts = payload(&messages, "raijin/new_york/alerts");
+        assert_eq!(alerts["count"], 1);
+        assert_eq!(alerts["alerts"][0]["name"], "Frost");
+        assert_eq!(
+            alerts["alerts"][0]["message"],
+            "Frost: Thu 05:00–09:00, down to -2.4°C"
+        );
+    }
+
+    #[test]
+    fn no_alerts_still_publish_a_count() {
+        let mut config = config("Home");
+        config.mqtt.discovery = false;
+        let messages = messages(&config, &forecast(), &[]);
+        let alerts = payload(&messages, "raijin/home/alerts");
+        assert_eq!(alerts["count"], 0);
+        assert_eq!(alerts["alerts"], Value::Array(Vec::new()));
+    }
+
+    #[test]
+    fn discovery_announces_every_sensor() {
+        let config = config("New York");
+        let messages = messages(&config, &forecast(), &[]);
+        let discovery: Vec<&str> = messages
+            .iter()
+            .map(|(topic, _)| topic.as_str())
+            .filter(|topic| topic.starts_with("homeassistant/"))
+            .collect();
+        assert_eq!(discovery.len(), 8);
+        assert!(discovery.iter().all(|topic| {
+            topic.starts_with("homeassistant/sensor/raijin_new_york/") && topic.ends_with("/config")
+        }));
+
+        let temperature = payload(
+            &messages,
+            "homeassistant/sensor/raijin_new_york/temperature/config",
+        );
+        assert_eq!(temperature["unique_id"], "raijin_new_york_temperature");
+        assert_eq!(temperature["state_topic"], "raijin/new_york/current");
+        assert_eq!(
+            temperature["value_template"],
+            "{{ value_json.temperature_2m }}"
+        );
+        assert_eq!(temperature["unit_of_measurement"], "°C");
+        assert_eq!(temperature["device_class"], "temperature");
+        assert_eq!(temperature["device"]["identifiers"][0], "raijin_new_york");
+        assert_eq!(temperature["device"]["name"], "Raijin New York");
+        // Sensors without a unit leave the key out rather than sending null
+        let weather = payload(
+            &messages,
+            "homeassistant/sensor/raijin_new_york/weather/config",
+        );
+        assert!(weather.get("unit_of_measurement").is_none());
+
+        let alerts = payload(
+            &messages,
+            "homeassistant/sensor/raijin_new_york/alerts/config",
+        );
+        assert_eq!(alerts["json_attributes_topic"], "raijin/new_york/alerts");
+    }
+
+    /// Publishes to a real broker and reads the retained state back
+    /// Run with e.g. `RAIJIN_TEST_MQTT_HOST=localhost cargo test -- --ignored` against mosquitto
+    #[test]
+    #[ignore = "needs an MQTT broker; set RAIJIN_TEST_MQTT_HOST"]
+    fn publishes_to_a_broker() {
+        let host = std::env::var("RAIJIN_TEST_MQTT_HOST").unwrap_or(String::from("localhost"));
+        // Topics of their own, so a broker in use isn't disturbed
+        let prefix = format!("raijin-test/{}", std::process::id());
+        let mut config = config("Integration Test");
+        let mqtt = &mut config.mqtt;
+        mqtt.host = Some(host.clone());
+        mqtt.discovery = false;
+        mqtt.current_topic = format!("{}/{{location}}/current", prefix);
+        mqtt.today_topic = format!("{}/{{location}}/today", prefix);
+        mqtt.alerts_topic = format!("{}/{{location}}/alerts", prefix);
+        publish(&config, &forecast(), &[frost()]).unwrap();
+
+        let reader = format!("{}-reader", config.mqtt.client_id);
+        let options = MqttOptions::new(reader, host, config.mqtt.port);
+        let (client, mut connection) = Client::new(options, 10);
+        let mut next_event = || {
+            connection
+                .recv_timeout(Duration::from_secs(5))
+                .expect("the broker stopped answering")
+                .unwrap()
+        };
+        let topic = format!("{}/integration_test/current", prefix);
+        client.subscribe(&topic, QoS::AtLeastOnce).unwrap();
+        let received = (0..10)
+            .map(|_| next_event())
+            .find_map(|event| match event {
+                Event::Incoming(Packet::Publish(publish)) => Some(publish),
+                _ => None,
+            })
+            .expect("the retained state never arrived");
+
+        // An empty retained message removes the one stored before
+        for kind in ["current", "today", "alerts"] {
+            let topic = format!("{}/integration_test/{}", prefix, kind);
+            client
+                .publish(topic, QoS::AtLeastOnce, true, Vec::new())
+                .unwrap();
+        }
+        let cleared = (0..20)
+            .map(|_| next_event())
+            .filter(|event| matches!(event, Event::Incoming(Packet::PubAck(_))))
+            .take(3)
+            .count();
+        assert_eq!(cleared, 3);
+
+        let current: Value = serde_json::from_slice(&received.payload).unwrap();
+        assert_eq!(received.topic, topic);
+        assert_eq!(current["location"], "Integration Test");
+        assert_eq!(current["temperature_2m"], 12.3);
+    }
+}
@@ -0,0 +1,184 @@
+mod bright_sky;
+mod consensus;
+mod met_norway;
+mod nws;
+mod open_meteo;
+
+use crate::config::{Config, ProviderKind};
+use crate::forecast::Forecast;
+use crate::warnings::Warning;
+use chrono::{DateTime, FixedOffset};
+use chrono_tz::Tz;
+use serde::Deserialize;
+use std::fmt;
+use ureq::Agent;
+
+#[derive(Debug)]
+pub enum ProviderError {
+    Http(ureq::Error),
+    /// The provider doesn't cover the configured location
+    Unsupported(String),
+    /// The provider's response lacked something the forecast needs
+    Incomplete(String),
+}
+
+impl fmt::Display for ProviderError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            ProviderError::Http(err) => write!(f, "{}", err),
+            ProviderError::Unsupported(message) => write!(f, "{}", message),
+            ProviderError::Incomplete(message) => write!(f, "incomplete response: {}", message),
+        }
+    }
+}
+
+impl std::error::Error for ProviderError {}
+
+impl From<ureq::Error> for ProviderError {
+    fn from(err: ureq::Error) -> Self {
+        ProviderError::Http(err)
+    }
+}
+
+/// A weather service Raijin can get its forecast from
+pub trait Provider {
+    /// Fetches the forecast for the configured location, units and number of days
+    /// An empty `weather` description is derived from the `weather_code`, so implementations
+    /// only fill it in with the service's own wording, as NWS does with `shortForecast`; the
+    /// personal station clears it again where it changes the code
+    fn fetch(&self, agent: &Agent, config: &Config) -> Result<Forecast, ProviderError>;
+
+    /// Official warnings the service publishes for the configured location
+    fn warnings(&self, _agent: &Agent, _config: &Config) -> Result<Vec<Warning>, ProviderError> {
+        Ok(Vec::new())
+    }
+}
+
+/// The provider selected by `provider` in the config
+pub fn from_config(config: &Config) -> Box<dyn Provider> {
+    from_kind(config.provider)
+}
+
+fn from_kind(kind: ProviderKind) -> Box<dyn Provider> {
+    match kind {
+        ProviderKind::OpenMeteo => Box::new(open_meteo::OpenMeteo),
+        ProviderKind::Nws => Box::new(nws::Nws),
+        ProviderKind::MetNorway => Box::new(met_norway::MetNorway),
+        ProviderKind::BrightSky => Box::new(bright_sky::BrightSky),
+        ProviderKind::Consensus => Box::new(consensus::Consensus),
+    }
+}
+
+/// The part of an Open-Meteo response naming the time zone it derived from the coordinates
+#[derive(Deserialize)]
+struct ZoneResponse {
+    timezone: String,
+}
+
+/// The location's time zone, for providers that only report UTC
+/// "auto" is derived from the coordinates by Open-Meteo, as it is for its own forecasts, so
+/// remote places don't get the system's time zone
+fn location_zone(agent: &Agent, config: &Config) -> Result<Tz, ProviderError> {
+    let location = config.location();
+    if let Ok(zone) = location.timezone.parse::<Tz>() {
+        return Ok(zone);
+    }
+    let response: ZoneResponse = agent
+        .get(format!(
+            "{}?latitude={:.4}&longitude={:.4}&timezone=auto",
+            config.endpoints.open_meteo, location.latitude, location.longitude
+        ))
+        .call()?
+        .body_mut()
+        .read_json()?;
+    response.timezone.parse().map_err(|_| {
+        ProviderError::Incomplete(format!("unknown time zone \"{}\"", response.timezone))
+    })
+}
+
+/// Converts a time to the location's time zone from `location_zone`
+fn to_local(time: DateTime<FixedOffset>, zone: Tz) -> DateTime<FixedOffset> {
+    time.with_timezone(&zone).fixed_offset()
+}
+
+/// Apparent temperature in °C from air temperature, relative humidity and wind speed in m/s,
+/// using the formula of the Australian Bureau of Meteorology, for providers that don't publish one
+pub fn apparent_temperature(
+    temperature: f32,
+    humidity: Option<f32>,
+    wind_speed: Option<f32>,
+) -> f32 {
+    let (Some(humidity), Some(wind_speed)) = (humidity, wind_speed) else {
+        return temperature;
+    };
+    let vapour_pressure =
+        humidity / 100.0 * 6.105 * (17.27 * temperature / (237.7 + temperature)).exp();
+    temperature + 0.33 * vapour_pressure - 0.70 * wind_speed - 4.00
+}
+
+fn max(values: impl Iterator<Item = f32>) -> Option<f32> {
+    values.reduce(f32::max)
+}
+
+fn min(values: impl Iterator<Item = f32>) -> Option<f32> {
+    values.reduce(f32::min)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::config::Location;
+    use std::thread;
+    use tiny_http::{Response, Server};
+
+    /// Answers a single request with `body`, returning the URL to send it to
+    fn serve_once(body: &'static str) -> String {
+        let server = Server::http("127.0.0.1:0").unwrap();
+        let address = server.server_addr().to_ip().unwrap();
+        thread::spawn(move || {
+            if let Ok(request) = server.recv() {
+                let _ = request.respond(Response::from_string(body));
+            }
+        });
+        format!("http://{}/v1/forecast", address)
+    }
+
+    fn chatham(timezone: &str) -> Config {
+        let mut config = Config::default();
+        config.locations.push(Location {
+            name: String::from("Waitangi"),
+            latitude: -43.95,
+            longitude: -176.56,
+            timezone: timezone.to_string(),
+            warning_area: None,
+            geocodes: Vec::new(),
+        });
+        config
+    }
+
+    #[test]
+    fn named_time_zones_need_no_request() {
+        let mut config = chatham("Pacific/Chatham");
+        config.endpoints.open_meteo = String::from("http://127.0.0.1:9/unreachable");
+        let zone = location_zone(&Agent::new_with_defaults(), &config).unwrap();
+        assert_eq!(zone, chrono_tz::Pacific::Chatham);
+    }
+
+    #[test]
+    fn auto_time_zones_come_from_the_coordinates() {
+        let mut config = chatham("auto");
+        config.endpoints.open_meteo =
+            serve_once(r#"{"latitude": -43.95, "timezone": "Pacific/Chatham"}"#);
+        let zone = location_zone(&Agent::new_with_defaults(), &config).unwrap();
+        assert_eq!(zone, chrono_tz::Pacific::Chatham);
+
+        // +13:45 in the southern summer, which no machine running the tests is likely to use
+        let time = DateTime::parse_from_rfc3339("2026-01-15T12:00:00Z").unwrap();
+        let local = to_local(time, zone);
+        assert_eq!(local.offset().local_minus_utc(), 13 * 3600 + 45 * 60);
+        assert_eq!(
+            local.format("%Y-%m-%dT%H:%M").to_string(),
+            "2026-01-16T01:45"
+        );
+    }
+}
@@ -0,0 +1,355 @@
+use super::{Provider, ProviderError, apparent_temperature, location_zone, max, min, to_local};
+use crate::config::Config;
+use crate::forecast::{Current, Forecast, Hour, Period, Source, Station};
+use chrono::{DateTime, TimeDelta, Utc};
+use chrono_tz::Tz;
+use serde::Deserialize;
+use ureq::Agent;
+use urlencoding::encode;
+
+/// One hour of MOSMIX forecast, or of observations for past hours, in DWD units (°C, km/h, mm)
+#[derive(Deserialize, Debug)]
+struct Record {
+    timestamp: String,
+    temperature: Option<f32>,
+    relative_humidity: Option<f32>,
+    wind_speed: Option<f32>,
+    wind_gust_speed: Option<f32>,
+    precipitation: Option<f32>,
+    precipitation_probability: Option<f32>,
+    cloud_cover: Option<f32>,
+    condition: Option<String>,
+    icon: Option<String>,
+}
+
+#[derive(Deserialize, Debug)]
+struct WeatherResponse {
+    weather: Vec<Record>,
+}
+
+/// Latest observation of a station; wind and precipitation cover the last 10, 30 or 60 minutes
+#[derive(Deserialize, Debug)]
+struct Observation {
+    timestamp: String,
+    source_id: i64,
+    temperature: Option<f32>,
+    relative_humidity: Option<f32>,
+    wind_speed_10: Option<f32>,
+    precipitation_60: Option<f32>,
+    cloud_cover: Option<f32>,
+    condition: Option<String>,
+    icon: Option<String>,
+}
+
+#[derive(Deserialize, Debug)]
+struct StationSource {
+    id: i64,
+    station_name: String,
+    /// Distance from the location in metres
+    distance: f32,
+}
+
+#[derive(Deserialize, Debug)]
+struct CurrentResponse {
+    weather: Observation,
+    sources: Vec<StationSource>,
+}
+
+/// Maps Bright Sky's condition onto the closest WMO code, with the hour's precipitation in mm for
+/// the intensity and the icon or cloud cover for dry weather
+/// WMO has no sleet, so sleet counts as snow of the same intensity
+fn weather_code(
+    condition: Option<&str>,
+    icon: Option<&str>,
+    precipitation: Option<f32>,
+    cloud_cover: Option<f32>,
+) -> i32 {
+    let precipitation = precipitation.unwrap_or_default();
+    let intensity = |(moderate, heavy), codes: [i32; 3]| {
+        if precipitation < moderate {
+            codes[0]
+        } else if precipitation < heavy {
+            codes[1]
+        } else {
+            codes[2]
+        }
+    };
+    match condition {
+        Some("thunderstorm") => 95,
+        Some("hail") => 96,
+        Some("snow" | "sleet") => intensity((1.0, 4.0), [71, 73, 75]),
+        Some("rain") => intensity((2.5, 7.6), [61, 63, 65]),
+        Some("fog") => 45,
+        _ => match (icon, cloud_cover) {
+            (Some("clear-day" | "clear-night"), _) => 0,
+            (Some("partly-cloudy-day" | "partly-cloudy-night"), _) => 2,
+            (Some("cloudy"), _) => 3,
+            (_, Some(cover)) if cover < 12.5 => 0,
+            (_, Some(cover)) if cover < 37.5 => 1,
+            (_, Some(cover)) if cover < 75.0 => 2,
+            _ => 3,
+        },
+    }
+}
+
+/// Bright Sky, which serves Deutscher Wetterdienst's open data: MOSMIX forecasts and station
+/// observations, mostly for Germany
+/// Using this API: <https://brightsky.dev/docs/>
+pub struct BrightSky;
+
+impl BrightSky {
+    /// Current conditions at the nearest station reporting them, if there is one
+    fn observation(
+        &self,
+        agent: &Agent,
+        config: &Config,
+        zone: Tz,
+    ) -> Result<Option<Current>, ProviderError> {
+        let location = config.location();
+        let units = &config.units;
+        let url = format!(
+            "{}/current_weather?lat={:.4}&lon={:.4}",
+            config.endpoints.bright_sky, location.latitude, location.longitude
+        );
+        let response: CurrentResponse = match agent.get(url).call() {
+            Err(ureq::Error::StatusCode(404)) => return Ok(None),
+            response => response?.body_mut().read_json()?,
+        };
+        let observation = response.weather;
+        let (Some(temperature), Ok(time)) = (
+            observation.temperature,
+            DateTime::parse_from_rfc3339(&observation.timestamp),
+        ) else {
+            return Ok(None);
+        };
+
+        Ok(Some(Current {
+            time: to_local(time, zone).format("%Y-%m-%dT%H:%M").to_string(),
+            temperature: units.temperature.convert_celsius(temperature),
+            apparent_temperature: units.temperature.convert_celsius(apparent_temperature(
+                temperature,
+                observation.relative_humidity,
+                observation.wind_speed_10.map(|kmh| kmh / 3.6),
+            )),
+            weather_code: weather_code(
+                observation.condition.as_deref(),
+                observation.icon.as_deref(),
+                observation.precipitation_60,
+                observation.cloud_cover,
+            ),
+            weather: String::new(),
+            station: response
+                .sources
+                .iter()
+                .find(|source| source.id == observation.source_id)
+                .map(|source| Station {
+                    name: source.station_name.clone(),
+                    distance: Some(source.distance / 1000.0),
+                }),
+            source: Source::Station,
+        }))
+    }
+}
+
+impl Provider for BrightSky {
+    fn fetch(&self, agent: &Agent, config: &Config) -> Result<Forecast, ProviderError> {
+        let location = config.location();
+        let units = &config.units;
+        let zone = location_zone(agent, config)?;
+
+        // Records are requested from midnight, so today's past hours come from observations
+        let now = to_local(Utc::now().fixed_offset(), zone);
+        let midnight = |days| {
+            format!(
+                "{}T00:00:00{}",
+                (now + TimeDelta::days(days)).format("%Y-%m-%d"),
+                now.format("%:z")
+            )
+        };
+        let url = format!(
+            "{}/weather?lat={:.4}&lon={:.4}&date={}&last_date={}",
+            config.endpoints.bright_sky,
+            location.latitude,
+            location.longitude,
+            encode(&midnight(0)),
+            encode(&midnight(i64::from(config.forecast_days))),
+        );
+        let response: WeatherResponse = match agent.get(url).call() {
+            Err(ureq::Error::StatusCode(404)) => {
+                return Err(ProviderError::Unsupported(format!(
+                    "Bright Sky has no DWD station near {}",
+                    location.name
+                )));
+            }
+            response => response?.body_mut().read_json()?,
+        };
+
+        let mut utc_offset_seconds = 0;
+        let mut hourly: Vec<Hour> = Vec::new();
+        for record in &response.weather {
+            let (Some(temperature), Ok(time)) = (
+                record.temperature,
+                DateTime::parse_from_rfc3339(&record.timestamp),
+            ) else {
+                continue;
+            };
+            let time = to_local(time, zone);
+            if hourly.is_empty() {
+                utc_offset_seconds = time.offset().local_minus_utc();
+            }
+            hourly.push(Hour {
+                datetime: time.format("%Y-%m-%dT%H:%M").to_string(),
+                temperature: units.temperature.convert_celsius(temperature),
+                apparent_temperature: units.temperature.convert_celsius(apparent_temperature(
+                    temperature,
+                    record.relative_humidity,
+                    record.wind_speed.map(|kmh| kmh / 3.6),
+                )),
+                weather_code: weather_code(
+                    record.condition.as_deref(),
+                    record.icon.as_deref(),
+                    record.precipitation,
+                    record.cloud_cover,
+                ),
+                weather: String::new(),
+                precipitation_probability: record
+                    .precipitation_probability
+                    .map(|probability| probability as i32),
+                precipitation: record
+                    .precipitation
+                    .map_or(0.0, |mm| units.precipitation.convert_mm(mm)),
+                wind_speed: record
+                    .wind_speed
+                    .map_or(0.0, |kmh| units.wind_speed.convert_kmh(kmh)),
+                wind_gusts: record
+                    .wind_gust_speed
+                    .map(|kmh| units.wind_speed.convert_kmh(kmh)),
+                humidity: record
+                    .relative_humidity
+                    .map(|humidity| humidity.round() as i32),
+                temperature_range: None,
+            });
+        }
+        if hourly.is_empty() {
+            return Err(ProviderError::Incomplete(String::from("no hourly records")));
+        }
+
+        let mut dates: Vec<&str> = Vec::new();
+        for hour in &hourly {
+            let date = hour.datetime.get(..10).unwrap_or_default();
+            if dates.last() != Some(&date) {
+                dates.push(date);
+            }
+        }
+        dates.truncate(config.forecast_days as usize);
+
+        let periods: Vec<Period> = dates
+            .iter()
+            .map(|date| {
+                let day: Vec<&Hour> = hourly
+                    .iter()
+                    .filter(|hour| hour.datetime.starts_with(date))
+                    .collect();
+                Period {
+                    date: date.to_string(),
+                    // Like Open-Meteo, the day gets its most severe weather
+                    weather_code: day.iter().map(|hour| hour.weather_code).max().unwrap_or(3),
+                    weather: String::new(),
+                    temperature_max: max(day.iter().map(|hour| hour.temperature))
+                        .unwrap_or_default(),
+                    temperature_min: min(day.iter().map(|hour| hour.temperature))
+                        .unwrap_or_default(),
+                    apparent_temperature_max: max(day.iter().map(|hour| hour.apparent_temperature))
+                        .unwrap_or_default(),
+                    apparent_temperature_min: min(day.iter().map(|hour| hour.apparent_temperature))
+                        .unwrap_or_default(),
+                    precipitation_probability: day
+                        .iter()
+                        .filter_map(|hour| hour.precipitation_probability)
+                        .max(),
+                    // MOSMIX has no sunrise or sunset times
+                    sunrise: String::new(),
+                    sunset: String::new(),
+                    wind_gusts_max: max(day.iter().filter_map(|hour| hour.wind_gusts)),
+                    narrative: String::new(),
+                    disagreement: None,
+                }
+            })
+            .collect();
+
+        // Without a station reporting right now, or if the observations can't be fetched, the
+        // forecast for the current hour stands in
+        let current = match self.observation(agent, config, zone).ok().flatten() {
+            Some(current) => current,
+            None => {
+                let now = now.format("%Y-%m-%dT%H:%M").to_string();
+                let hour = hourly
+                    .iter()
+                    .rev()
+                    .find(|hour| hour.datetime <= now)
+                    .unwrap_or(&hourly[0]);
+                Current {
+                    time: hour.datetime.clone(),
+                    temperature: hour.temperature,
+                    apparent_temperature: hour.apparent_temperature,
+                    weather_code: hour.weather_code,
+                    weather: String::new(),
+                    station: None,
+                    source: Source::Model,
+                }
+            }
+        };
+
+        Ok(Forecast {
+            timezone: zone.name().to_string(),
+            utc_offset_seconds,
+            periods,
+            current,
+            hourly,
+        })
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn conditions_map_onto_wmo_codes() {
+        assert_eq!(
+            weather_code(Some("thunderstorm"), None, Some(10.0), None),
+            95
+        );
+        assert_eq!(weather_code(Some("hail"), None, None, None), 96);
+        assert_eq!(
+            weather_code(Some("fog"), Some("fog"), None, Some(100.0)),
+            45
+        );
+    }
+
+    #[test]
+    fn precipitation_sets_the_intensity() {
+        assert_eq!(weather_code(Some("rain"), None, Some(0.4), None), 61);
+        assert_eq!(weather_code(Some("rain"), None, Some(2.5), None), 63);
+        assert_eq!(weather_code(Some("rain"), None, Some(8.0), None), 65);
+        assert_eq!(weather_code(Some("sleet"), None, Some(1.5), None), 73);
+        assert_eq!(weather_code(Some("snow"), None, Some(5.0), None), 75);
+        assert_eq!(weather_code(Some("snow"), None, None, None), 71);
+    }
+
+    #[test]
+    fn dry_weather_follows_the_icon_then_the_cloud_cover() {
+        assert_eq!(
+            weather_code(Some("dry"), Some("clear-night"), None, Some(90.0)),
+            0
+        );
+        assert_eq!(
+            weather_code(Some("dry"), Some("partly-cloudy-day"), None, None),
+            2
+        );
+        assert_eq!(weather_code(Some("dry"), Some("wind"), None, Some(10.0)), 0);
+        assert_eq!(weather_code(None, None, None, Some(30.0)), 1);
+        assert_eq!(weather_code(None, None, None, Some(50.0)), 2);
+        assert_eq!(weather_code(None, None, None, None), 3);
+    }
+}
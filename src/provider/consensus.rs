@@ -0,0 +1,312 @@
+use super::{Provider, ProviderError, from_kind, max, min};
+use crate::config::Config;
+use crate::forecast::{Current, Forecast, Hour, Period, Source};
+use crate::warnings::Warning;
+use std::collections::BTreeMap;
+use ureq::Agent;
+
+/// Rounds to one decimal, like the providers' own values
+fn round(value: f32) -> f32 {
+    (value * 10.0).round() / 10.0
+}
+
+fn mean(values: impl Iterator<Item = f32>) -> f32 {
+    let (sum, count) = values.fold((0.0, 0), |(sum, count), value| (sum + value, count + 1));
+    if count == 0 {
+        0.0
+    } else {
+        round(sum / count as f32)
+    }
+}
+
+/// The mean of the providers that have a value, or None when none of them do
+fn mean_present(values: impl Iterator<Item = Option<f32>>) -> Option<f32> {
+    let present: Vec<f32> = values.flatten().collect();
+    (!present.is_empty()).then(|| mean(present.into_iter()))
+}
+
+/// Lowest and highest value, if more than one provider contributed
+fn spread(values: &[f32]) -> Option<(f32, f32)> {
+    if values.len() < 2 {
+        return None;
+    }
+    Some((min(values.iter().copied())?, max(values.iter().copied())?))
+}
+
+/// Whether the forecast's hours cover `date` from midnight
+/// Providers that start at the current hour only describe the rest of today
+fn covers_whole_day(forecast: &Forecast, date: &str) -> bool {
+    forecast
+        .hourly
+        .first()
+        .is_none_or(|hour| hour.datetime.as_str() <= format!("{}T00:00", date).as_str())
+}
+
+/// Runs `fetch` for every provider of the consensus, skipping the ones that fail
+/// A provider can fail on its own, e.g. NWS outside the US, so only all of them failing is an error
+fn each<T>(
+    config: &Config,
+    fetch: impl Fn(&dyn Provider) -> Result<T, ProviderError>,
+) -> Result<Vec<T>, ProviderError> {
+    let mut results = Vec::new();
+    let mut first_error = None;
+    for kind in &config.consensus.providers {
+        match fetch(from_kind(*kind).as_ref()) {
+            Ok(result) => results.push(result),
+            Err(err) => {
+                first_error.get_or_insert(err);
+            }
+        }
+    }
+    match first_error {
+        Some(err) if results.is_empty() => Err(err),
+        _ => Ok(results),
+    }
+}
+
+/// Groups entries of every forecast by key, keeping the providers' order within each group
+fn align<'a, T>(
+    forecasts: &'a [Forecast],
+    entries: impl Fn(&'a Forecast) -> &'a [T],
+    key: impl Fn(&T) -> &str,
+) -> BTreeMap<&'a str, Vec<&'a T>>
+where
+    T: 'a,
+{
+    let mut groups: BTreeMap<&str, Vec<&T>> = BTreeMap::new();
+    for forecast in forecasts {
+        for entry in entries(forecast) {
+            // Local times and dates sort chronologically as strings
+            groups.entry(key(entry)).or_default().push(entry);
+        }
+    }
+    groups
+}
+
+/// The mean of every hour any provider forecasts
+/// Hours only one provider has, e.g. after MET Norway's first days, are that provider's own and
+/// have no spread
+fn merge_hourly(forecasts: &[Forecast]) -> Vec<Hour> {
+    align(forecasts, |f| &f.hourly, |hour| &hour.datetime)
+        .into_values()
+        .map(|hours| {
+            let first = hours[0];
+            let temperatures: Vec<f32> = hours.iter().map(|hour| hour.temperature).collect();
+            Hour {
+                datetime: first.datetime.clone(),
+                temperature: mean(temperatures.iter().copied()),
+                apparent_temperature: mean(hours.iter().map(|hour| hour.apparent_temperature)),
+                weather_code: first.weather_code,
+                weather: first.weather.clone(),
+                precipitation_probability: mean_present(
+                    hours
+                        .iter()
+                        .map(|hour| hour.precipitation_probability.map(|p| p as f32)),
+                )
+                .map(|mean| mean.round() as i32),
+                precipitation: mean(hours.iter().map(|hour| hour.precipitation)),
+                wind_speed: mean(hours.iter().map(|hour| hour.wind_speed)),
+                wind_gusts: mean_present(hours.iter().map(|hour| hour.wind_gusts)),
+                humidity: hours.iter().any(|hour| hour.humidity.is_some()).then(|| {
+                    mean(
+                        hours
+                            .iter()
+                            .filter_map(|hour| hour.humidity)
+                            .map(|h| h as f32),
+                    )
+                    .round() as i32
+                }),
+                temperature_range: spread(&temperatures).map(|(low, high)| [low, high]),
+            }
+        })
+        .collect()
+}
+
+/// The mean of several providers, with the spread between them
+/// Weather codes, descriptions and everything else that can't be averaged come from the first
+/// provider in `consensus.providers` that has the hour or day
+pub struct Consensus;
+
+impl Provider for Consensus {
+    fn fetch(&self, agent: &Agent, config: &Config) -> Result<Forecast, ProviderError> {
+        let forecasts = each(config, |provider| provider.fetch(agent, config))?;
+
+        let mut hourly = merge_hourly(&forecasts);
+
+        let mut periods: Vec<Period> = align(&forecasts, |f| &f.periods, |period| &period.date)
+            .into_values()
+            .map(|periods| {
+                let first = periods[0];
+                let highs: Vec<f32> = periods.iter().map(|p| p.temperature_max).collect();
+                let lows: Vec<f32> = periods.iter().map(|p| p.temperature_min).collect();
+                // Today's high from a provider that starts now can't be compared with a whole day's
+                let whole_days: Vec<&Period> = forecasts
+                    .iter()
+                    .filter(|forecast| covers_whole_day(forecast, &first.date))
+                    .filter_map(|forecast| forecast.periods.iter().find(|p| p.date == first.date))
+                    .collect();
+                let whole_highs: Vec<f32> = whole_days.iter().map(|p| p.temperature_max).collect();
+                let whole_lows: Vec<f32> = whole_days.iter().map(|p| p.temperature_min).collect();
+                let disagreement = match (spread(&whole_highs), spread(&whole_lows)) {
+                    (Some(highs), Some(lows)) => {
+                        Some(round((highs.1 - highs.0).max(lows.1 - lows.0)))
+                    }
+                    _ => None,
+                };
+                Period {
+                    date: first.date.clone(),
+                    weather_code: first.weather_code,
+                    weather: first.weather.clone(),
+                    temperature_max: mean(highs.iter().copied()),
+                    temperature_min: mean(lows.iter().copied()),
+                    apparent_temperature_max: mean(
+                        periods.iter().map(|p| p.apparent_temperature_max),
+                    ),
+                    apparent_temperature_min: mean(
+                        periods.iter().map(|p| p.apparent_temperature_min),
+                    ),
+                    precipitation_probability: mean_present(
+                        periods
+                            .iter()
+                            .map(|p| p.precipitation_probability.map(|p| p as f32)),
+                    )
+                    .map(|mean| mean.round() as i32),
+                    sunrise: periods
+                        .iter()
+                        .map(|p| &p.sunrise)
+                        .find(|sunrise| !sunrise.is_empty())
+                        .cloned()
+                        .unwrap_or_default(),
+                    sunset: periods
+                        .iter()
+                        .map(|p| &p.sunset)
+                        .find(|sunset| !sunset.is_empty())
+                        .cloned()
+                        .unwrap_or_default(),
+                    wind_gusts_max: mean_present(periods.iter().map(|p| p.wind_gusts_max)),
+                    narrative: periods
+                        .iter()
+                        .map(|p| &p.narrative)
+                        .find(|narrative| !narrative.is_empty())
+                        .cloned()
+                        .unwrap_or_default(),
+                    disagreement,
+                }
+            })
+            .collect();
+        periods.truncate(config.forecast_days as usize);
+        if let Some(last) = periods.last() {
+            hourly.retain(|hour| hour.datetime.get(..10).unwrap_or_default() <= last.date.as_str());
+        }
+
+        let first = &forecasts[0];
+        let current = Current {
+            time: first.current.time.clone(),
+            temperature: mean(forecasts.iter().map(|f| f.current.temperature)),
+            apparent_temperature: mean(forecasts.iter().map(|f| f.current.apparent_temperature)),
+            weather_code: first.current.weather_code,
+            weather: first.current.weather.clone(),
+            station: forecasts.iter().find_map(|f| f.current.station.clone()),
+            // Averaged with the other providers, so no longer a plain observation
+            source: Source::Model,
+        };
+
+        Ok(Forecast {
+            timezone: first.timezone.clone(),
+            utc_offset_seconds: first.utc_offset_seconds,
+            periods,
+            current,
+            hourly,
+        })
+    }
+
+    /// Every provider's own warnings together
+    fn warnings(&self, agent: &Agent, config: &Config) -> Result<Vec<Warning>, ProviderError> {
+        Ok(each(config, |provider| provider.warnings(agent, config))?
+            .into_iter()
+            .flatten()
+            .collect())
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::forecast::fixtures::hour;
+
+    fn forecast(hours: &[(&str, f32)]) -> Forecast {
+        Forecast {
+            hourly: hours
+                .iter()
+                .map(|&(datetime, temperature)| hour(datetime, temperature))
+                .collect(),
+            ..Default::default()
+        }
+    }
+
+    #[test]
+    fn entries_are_grouped_by_time_in_provider_order() {
+        let forecasts = [
+            forecast(&[("2026-10-18T01:00", 1.0), ("2026-10-18T00:00", 0.0)]),
+            forecast(&[("2026-10-18T01:00", 11.0), ("2026-10-18T02:00", 12.0)]),
+        ];
+        let groups = align(&forecasts, |f| &f.hourly, |hour| &hour.datetime);
+
+        let keys: Vec<&str> = groups.keys().copied().collect();
+        assert_eq!(
+            keys,
+            ["2026-10-18T00:00", "2026-10-18T01:00", "2026-10-18T02:00"]
+        );
+        let temperatures = |key| -> Vec<f32> {
+            groups[key]
+                .iter()
+                .map(|hour: &&Hour| hour.temperature)
+                .collect()
+        };
+        assert_eq!(temperatures("2026-10-18T00:00"), [0.0]);
+        assert_eq!(temperatures("2026-10-18T01:00"), [1.0, 11.0]);
+        assert_eq!(temperatures("2026-10-18T02:00"), [12.0]);
+    }
+
+    #[test]
+    fn hours_of_a_single_provider_are_kept_without_a_spread() {
+        let forecasts = [
+            forecast(&[("2026-10-18T00:00", 10.0), ("2026-10-18T01:00", 11.0)]),
+            forecast(&[("2026-10-18T00:00", 14.0)]),
+        ];
+        let hourly = merge_hourly(&forecasts);
+
+        assert_eq!(hourly.len(), 2);
+        assert_eq!(hourly[0].temperature, 12.0);
+        assert_eq!(hourly[0].temperature_range, Some([10.0, 14.0]));
+        assert_eq!(hourly[1].datetime, "2026-10-18T01:00");
+        assert_eq!(hourly[1].temperature, 11.0);
+        assert_eq!(hourly[1].temperature_range, None);
+    }
+
+    #[test]
+    fn spread_needs_two_providers() {
+        assert_eq!(spread(&[]), None);
+        assert_eq!(spread(&[4.0]), None);
+        assert_eq!(spread(&[4.0, -1.5, 2.0]), Some((-1.5, 4.0)));
+    }
+
+    #[test]
+    fn means_skip_missing_values() {
+        assert_eq!(mean([1.0, 2.0, 4.0].into_iter()), 2.3);
+        assert_eq!(
+            mean_present([Some(10.0), None, Some(20.0)].into_iter()),
+            Some(15.0)
+        );
+        assert_eq!(mean_present([None, None].into_iter()), None);
+    }
+
+    #[test]
+    fn providers_starting_now_miss_part_of_today() {
+        let from_midnight = forecast(&[("2026-10-18T00:00", 0.0)]);
+        let from_now = forecast(&[("2026-10-18T14:00", 0.0)]);
+        assert!(covers_whole_day(&from_midnight, "2026-10-18"));
+        assert!(!covers_whole_day(&from_now, "2026-10-18"));
+        assert!(covers_whole_day(&from_now, "2026-10-19"));
+    }
+}
@@ -0,0 +1,304 @@
+use super::{Provider, ProviderError, apparent_temperature, location_zone, max, min, to_local};
+use crate::config::Config;
+use crate::forecast::{Current, Forecast, Hour, Period, Source};
+use chrono::{DateTime, FixedOffset};
+use serde::Deserialize;
+use ureq::Agent;
+
+#[derive(Deserialize, Debug)]
+struct Summary {
+    /// e.g. "lightrainshowers_day"
+    symbol_code: String,
+}
+
+/// Values for the hours following a time step; the compact API leaves most of them out
+#[derive(Deserialize, Debug, Default)]
+struct PeriodDetails {
+    air_temperature_max: Option<f32>,
+    air_temperature_min: Option<f32>,
+    probability_of_precipitation: Option<f32>,
+    /// In mm
+    precipitation_amount: Option<f32>,
+}
+
+#[derive(Deserialize, Debug)]
+struct NextHours {
+    summary: Summary,
+    #[serde(default)]
+    details: PeriodDetails,
+}
+
+/// Values at the time step itself, in °C, % and m/s
+#[derive(Deserialize, Debug)]
+struct InstantDetails {
+    air_temperature: f32,
+    relative_humidity: Option<f32>,
+    wind_speed: Option<f32>,
+    wind_speed_of_gust: Option<f32>,
+}
+
+#[derive(Deserialize, Debug)]
+struct Instant {
+    details: InstantDetails,
+}
+
+#[derive(Deserialize, Debug)]
+struct Data {
+    instant: Instant,
+    next_1_hours: Option<NextHours>,
+    next_6_hours: Option<NextHours>,
+    next_12_hours: Option<NextHours>,
+}
+
+impl Data {
+    /// The shortest period following the time step; hourly steps have all three, later ones
+    /// only the 6 and 12 hour periods
+    fn next(&self) -> Option<&NextHours> {
+        self.next_1_hours
+            .as_ref()
+            .or(self.next_6_hours.as_ref())
+            .or(self.next_12_hours.as_ref())
+    }
+}
+
+#[derive(Deserialize, Debug)]
+struct TimeStep {
+    /// UTC time, e.g. "2026-10-18T14:00:00Z"
+    time: String,
+    data: Data,
+}
+
+#[derive(Deserialize, Debug)]
+struct Properties {
+    timeseries: Vec<TimeStep>,
+}
+
+#[derive(Deserialize, Debug)]
+struct MetNorwayRawForecast {
+    properties: Properties,
+}
+
+/// Maps a symbol code such as "lightrainshowers_day" onto the closest WMO code
+/// WMO has no sleet, so sleet counts as snow of the same intensity
+fn weather_code(symbol: &str) -> i32 {
+    let symbol = symbol.split('_').next().unwrap_or_default();
+    if symbol.contains("thunder") {
+        return 95;
+    }
+    match symbol {
+        "clearsky" => 0,
+        "fair" => 1,
+        "partlycloudy" => 2,
+        "cloudy" => 3,
+        "fog" => 45,
+        "lightrain" => 61,
+        "rain" => 63,
+        "heavyrain" => 65,
+        "lightsnow" | "lightsleet" => 71,
+        "snow" | "sleet" => 73,
+        "heavysnow" | "heavysleet" => 75,
+        "lightrainshowers" => 80,
+        "rainshowers" => 81,
+        "heavyrainshowers" => 82,
+        "lightsnowshowers" | "snowshowers" | "lightsleetshowers" | "sleetshowers" => 85,
+        "heavysnowshowers" | "heavysleetshowers" => 86,
+        _ => 3,
+    }
+}
+
+/// A time step converted to the location's time and the configured units
+struct Step<'a> {
+    time: DateTime<FixedOffset>,
+    data: &'a Data,
+    temperature: f32,
+    apparent_temperature: f32,
+    weather_code: i32,
+    precipitation_probability: Option<f32>,
+    wind_speed: Option<f32>,
+    wind_gusts: Option<f32>,
+}
+
+/// The Norwegian Meteorological Institute's Locationforecast, worldwide and without an API key
+/// Using this API: <https://api.met.no/weatherapi/locationforecast/2.0/documentation>
+pub struct MetNorway;
+
+impl Provider for MetNorway {
+    fn fetch(&self, agent: &Agent, config: &Config) -> Result<Forecast, ProviderError> {
+        let location = config.location();
+        let units = &config.units;
+        let zone = location_zone(agent, config)?;
+        // Their terms ask for coordinates with at most four decimals, so responses can be cached
+        let json: MetNorwayRawForecast = agent
+            .get(format!(
+                "{}?lat={:.4}&lon={:.4}",
+                config.endpoints.met_norway, location.latitude, location.longitude
+            ))
+            .call()?
+            .body_mut()
+            .read_json()?;
+
+        let steps: Vec<Step> = json
+            .properties
+            .timeseries
+            .iter()
+            .filter_map(|step| {
+                let time = to_local(DateTime::parse_from_rfc3339(&step.time).ok()?, zone);
+                let details = &step.data.instant.details;
+                let next = step.data.next();
+                Some(Step {
+                    time,
+                    data: &step.data,
+                    temperature: units.temperature.convert_celsius(details.air_temperature),
+                    apparent_temperature: units.temperature.convert_celsius(apparent_temperature(
+                        details.air_temperature,
+                        details.relative_humidity,
+                        details.wind_speed,
+                    )),
+                    weather_code: next.map_or(3, |next| weather_code(&next.summary.symbol_code)),
+                    precipitation_probability: next
+                        .and_then(|next| next.details.probability_of_precipitation),
+                    wind_speed: details
+                        .wind_speed
+                        .map(|speed| units.wind_speed.convert_kmh(speed * 3.6)),
+                    wind_gusts: details
+                        .wind_speed_of_gust
+                        .map(|gust| units.wind_speed.convert_kmh(gust * 3.6)),
+                })
+            })
+            .collect();
+        let Some(first) = steps.first() else {
+            return Err(ProviderError::Incomplete(String::from(
+                "the time series is empty",
+            )));
+        };
+
+        let mut dates: Vec<String> = Vec::new();
+        for step in &steps {
+            let date = step.time.format("%Y-%m-%d").to_string();
+            if dates.last() != Some(&date) {
+                dates.push(date);
+            }
+        }
+        dates.truncate(config.forecast_days as usize);
+
+        let mut periods: Vec<Period> = Vec::new();
+        for date in &dates {
+            let day: Vec<&Step> = steps
+                .iter()
+                .filter(|step| step.time.format("%Y-%m-%d").to_string() == *date)
+                .collect();
+            // The 6 hour periods of the complete API catch extremes between the time steps
+            let period_details = day
+                .iter()
+                .filter_map(|step| step.data.next_6_hours.as_ref())
+                .map(|next| &next.details);
+            let temperature_max = max(day.iter().map(|step| step.temperature).chain(
+                period_details
+                    .clone()
+                    .filter_map(|details| details.air_temperature_max)
+                    .map(|celsius| units.temperature.convert_celsius(celsius)),
+            ))
+            .unwrap_or_default();
+            let temperature_min = min(day.iter().map(|step| step.temperature).chain(
+                period_details
+                    .filter_map(|details| details.air_temperature_min)
+                    .map(|celsius| units.temperature.convert_celsius(celsius)),
+            ))
+            .unwrap_or_default();
+
+            periods.push(Period {
+                date: date.clone(),
+                // Like Open-Meteo, the day gets its most severe weather
+                weather_code: day.iter().map(|step| step.weather_code).max().unwrap_or(3),
+                weather: String::new(),
+                temperature_max,
+                temperature_min,
+                apparent_temperature_max: max(day.iter().map(|step| step.apparent_temperature))
+                    .unwrap_or(temperature_max),
+                apparent_temperature_min: min(day.iter().map(|step| step.apparent_temperature))
+                    .unwrap_or(temperature_min),
+                precipitation_probability: max(day
+                    .iter()
+                    .filter_map(|step| step.precipitation_probability))
+                .map(|probability| probability as i32),
+                // Locationforecast has no sunrise or sunset times
+                sunrise: String::new(),
+                sunset: String::new(),
+                wind_gusts_max: max(day.iter().filter_map(|step| step.wind_gusts)),
+                narrative: String::new(),
+                disagreement: None,
+            });
+        }
+
+        // Only the first two or three days come in hourly steps; the rest only feed the daily
+        // forecast
+        let last_date = dates.last().cloned().unwrap_or_default();
+        let hourly: Vec<Hour> = steps
+            .iter()
+            .filter(|step| step.data.next_1_hours.is_some())
+            .map(|step| Hour {
+                datetime: step.time.format("%Y-%m-%dT%H:%M").to_string(),
+                temperature: step.temperature,
+                apparent_temperature: step.apparent_temperature,
+                weather_code: step.weather_code,
+                weather: String::new(),
+                precipitation_probability: step
+                    .precipitation_probability
+                    .map(|probability| probability as i32),
+                precipitation: step
+                    .data
+                    .next_1_hours
+                    .as_ref()
+                    .and_then(|next| next.details.precipitation_amount)
+                    .map_or(0.0, |mm| units.precipitation.convert_mm(mm)),
+                wind_speed: step.wind_speed.unwrap_or_default(),
+                wind_gusts: step.wind_gusts,
+                humidity: step
+                    .data
+                    .instant
+                    .details
+                    .relative_humidity
+                    .map(|humidity| humidity.round() as i32),
+                temperature_range: None,
+            })
+            .filter(|hour| hour.datetime.get(..10).unwrap_or_default() <= last_date.as_str())
+            .collect();
+
+        Ok(Forecast {
+            timezone: zone.name().to_string(),
+            utc_offset_seconds: first.time.offset().local_minus_utc(),
+            periods,
+            current: Current {
+                time: first.time.format("%Y-%m-%dT%H:%M").to_string(),
+                temperature: first.temperature,
+                apparent_temperature: first.apparent_temperature,
+                weather_code: first.weather_code,
+                weather: String::new(),
+                station: None,
+                source: Source::Model,
+            },
+            hourly,
+        })
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn symbols_map_onto_wmo_codes() {
+        assert_eq!(weather_code("clearsky_day"), 0);
+        assert_eq!(weather_code("clearsky_polartwilight"), 0);
+        assert_eq!(weather_code("fair_night"), 1);
+        assert_eq!(weather_code("cloudy"), 3);
+        assert_eq!(weather_code("heavyrain"), 65);
+        assert_eq!(weather_code("lightrainshowers_day"), 80);
+        assert_eq!(weather_code("sleet"), 73);
+        assert_eq!(weather_code("heavysleetshowers_night"), 86);
+        // Misspelt like this in MET's own symbol list
+        assert_eq!(weather_code("lightssnowshowersandthunder_day"), 95);
+        assert_eq!(weather_code("rainandthunder"), 95);
+        assert_eq!(weather_code("unknown_day"), 3);
+    }
+}
@@ -0,0 +1,465 @@
+use super::{Provider, ProviderError, max, min};
+use crate::config::Config;
+use crate::forecast::{Current, Forecast, Hour, Period, Source};
+use crate::warnings::{Severity, Warning};
+use chrono::DateTime;
+use serde::Deserialize;
+use serde::de::DeserializeOwned;
+use std::collections::HashMap;
+use ureq::Agent;
+
+/// GeoJSON wrapper around every api.weather.gov response
+#[derive(Deserialize, Debug)]
+struct Feature<T> {
+    properties: T,
+}
+
+/// Metadata of a location: its forecast office grid, zones and time zone
+#[derive(Deserialize, Debug)]
+#[serde(rename_all = "camelCase")]
+struct Point {
+    forecast: String,
+    forecast_hourly: String,
+    forecast_grid_data: String,
+    forecast_zone: String,
+    county: Option<String>,
+    time_zone: String,
+}
+
+#[derive(Deserialize, Debug)]
+struct QuantitativeValue {
+    value: Option<f32>,
+}
+
+/// One entry of the 12-hourly narrative or the hourly forecast
+#[derive(Deserialize, Debug)]
+#[serde(rename_all = "camelCase")]
+struct NwsPeriod {
+    /// e.g. "Tonight" or "Thursday"
+    name: String,
+    start_time: String,
+    is_daytime: bool,
+    temperature: f32,
+    probability_of_precipitation: QuantitativeValue,
+    icon: String,
+    short_forecast: String,
+    #[serde(default)]
+    detailed_forecast: String,
+}
+
+#[derive(Deserialize, Debug)]
+struct Periods {
+    periods: Vec<NwsPeriod>,
+}
+
+#[derive(Deserialize, Debug)]
+#[serde(rename_all = "camelCase")]
+struct SeriesValue {
+    /// Start and ISO 8601 duration, e.g. "2026-10-18T14:00:00+00:00/PT3H"
+    valid_time: String,
+    value: Option<f32>,
+}
+
+#[derive(Deserialize, Debug, Default)]
+struct Series {
+    values: Vec<SeriesValue>,
+}
+
+/// The raw gridpoint series the hourly forecast lacks; always in °C, km/h, % and mm
+#[derive(Deserialize, Debug)]
+#[serde(rename_all = "camelCase")]
+struct GridData {
+    apparent_temperature: Series,
+    wind_gust: Series,
+    #[serde(default)]
+    wind_speed: Series,
+    #[serde(default)]
+    relative_humidity: Series,
+    /// Totals over each value's duration, usually 6 hours
+    #[serde(default)]
+    quantitative_precipitation: Series,
+}
+
+#[derive(Deserialize, Debug)]
+#[serde(rename_all = "camelCase")]
+struct Alert {
+    event: String,
+    headline: Option<String>,
+    severity: String,
+    area_desc: String,
+    onset: Option<String>,
+    effective: Option<String>,
+    expires: Option<String>,
+    /// When the hazard ends, which can be later than the message expires
+    ends: Option<String>,
+    status: String,
+    message_type: String,
+}
+
+#[derive(Deserialize, Debug)]
+struct Alerts {
+    features: Vec<Feature<Alert>>,
+}
+
+fn get<T: DeserializeOwned>(agent: &Agent, url: &str) -> Result<T, ProviderError> {
+    Ok(agent
+        .get(url)
+        .header("Accept", "application/geo+json")
+        .call()?
+        .body_mut()
+        .read_json::<T>()?)
+}
+
+/// Hours covered by an ISO 8601 duration such as "PT3H" or "P1DT6H"
+fn duration_hours(duration: &str) -> i64 {
+    let mut hours = 0;
+    let mut number = 0;
+    for c in duration.chars() {
+        match c {
+            '0'..='9' => number = number * 10 + i64::from(c as u8 - b'0'),
+            'D' => hours += number * 24,
+            'H' => hours += number,
+            _ => {}
+        }
+        if !c.is_ascii_digit() {
+            number = 0;
+        }
+    }
+    hours.max(1)
+}
+
+/// Expands a gridpoint series into one value per hour, keyed by the hour's Unix timestamp
+fn hourly_series(series: &Series) -> HashMap<i64, f32> {
+    expand(series, false)
+}
+
+/// Like `hourly_series`, but splits totals such as precipitation evenly across their hours
+fn hourly_amounts(series: &Series) -> HashMap<i64, f32> {
+    expand(series, true)
+}
+
+fn expand(series: &Series, split: bool) -> HashMap<i64, f32> {
+    let mut hours = HashMap::new();
+    for entry in &series.values {
+        let Some(value) = entry.value else {
+            continue;
+        };
+        let Some((start, duration)) = entry.valid_time.split_once('/') else {
+            continue;
+        };
+        let Ok(start) = DateTime::parse_from_rfc3339(start) else {
+            continue;
+        };
+        let duration = duration_hours(duration);
+        let value = if split {
+            value / duration as f32
+        } else {
+            value
+        };
+        for hour in 0..duration {
+            hours.insert(start.timestamp() + hour * 3600, value);
+        }
+    }
+    hours
+}
+
+/// Maps the condition in an icon URL such as ".../icons/land/day/tsra_sct,40?size=medium" onto
+/// the closest WMO code; the first condition wins when the icon shows two
+fn weather_code(icon: &str) -> i32 {
+    let path = icon.split('?').next().unwrap_or_default();
+    let condition = ["/day/", "/night/"]
+        .iter()
+        .find_map(|part| path.split_once(part))
+        .and_then(|(_, rest)| rest.split(['/', ',']).next())
+        .unwrap_or_default();
+
+    match condition.trim_start_matches("wind_") {
+        "skc" | "hot" | "cold" => 0,
+        "few" => 1,
+        "sct" => 2,
+        "bkn" | "ovc" => 3,
+        "fog" | "haze" | "smoke" | "dust" => 45,
+        "rain" => 63,
+        "rain_showers" | "rain_showers_hi" => 80,
+        "fzra" | "rain_fzra" | "snow_fzra" => 67,
+        "snow" | "rain_snow" => 73,
+        "blizzard" => 75,
+        "sleet" | "rain_sleet" | "snow_sleet" => 77,
+        "tsra" | "tsra_sct" | "tsra_hi" => 95,
+        "tornado" | "hurricane" | "tropical_storm" => 99,
+        _ => 3,
+    }
+}
+
+/// "YYYY-MM-DDTHH:MM" part of an ISO 8601 time, which NWS gives in the location's offset
+fn local(time: &str) -> String {
+    time.get(..16).unwrap_or(time).to_string()
+}
+
+/// The US National Weather Service, with forecaster-written narratives and zone alerts
+/// Using this API: <https://api.weather.gov>
+pub struct Nws;
+
+impl Nws {
+    fn point(&self, agent: &Agent, config: &Config) -> Result<Point, ProviderError> {
+        let location = config.location();
+        // api.weather.gov redirects coordinates with more than four decimals
+        let url = format!(
+            "{}/points/{:.4},{:.4}",
+            config.endpoints.nws, location.latitude, location.longitude
+        );
+        match get::<Feature<Point>>(agent, &url) {
+            Err(ProviderError::Http(ureq::Error::StatusCode(404))) => {
+                Err(ProviderError::Unsupported(format!(
+                    "the National Weather Service has no forecast for {}; it only covers the United States",
+                    location.name
+                )))
+            }
+            point => Ok(point?.properties),
+        }
+    }
+}
+
+impl Provider for Nws {
+    fn fetch(&self, agent: &Agent, config: &Config) -> Result<Forecast, ProviderError> {
+        let units = &config.units;
+        let point = self.point(agent, config)?;
+        let narrative: Periods =
+            get::<Feature<Periods>>(agent, &format!("{}?units=si", point.forecast))?.properties;
+        let hourly: Periods =
+            get::<Feature<Periods>>(agent, &format!("{}?units=si", point.forecast_hourly))?
+                .properties;
+        let grid: GridData = get::<Feature<GridData>>(agent, &point.forecast_grid_data)?.properties;
+        let apparent = hourly_series(&grid.apparent_temperature);
+        let gusts = hourly_series(&grid.wind_gust);
+        let wind_speeds = hourly_series(&grid.wind_speed);
+        let humidities = hourly_series(&grid.relative_humidity);
+        let precipitation = hourly_amounts(&grid.quantitative_precipitation);
+
+        let mut hours: Vec<Hour> = Vec::new();
+        let mut utc_offset_seconds = 0;
+        for period in &hourly.periods {
+            let Ok(start) = DateTime::parse_from_rfc3339(&period.start_time) else {
+                continue;
+            };
+            if hours.is_empty() {
+                utc_offset_seconds = start.offset().local_minus_utc();
+            }
+            let temperature = units.temperature.convert_celsius(period.temperature);
+            hours.push(Hour {
+                datetime: local(&period.start_time),
+                temperature,
+                apparent_temperature: apparent
+                    .get(&start.timestamp())
+                    .map_or(temperature, |celsius| {
+                        units.temperature.convert_celsius(*celsius)
+                    }),
+                weather_code: weather_code(&period.icon),
+                weather: period.short_forecast.clone(),
+                precipitation_probability: period
+                    .probability_of_precipitation
+                    .value
+                    .map(|probability| probability as i32),
+                precipitation: precipitation
+                    .get(&start.timestamp())
+                    .map_or(0.0, |mm| units.precipitation.convert_mm(*mm)),
+                wind_speed: wind_speeds
+                    .get(&start.timestamp())
+                    .map_or(0.0, |kmh| units.wind_speed.convert_kmh(*kmh)),
+                wind_gusts: gusts
+                    .get(&start.timestamp())
+                    .map(|kmh| units.wind_speed.convert_kmh(*kmh)),
+                humidity: humidities
+                    .get(&start.timestamp())
+                    .map(|humidity| humidity.round() as i32),
+                temperature_range: None,
+            });
+        }
+        let Some(first_hour) = hours.first() else {
+            return Err(ProviderError::Incomplete(String::from(
+                "the hourly forecast is empty",
+            )));
+        };
+
+        // The narrative comes in day and night halves; both belong to the date they start on
+        let mut days: Vec<(String, Vec<&NwsPeriod>)> = Vec::new();
+        for period in &narrative.periods {
+            let date = period.start_time.get(..10).unwrap_or_default().to_string();
+            match days.last_mut() {
+                Some((last, halves)) if *last == date => halves.push(period),
+                _ => days.push((date, vec![period])),
+            }
+        }
+        days.truncate(config.forecast_days as usize);
+
+        let mut periods: Vec<Period> = Vec::new();
+        for (date, halves) in &days {
+            let day = halves.iter().copied().find(|half| half.is_daytime);
+            let night = halves.iter().copied().rev().find(|half| !half.is_daytime);
+            let main = day.or(night).expect("every date has at least one half");
+            let same_day: Vec<&Hour> = hours
+                .iter()
+                .filter(|hour| hour.datetime.starts_with(date.as_str()))
+                .collect();
+
+            let celsius = |half: &NwsPeriod| units.temperature.convert_celsius(half.temperature);
+            let temperature_max = day
+                .map(celsius)
+                .or_else(|| max(same_day.iter().map(|hour| hour.temperature)))
+                .unwrap_or_else(|| celsius(main));
+            // The last day is often only partly covered by the hourly forecast
+            let temperature_min = night
+                .map(celsius)
+                .or_else(|| min(same_day.iter().map(|hour| hour.temperature)))
+                .unwrap_or_else(|| celsius(main))
+                .min(temperature_max);
+
+            periods.push(Period {
+                date: date.clone(),
+                weather_code: weather_code(&main.icon),
+                weather: main.short_forecast.clone(),
+                temperature_max,
+                temperature_min,
+                apparent_temperature_max: max(same_day.iter().map(|h| h.apparent_temperature))
+                    .unwrap_or(temperature_max),
+                apparent_temperature_min: min(same_day.iter().map(|h| h.apparent_temperature))
+                    .unwrap_or(temperature_min),
+                precipitation_probability: max(halves
+                    .iter()
+                    .filter_map(|half| half.probability_of_precipitation.value))
+                .map(|probability| probability as i32),
+                // NWS publishes no sunrise or sunset times
+                sunrise: String::new(),
+                sunset: String::new(),
+                wind_gusts_max: max(same_day.iter().filter_map(|hour| hour.wind_gusts)),
+                narrative: halves
+                    .iter()
+                    .map(|half| format!("{}: {}", half.name, half.detailed_forecast))
+                    .collect::<Vec<_>>()
+                    .join("\n"),
+                disagreement: None,
+            });
+        }
+
+        // NWS has no "current" forecast; the hour in progress stands in for it
+        let current = Current {
+            time: first_hour.datetime.clone(),
+            temperature: first_hour.temperature,
+            apparent_temperature: first_hour.apparent_temperature,
+            weather_code: first_hour.weather_code,
+            weather: first_hour.weather.clone(),
+            station: None,
+            source: Source::Model,
+        };
+
+        if let Some((last_date, _)) = days.last() {
+            hours.retain(|hour| hour.datetime.get(..10).unwrap_or_default() <= last_date.as_str());
+        }
+
+        Ok(Forecast {
+            timezone: point.time_zone,
+            utc_offset_seconds,
+            periods,
+            current,
+            hourly: hours,
+        })
+    }
+
+    /// Active alerts for the location's forecast zone and county
+    fn warnings(&self, agent: &Agent, config: &Config) -> Result<Vec<Warning>, ProviderError> {
+        let point = self.point(agent, config)?;
+        let zones: Vec<&str> = [Some(&point.forecast_zone), point.county.as_ref()]
+            .into_iter()
+            .flatten()
+            .filter_map(|url| url.rsplit('/').next())
+            .collect();
+        let url = format!(
+            "{}/alerts/active?zone={}",
+            config.endpoints.nws,
+            zones.join(",")
+        );
+        let alerts: Alerts = get(agent, &url)?;
+
+        let time = |time: &Option<String>| {
+            time.as_deref()
+                .and_then(|time| DateTime::parse_from_rfc3339(time).ok())
+        };
+        Ok(alerts
+            .features
+            .into_iter()
+            .map(|feature| feature.properties)
+            .filter(|alert| alert.status == "Actual" && alert.message_type != "Cancel")
+            .map(|alert| Warning {
+                headline: alert
+                    .headline
+                    .clone()
+                    .unwrap_or_else(|| alert.event.clone()),
+                severity: Severity::parse(&alert.severity),
+                area: alert.area_desc.clone(),
+                onset: time(&alert.onset).or_else(|| time(&alert.effective)),
+                expires: time(&alert.ends).or_else(|| time(&alert.expires)),
+                event: alert.event,
+            })
+            .collect())
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn series(json: &str) -> Series {
+        serde_json::from_str(json).unwrap()
+    }
+
+    #[test]
+    fn durations_count_days_and_hours() {
+        assert_eq!(duration_hours("PT1H"), 1);
+        assert_eq!(duration_hours("PT3H"), 3);
+        assert_eq!(duration_hours("P1DT6H"), 30);
+        assert_eq!(duration_hours("P2D"), 48);
+        assert_eq!(duration_hours("PT12H"), 12);
+        // Minutes don't add hours, and nothing shorter than an hour is returned
+        assert_eq!(duration_hours("PT30M"), 1);
+        assert_eq!(duration_hours("PT1H30M"), 1);
+    }
+
+    #[test]
+    fn series_expand_into_hours() {
+        let series = series(
+            r#"{"values": [
+                {"validTime": "2026-10-18T14:00:00+00:00/PT3H", "value": 6.0},
+                {"validTime": "2026-10-18T17:00:00+00:00/PT1H", "value": null},
+                {"validTime": "not a time/PT1H", "value": 1.0},
+                {"validTime": "2026-10-18T18:00:00+00:00/PT2H", "value": 2.0}
+            ]}"#,
+        );
+        let start = DateTime::parse_from_rfc3339("2026-10-18T14:00:00+00:00")
+            .unwrap()
+            .timestamp();
+        let at = |hours: i64| start + hours * 3600;
+
+        let values = hourly_series(&series);
+        assert_eq!(values.len(), 5);
+        assert_eq!(values[&at(0)], 6.0);
+        assert_eq!(values[&at(2)], 6.0);
+        assert!(!values.contains_key(&at(3)));
+        assert_eq!(values[&at(5)], 2.0);
+
+        let amounts = hourly_amounts(&series);
+        assert_eq!(amounts[&at(1)], 2.0);
+        assert_eq!(amounts[&at(4)], 1.0);
+    }
+
+    #[test]
+    fn icons_map_onto_wmo_codes() {
+        let icon = |path: &str| format!("https://api.weather.gov/icons/land/{}?size=medium", path);
+        assert_eq!(weather_code(&icon("day/skc")), 0);
+        assert_eq!(weather_code(&icon("night/wind_few")), 1);
+        assert_eq!(weather_code(&icon("day/tsra_sct,40")), 95);
+        // The first of two conditions wins
+        assert_eq!(weather_code(&icon("day/rain_showers,30/snow,60")), 80);
+        assert_eq!(weather_code(&icon("day/unknown")), 3);
+        assert_eq!(weather_code(""), 3);
+    }
+}
@@ -0,0 +1,136 @@
+use super::{Provider, ProviderError};
+use crate::config::Config;
+use crate::forecast::{Current, Forecast, Hour, Period, Source};
+use serde::Deserialize;
+use ureq::Agent;
+use urlencoding::encode;
+
+/// Daily forecast data
+#[derive(Deserialize, Debug)]
+struct OpenMeteoTimeAndCode {
+    time: Vec<String>,
+    weather_code: Vec<i32>,
+    temperature_2m_max: Vec<f32>,
+    temperature_2m_min: Vec<f32>,
+    apparent_temperature_max: Vec<f32>,
+    apparent_temperature_min: Vec<f32>,
+    precipitation_probability_mean: Vec<Option<i32>>,
+    sunrise: Vec<Option<String>>,
+    sunset: Vec<Option<String>>,
+    wind_gusts_10m_max: Vec<Option<f32>>,
+}
+
+/// Raw hourly data
+#[derive(Deserialize, Debug)]
+struct OpenMeteoHourlyData {
+    time: Vec<String>,
+    weather_code: Vec<i32>,
+    temperature_2m: Vec<f32>,
+    apparent_temperature: Vec<f32>,
+    precipitation_probability: Vec<Option<i32>>,
+    precipitation: Vec<Option<f32>>,
+    relative_humidity_2m: Vec<Option<i32>>,
+    wind_speed_10m: Vec<Option<f32>>,
+    wind_gusts_10m: Vec<Option<f32>>,
+}
+
+/// Today's weather data
+#[derive(Deserialize, Debug)]
+struct OpenMeteoCurrent {
+    time: String,
+    temperature_2m: f32,
+    apparent_temperature: f32,
+    weather_code: i32,
+}
+
+/// Combination forecast including daily, hourly, and current
+#[derive(Deserialize, Debug)]
+struct OpenMeteoRawForecast {
+    timezone: String,
+    utc_offset_seconds: i32,
+    daily: OpenMeteoTimeAndCode,
+    hourly: OpenMeteoHourlyData,
+    current: OpenMeteoCurrent,
+}
+
+/// Open-Meteo, which needs no API key and already speaks WMO codes and every unit Raijin offers
+/// Using this API: <https://api.open-meteo.com/v1/forecast>
+pub struct OpenMeteo;
+
+impl Provider for OpenMeteo {
+    fn fetch(&self, agent: &Agent, config: &Config) -> Result<Forecast, ProviderError> {
+        let location = config.location();
+
+        let url = format!(
+            "{}?latitude={}&longitude={}&daily=temperature_2m_max,temperature_2m_min,apparent_temperature_max,apparent_temperature_min,weather_code,precipitation_probability_mean,sunrise,sunset,wind_gusts_10m_max&hourly=temperature_2m,apparent_temperature,weather_code,precipitation_probability,precipitation,relative_humidity_2m,wind_speed_10m,wind_gusts_10m&current=temperature_2m,apparent_temperature,weather_code&timezone={}&temperature_unit={}&wind_speed_unit={}&precipitation_unit={}&forecast_days={}",
+            config.endpoints.open_meteo,
+            location.latitude,
+            location.longitude,
+            encode(&location.timezone),
+            config.units.temperature.api_name(),
+            config.units.wind_speed.api_name(),
+            config.units.precipitation.api_name(),
+            config.forecast_days,
+        );
+
+        let json = agent
+            .get(url)
+            .call()?
+            .body_mut()
+            .read_json::<OpenMeteoRawForecast>()?;
+
+        let mut periods: Vec<Period> = Vec::new();
+        for (count, i) in json.daily.time.iter().enumerate() {
+            periods.push(Period {
+                date: i.to_string(),
+                weather_code: json.daily.weather_code[count],
+                weather: String::new(),
+                temperature_max: json.daily.temperature_2m_max[count],
+                temperature_min: json.daily.temperature_2m_min[count],
+                apparent_temperature_max: json.daily.apparent_temperature_max[count],
+                apparent_temperature_min: json.daily.apparent_temperature_min[count],
+                precipitation_probability: json.daily.precipitation_probability_mean[count],
+                sunrise: json.daily.sunrise[count].clone().unwrap_or_default(),
+                sunset: json.daily.sunset[count].clone().unwrap_or_default(),
+                wind_gusts_max: json.daily.wind_gusts_10m_max[count],
+                narrative: String::new(),
+                disagreement: None,
+            });
+        }
+
+        let mut hourly: Vec<Hour> = Vec::new();
+        for (count, i) in json.hourly.time.iter().enumerate() {
+            hourly.push(Hour {
+                datetime: i.to_string(),
+                temperature: json.hourly.temperature_2m[count],
+                apparent_temperature: json.hourly.apparent_temperature[count],
+                weather_code: json.hourly.weather_code[count],
+                weather: String::new(),
+                precipitation_probability: json.hourly.precipitation_probability[count],
+                precipitation: json.hourly.precipitation[count].unwrap_or_default(),
+                wind_speed: json.hourly.wind_speed_10m[count].unwrap_or_default(),
+                wind_gusts: json.hourly.wind_gusts_10m[count],
+                humidity: json.hourly.relative_humidity_2m[count],
+                temperature_range: None,
+            });
+        }
+
+        let current = Current {
+            time: json.current.time,
+            temperature: json.current.temperature_2m,
+            apparent_temperature: json.current.apparent_temperature,
+            weather_code: json.current.weather_code,
+            weather: String::new(),
+            station: None,
+            source: Source::Model,
+        };
+
+        Ok(Forecast {
+            timezone: json.timezone,
+            utc_offset_seconds: json.utc_offset_seconds,
+            periods,
+            current,
+            hourly,
+        })
+    }
+}
@@ -0,0 +1,315 @@
+use crate::config::{Config, Theme};
+use crate::forecast::Forecast;
+use crate::{MoonPhase, degrees, get_day_from_date, percent};
+use ratatui::crossterm::style::{Color, Stylize};
+use ratatui::crossterm::terminal;
+use std::env;
+use std::ffi::OsStr;
+use std::io::{self, IsTerminal};
+
+/// Width of the label column in the `--once` report
+const LABEL_WIDTH: usize = 12;
+
+/// Width used when stdout isn't a terminal
+const FALLBACK_WIDTH: usize = 80;
+
+/// Heading naming the location the forecast is for
+fn print_heading(config: &Config) {
+    let location = config.location();
+    println!(
+        "{} ({}, {})",
+        location.name, location.latitude, location.longitude
+    );
+}
+
+/// Prints the same values as the "Right Now" table
+pub fn print_now(forecast: &Forecast, config: &Config) {
+    print_heading(config);
+    let mut rows = vec![
+        ("Current Temp:", degrees(forecast.current.temperature)),
+        (
+            "Feels Like:",
+            degrees(forecast.current.apparent_temperature),
+        ),
+    ];
+    if let Some(today) = forecast.periods.first() {
+        rows.extend([
+            ("High:", degrees(today.temperature_max)),
+            ("Low:", degrees(today.temperature_min)),
+            ("Weather Summary:", today.weather.clone()),
+            ("Chance of Rain:", percent(today.precipitation_probability)),
+        ]);
+    }
+    for (label, value) in rows {
+        println!("{:<17}{}", label, value);
+    }
+    println!("{:<17}{}", "Source:", forecast.current.source.label());
+    if let Some(station) = &forecast.current.station {
+        println!("{:<17}{}", "Station:", station.describe());
+    }
+}
+
+/// Prints one line per forecast day
+pub fn print_daily(forecast: &Forecast, config: &Config) {
+    print_heading(config);
+    for period in &forecast.periods {
+        println!(
+            "{}  {:<9}  High {:>7}  Low {:>7}  Rain {:>4}  {}",
+            period.date,
+            get_day_from_date(&period.date).unwrap_or_default(),
+            degrees(period.temperature_max),
+            degrees(period.temperature_min),
+            percent(period.precipitation_probability),
+            period.weather
+        );
+        // Narratives, from providers that write them, go below the day they describe
+        for line in period.narrative.lines() {
+            println!("    {}", line);
+        }
+    }
+}
+
+/// Prints one line per forecast hour, with a blank line between days
+pub fn print_hourly(forecast: &Forecast, config: &Config) {
+    print_heading(config);
+    let mut last_date = "";
+    for hour in &forecast.hourly {
+        let (date, time) = hour
+            .datetime
+            .split_once('T')
+            .unwrap_or((&hour.datetime, ""));
+        if date != last_date {
+            println!();
+            println!("{} ({})", date, get_day_from_date(date).unwrap_or_default());
+            last_date = date;
+        }
+        println!(
+            "  {}  {:>7}  {}",
+            time,
+            degrees(hour.temperature),
+            hour.weather
+        );
+    }
+}
+
+/// Decides whether and how wide the `--once` report is drawn
+struct Output {
+    color: bool,
+    width: usize,
+}
+
+impl Output {
+    fn detect() -> Output {
+        let is_terminal = io::stdout().is_terminal();
+        let width = if is_terminal {
+            terminal::size()
+                .ok()
+                .map(|(columns, _)| columns as usize)
+                .filter(|&columns| columns > 0)
+                .unwrap_or(FALLBACK_WIDTH)
+        } else {
+            FALLBACK_WIDTH
+        };
+        Output {
+            color: colored(is_terminal, env::var_os("NO_COLOR").as_deref()),
+            width,
+        }
+    }
+
+    fn paint(&self, text: &str, color: &str) -> String {
+        if self.color {
+            text.with(Color::from(Theme::color(color))).to_string()
+        } else {
+            text.to_string()
+        }
+    }
+
+    fn bold(&self, text: &str) -> String {
+        if self.color {
+            text.bold().to_string()
+        } else {
+            text.to_string()
+        }
+    }
+
+    /// Prints a labelled row, wrapping the value with a hanging indent
+    fn row(&self, label: &str, label_color: &str, value: &str) {
+        for line in self.row_lines(label, label_color, value) {
+            println!("{}", line);
+        }
+    }
+
+    fn row_lines(&self, label: &str, label_color: &str, value: &str) -> Vec<String> {
+        let value_width = self.width.saturating_sub(LABEL_WIDTH).max(10);
+        wrap(value, value_width)
+            .iter()
+            .enumerate()
+            .map(|(i, line)| {
+                let label = if i == 0 { label } else { "" };
+                let padded = format!("{:<width$}", label, width = LABEL_WIDTH);
+                format!("{}{}", self.paint(&padded, label_color), line)
+            })
+            .collect()
+    }
+}
+
+/// Colour only goes to terminals, and never when NO_COLOR is set (<https://no-color.org>)
+fn colored(is_terminal: bool, no_color: Option<&OsStr>) -> bool {
+    is_terminal && no_color.is_none_or(OsStr::is_empty)
+}
+
+/// Number of characters a terminal shows for `text`, ignoring ANSI escape sequences
+fn visible_len(text: &str) -> usize {
+    let mut len = 0;
+    let mut in_escape = false;
+    for c in text.chars() {
+        if in_escape {
+            in_escape = !c.is_ascii_alphabetic();
+        } else if c == '\u{1b}' {
+            in_escape = true;
+        } else {
+            len += 1;
+        }
+    }
+    len
+}
+
+/// Greedy word wrap; words longer than `width` get a line of their own
+fn wrap(text: &str, width: usize) -> Vec<String> {
+    let mut lines: Vec<String> = Vec::new();
+    let mut current = String::new();
+    for word in text.split_whitespace() {
+        let needed = visible_len(&current) + visible_len(word) + 1;
+        if !current.is_empty() && needed > width {
+            lines.push(std::mem::take(&mut current));
+        }
+        if !current.is_empty() {
+            current.push(' ');
+        }
+        current.push_str(word);
+    }
+    lines.push(current);
+    lines
+}
+
+/// Prints a compact summary of current conditions, today, the next 4 days and the moon
+pub fn print_once(forecast: &Forecast, moon_phase: &MoonPhase, config: &Config) {
+    let output = Output::detect();
+    let theme = &config.theme;
+    let location = config.location();
+    let heading = match forecast.periods.first() {
+        Some(today) => format!(
+            "{} \u{2014} {} {}",
+            location.name,
+            get_day_from_date(&today.date).unwrap_or_default(),
+            today.date
+        ),
+        None => location.name.clone(),
+    };
+    println!("{}", output.bold(&heading));
+
+    output.row(
+        "Now",
+        &theme.right_now,
+        &format!(
+            "{} (feels like {}), {}",
+            output.paint(&degrees(forecast.current.temperature), &theme.data),
+            degrees(forecast.current.apparent_temperature),
+            forecast.current.weather
+        ),
+    );
+
+    for (i, period) in forecast.periods.iter().take(5).enumerate() {
+        let label = if i == 0 {
+            String::from("Today")
+        } else {
+            format!(
+                "{} {}",
+                get_day_from_date(&period.date).unwrap_or_default(),
+                period.date.get(5..).unwrap_or_default()
+            )
+        };
+        output.row(
+            &label,
+            if i == 0 {
+                &theme.right_now
+            } else {
+                &theme.forecast
+            },
+            &format!(
+                "{} / {}, {}, {} rain",
+                output.paint(&degrees(period.temperature_max), &theme.data),
+                degrees(period.temperature_min),
+                period.weather,
+                percent(period.precipitation_probability)
+            ),
+        );
+    }
+
+    output.row(
+        "Moon",
+        &theme.moon,
+        &format!("{} ({})", moon_phase.phase, moon_phase.illumination),
+    );
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn rows_wrap_to_the_width_with_a_hanging_indent() {
+        let output = Output {
+            color: false,
+            width: 32,
+        };
+        let lines = output.row_lines(
+            "Today",
+            "white",
+            "16° / 4°, Slight rain showers turning heavy, 80% rain",
+        );
+        assert_eq!(
+            lines,
+            [
+                "Today       16° / 4°, Slight",
+                "            rain showers turning",
+                "            heavy, 80% rain",
+            ]
+        );
+
+        // Narrow terminals still get 10 columns of value, and long words a line of their own
+        assert_eq!(wrap("a thunderstorm b", 5), ["a", "thunderstorm", "b"]);
+        let narrow = Output {
+            color: false,
+            width: 8,
+        };
+        assert_eq!(
+            narrow.row_lines("Now", "white", "12° Overcast"),
+            ["Now         12°", "            Overcast"]
+        );
+        assert_eq!(wrap("", 10), [""]);
+    }
+
+    #[test]
+    fn no_color_keeps_the_output_plain() {
+        assert!(colored(true, None));
+        assert!(colored(true, Some(OsStr::new(""))));
+        assert!(!colored(true, Some(OsStr::new("1"))));
+        assert!(!colored(false, None));
+
+        let plain = Output {
+            color: colored(true, Some(OsStr::new("1"))),
+            width: FALLBACK_WIDTH,
+        };
+        assert_eq!(plain.row_lines("Now", "red", "12°"), ["Now         12°"]);
+        assert_eq!(plain.bold("Berlin"), "Berlin");
+
+        let painted = Output {
+            color: true,
+            width: FALLBACK_WIDTH,
+        };
+        let line = &painted.row_lines("Now", "red", "12°")[0];
+        assert!(line.contains('\u{1b}'));
+        assert_eq!(visible_len(line), "Now         12°".chars().count());
+    }
+}
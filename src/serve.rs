@@ -0,0 +1,298 @@
+use crate::alerts::{self, AlertsReport};
+use crate::config::Config;
+use crate::forecast::Forecast;
+use crate::json::{self, Section};
+use crate::warnings::{self, Severity, Warning};
+use crate::{MoonPhase, fetch_all, station};
+use serde::Serialize;
+use std::io;
+use std::sync::{Arc, Mutex, PoisonError};
+use std::thread;
+use std::time::{Duration, Instant};
+use tiny_http::{Header, Method, Request, Response, Server};
+use ureq::Agent;
+
+/// Endpoints listed in the 404 response
+const ENDPOINTS: [&str; 7] = [
+    "/",
+    "/current",
+    "/daily",
+    "/hourly",
+    "/moon",
+    "/alerts",
+    "/warnings",
+];
+
+/// Everything the server responds with, replaced whole by the refresh thread
+struct Snapshot {
+    forecast: Forecast,
+    moon_phase: MoonPhase,
+    warnings: Vec<Warning>,
+}
+
+/// Latest snapshot, shared between the refresh thread and the request loop
+/// Requests only hold the lock to clone the `Arc`, so a slow client doesn't hold up the refresh
+type Latest = Arc<Mutex<Arc<Snapshot>>>;
+
+/// Served on `/warnings`
+#[derive(Serialize)]
+struct WarningsReport<'a> {
+    location: &'a str,
+    count: usize,
+    warnings: Vec<WarningReport<'a>>,
+}
+
+#[derive(Serialize)]
+struct WarningReport<'a> {
+    event: &'a str,
+    headline: &'a str,
+    severity: Severity,
+    area: &'a str,
+    /// RFC 3339, null when the issuer doesn't say
+    onset: Option<String>,
+    expires: Option<String>,
+}
+
+/// Status code and JSON body answering a request for `path`
+fn route(method: &Method, path: &str, snapshot: &Snapshot, config: &Config) -> (u16, String) {
+    if *method != Method::Get {
+        return (405, String::from("{\"error\":\"only GET is supported\"}"));
+    }
+
+    let report = |section| json::report(&snapshot.forecast, &snapshot.moon_phase, config, section);
+    let location = &config.location().name;
+    let body = match path.trim_end_matches('/') {
+        "" => report(Section::All),
+        "/current" => report(Section::Current),
+        "/daily" => report(Section::Daily),
+        "/hourly" => report(Section::Hourly),
+        "/moon" => report(Section::Moon),
+        "/alerts" => {
+            let alerts = alerts::evaluate(&config.alerts, &snapshot.forecast);
+            to_json(&AlertsReport::new(location, &alerts.windows, &config.units))
+        }
+        "/warnings" => to_json(&WarningsReport {
+            location,
+            count: snapshot.warnings.len(),
+            warnings: snapshot
+                .warnings
+                .iter()
+                .map(|warning| WarningReport {
+                    event: &warning.event,
+                    headline: &warning.headline,
+                    severity: warning.severity,
+                    area: &warning.area,
+                    onset: warning.onset.map(|time| time.to_rfc3339()),
+                    expires: warning.expires.map(|time| time.to_rfc3339()),
+                })
+                .collect(),
+        }),
+        _ => {
+            return (
+                404,
+                format!(
+                    "{{\"error\":\"not found\",\"endpoints\":{}}}",
+                    to_json(&ENDPOINTS)
+                ),
+            );
+        }
+    };
+    (200, body)
+}
+
+fn to_json(value: &impl Serialize) -> String {
+    serde_json::to_string_pretty(value).expect("Response could not be serialized")
+}
+
+fn json_response(status: u16, body: String) -> Response<io::Cursor<Vec<u8>>> {
+    Response::from_string(body)
+        .with_status_code(status)
+        .with_header(Header::from_bytes("Content-Type", "application/json").unwrap())
+        // Lets browser dashboards on other local ports read the data
+        .with_header(Header::from_bytes("Access-Control-Allow-Origin", "*").unwrap())
+}
+
+fn respond(request: Request, latest: &Latest, config: &Config) {
+    let path = request.url().split('?').next().unwrap_or("").to_string();
+    // A refresh thread that panicked leaves the last snapshot, which is still worth serving
+    let snapshot = Arc::clone(&latest.lock().unwrap_or_else(PoisonError::into_inner));
+    let (status, body) = route(request.method(), &path, &snapshot, config);
+
+    // A client hanging up early is its own problem
+    let _ = request.respond(json_response(status, body));
+}
+
+/// The warnings to serve, keeping the previous ones when they can't be fetched at all
+fn refresh_warnings(agent: &Agent, config: &Config, previous: &[Warning]) -> Vec<Warning> {
+    let (warnings, errors) = warnings::fetch(agent, config);
+    for err in &errors {
+        eprintln!("Could not refresh the warnings: {}", err);
+    }
+    if warnings.is_empty() && !errors.is_empty() {
+        previous.to_vec()
+    } else {
+        warnings
+    }
+}
+
+/// Serves the forecast on localhost until the process is killed
+/// The forecast and warnings are refreshed in the background every `refresh_interval` minutes
+pub fn run(
+    agent: Agent,
+    config: Config,
+    weather_codes: serde_json::Value,
+    port: u16,
+    (forecast, moon_phase): (Forecast, MoonPhase),
+) -> io::Result<()> {
+    let server = Server::http(("127.0.0.1", port)).map_err(io::Error::other)?;
+    let latest: Latest = Arc::new(Mutex::new(Arc::new(Snapshot {
+        forecast,
+        moon_phase,
+        warnings: refresh_warnings(&agent, &config, &[]),
+    })));
+
+    let refresh_latest = Arc::clone(&latest);
+    let refresh_config = config.clone();
+    let forecast_interval = Duration::from_secs(config.refresh_interval * 60);
+    // With a personal station, current conditions are re-read every minute and the forecast
+    // comes from the cache in between
+    let interval = if config.station.is_configured() {
+        station::REFRESH_INTERVAL
+    } else {
+        forecast_interval
+    };
+    thread::spawn(move || {
+        let mut warnings_fetched = Instant::now();
+        loop {
+            thread::sleep(interval);
+            let previous = Arc::clone(
+                &refresh_latest
+                    .lock()
+                    .unwrap_or_else(PoisonError::into_inner),
+            );
+            let warnings = if warnings_fetched.elapsed() >= forecast_interval {
+                warnings_fetched = Instant::now();
+                refresh_warnings(&agent, &refresh_config, &previous.warnings)
+            } else {
+                previous.warnings.clone()
+            };
+            let snapshot = match fetch_all(&agent, &refresh_config, &weather_codes) {
+                Ok((forecast, moon_phase)) => Snapshot {
+                    forecast,
+                    moon_phase,
+                    warnings,
+                },
+                Err(err) => {
+                    eprintln!("Could not refresh the forecast: {}", err);
+                    Snapshot {
+                        forecast: previous.forecast.clone(),
+                        moon_phase: previous.moon_phase.clone(),
+                        warnings,
+                    }
+                }
+            };
+            *refresh_latest
+                .lock()
+                .unwrap_or_else(PoisonError::into_inner) = Arc::new(snapshot);
+        }
+    });
+
+    eprintln!("Serving the forecast on http://127.0.0.1:{}", port);
+    for request in server.incoming_requests() {
+        respond(request, &latest, &config);
+    }
+    Ok(())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::config::{AlertCondition, AlertMetric, AlertRule, Location};
+    use crate::forecast::fixtures::{hour, period};
+    use chrono::DateTime;
+
+    fn config() -> Config {
+        let mut config = Config::default();
+        config.locations.push(Location {
+            name: String::from("Berlin"),
+            latitude: 52.52,
+            longitude: 13.41,
+            timezone: String::from("Europe/Berlin"),
+            warning_area: None,
+            geocodes: Vec::new(),
+        });
+        config.alerts.push(AlertRule {
+            name: String::from("Frost"),
+            metric: AlertMetric::Temperature,
+            condition: AlertCondition::Below,
+            threshold: 0.0,
+        });
+        config
+    }
+
+    fn snapshot() -> Snapshot {
+        Snapshot {
+            forecast: Forecast {
+                periods: vec![period("2026-10-18", -2.0, 8.0)],
+                hourly: vec![
+                    hour("2026-10-18T05:00", -1.0),
+                    hour("2026-10-18T06:00", 2.0),
+                ],
+                ..Forecast::default()
+            },
+            moon_phase: MoonPhase::default(),
+            warnings: vec![Warning {
+                event: String::from("Wind"),
+                headline: String::from("Gale warning"),
+                severity: Severity::Moderate,
+                area: String::from("Berlin"),
+                onset: DateTime::parse_from_rfc3339("2026-10-18T12:00:00+02:00").ok(),
+                expires: None,
+            }],
+        }
+    }
+
+    fn get(path: &str) -> (u16, serde_json::Value) {
+        let (status, body) = route(&Method::Get, path, &snapshot(), &config());
+        (status, serde_json::from_str(&body).unwrap())
+    }
+
+    #[test]
+    fn sections_are_served_on_their_paths() {
+        let (status, body) = get("/daily/");
+        assert_eq!(status, 200);
+        assert_eq!(body["daily"][0]["date"], "2026-10-18");
+        assert!(body.get("hourly").is_none());
+
+        let (status, body) = get("/");
+        assert_eq!(status, 200);
+        assert!(body.get("current").is_some() && body.get("moon").is_some());
+    }
+
+    #[test]
+    fn alerts_and_warnings_are_served() {
+        let (status, body) = get("/alerts");
+        assert_eq!(status, 200);
+        assert_eq!(body["location"], "Berlin");
+        assert_eq!(body["count"], 1);
+        assert_eq!(body["alerts"][0]["name"], "Frost");
+        assert_eq!(body["alerts"][0]["start"], "2026-10-18T05:00");
+
+        let (status, body) = get("/warnings");
+        assert_eq!(status, 200);
+        assert_eq!(body["count"], 1);
+        assert_eq!(body["warnings"][0]["severity"], "Moderate");
+        assert_eq!(body["warnings"][0]["onset"], "2026-10-18T12:00:00+02:00");
+        assert!(body["warnings"][0]["expires"].is_null());
+    }
+
+    #[test]
+    fn unknown_paths_and_methods_are_refused() {
+        let (status, body) = get("/radar");
+        assert_eq!(status, 404);
+        assert_eq!(body["endpoints"][6], "/warnings");
+
+        let (status, _) = route(&Method::Post, "/current", &snapshot(), &config());
+        assert_eq!(status, 405);
+    }
+}
@@ -0,0 +1,424 @@
+use crate::cache::fnv1a;
+use crate::config::Config;
+use crate::forecast::{Forecast, Source, Station};
+use crate::provider::apparent_temperature;
+use chrono::{DateTime, FixedOffset, NaiveDateTime, Offset, TimeDelta, Utc};
+use serde::{Deserialize, Serialize};
+use std::collections::HashMap;
+use std::path::PathBuf;
+use std::time::Duration;
+use std::{fmt, fs, io, thread};
+use tiny_http::{Method, Response, Server};
+use ureq::Agent;
+use urlencoding::decode;
+
+/// How often the TUI and `raijin serve` re-read the station; most upload every minute or so
+pub const REFRESH_INTERVAL: Duration = Duration::from_secs(60);
+
+/// What the station measured, in °C, %, km/h and mm/h
+/// Also the format of `station.file` and of the uploads the Ecowitt receiver stores
+#[derive(Serialize, Deserialize, Debug, Clone)]
+#[serde(deny_unknown_fields)]
+pub struct Observation {
+    /// RFC 3339 time of the measurement; without it the file's modification time is used
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub time: Option<String>,
+    pub temperature: f32,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub humidity: Option<f32>,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub wind_speed: Option<f32>,
+    /// Calculated from temperature, humidity and wind if left out
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub apparent_temperature: Option<f32>,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub rain_rate: Option<f32>,
+}
+
+#[derive(Debug)]
+pub enum StationError {
+    Io(PathBuf, io::Error),
+    Http(ureq::Error),
+    Parse(String),
+}
+
+impl fmt::Display for StationError {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        match self {
+            StationError::Io(path, err) => write!(f, "{}: {}", path.display(), err),
+            StationError::Http(err) => write!(f, "{}", err),
+            StationError::Parse(msg) => write!(f, "unreadable observation: {}", msg),
+        }
+    }
+}
+
+fn fahrenheit(value: f32) -> f32 {
+    (value - 32.0) * 5.0 / 9.0
+}
+
+/// Parses the form an Ecowitt gateway POSTs to a "Customized" server in Ecowitt protocol,
+/// e.g. "tempf=54.3&humidity=81&windspeedmph=3.4&rainratein=0.000&dateutc=2026-10-18+14:05:00"
+fn parse_ecowitt(body: &str) -> Result<Observation, StationError> {
+    let fields: HashMap<&str, String> = body
+        .split('&')
+        .filter_map(|pair| pair.split_once('='))
+        .filter_map(|(key, value)| Some((key, decode(&value.replace('+', " ")).ok()?.into_owned())))
+        .collect();
+    let number = |key: &str| {
+        fields
+            .get(key)
+            .and_then(|value| value.trim().parse::<f32>().ok())
+    };
+
+    let temperature = number("tempf")
+        .ok_or_else(|| StationError::Parse(String::from("the upload has no tempf")))?;
+    // Gateways without a time source send "now"
+    let time = fields
+        .get("dateutc")
+        .and_then(|value| NaiveDateTime::parse_from_str(value, "%Y-%m-%d %H:%M:%S").ok())
+        .map_or_else(Utc::now, |time| time.and_utc());
+
+    Ok(Observation {
+        time: Some(time.to_rfc3339()),
+        temperature: fahrenheit(temperature),
+        humidity: number("humidity"),
+        wind_speed: number("windspeedmph").map(|mph| mph * 1.609344),
+        apparent_temperature: None,
+        rain_rate: number("rainratein").map(|inches| inches * 25.4),
+    })
+}
+
+/// A value of a WeeWX JSON report, with the units the report is configured for
+#[derive(Deserialize, Debug)]
+struct WeewxValue {
+    value: serde_json::Value,
+    #[serde(default)]
+    units: String,
+}
+
+impl WeewxValue {
+    /// Templates write numbers, strings or "N/A"
+    fn number(&self) -> Option<f32> {
+        match &self.value {
+            serde_json::Value::Number(number) => number.as_f64().map(|n| n as f32),
+            serde_json::Value::String(text) => text.trim().parse().ok(),
+            _ => None,
+        }
+    }
+}
+
+#[derive(Deserialize, Debug)]
+struct WeewxReport {
+    current: HashMap<String, WeewxValue>,
+}
+
+/// Parses the `current.json` of the weewx-json extension, converting from the report's units
+fn parse_weewx(json: &str) -> Result<Observation, StationError> {
+    let report: WeewxReport =
+        serde_json::from_str(json).map_err(|err| StationError::Parse(err.to_string()))?;
+    let value = |key: &str| {
+        let value = report.current.get(key)?;
+        Some((value.number()?, value.units.to_lowercase()))
+    };
+
+    let temperature = value("temperature")
+        .map(|(value, units)| {
+            if units.contains('f') {
+                fahrenheit(value)
+            } else {
+                value
+            }
+        })
+        .ok_or_else(|| StationError::Parse(String::from("the report has no temperature")))?;
+    let wind_speed = value("wind speed").map(|(value, units)| match units.as_str() {
+        "mph" | "mile_per_hour" => value * 1.609344,
+        "m/s" | "meter_per_second" => value * 3.6,
+        "knots" | "knot" => value * 1.852,
+        _ => value,
+    });
+    let rain_rate = value("rain rate").map(|(value, units)| {
+        if units.starts_with("in") {
+            value * 25.4
+        } else if units.starts_with("cm") {
+            value * 10.0
+        } else {
+            value
+        }
+    });
+
+    Ok(Observation {
+        time: None,
+        temperature,
+        humidity: value("humidity").map(|(value, _)| value),
+        wind_speed,
+        apparent_temperature: None,
+        rain_rate,
+    })
+}
+
+/// Reads a URL or a file, along with when it last changed
+fn read(agent: &Agent, source: &str) -> Result<(String, DateTime<Utc>), StationError> {
+    if source.starts_with("http://") || source.starts_with("https://") {
+        let mut response = agent.get(source).call().map_err(StationError::Http)?;
+        // WeeWX regenerates its reports, so the web server knows how old they are
+        let modified = response
+            .headers()
+            .get("Last-Modified")
+            .and_then(|value| value.to_str().ok())
+            .and_then(|value| DateTime::parse_from_rfc2822(value).ok())
+            .map_or_else(Utc::now, |time| time.to_utc());
+        let body = response
+            .body_mut()
+            .read_to_string()
+            .map_err(StationError::Http)?;
+        Ok((body, modified))
+    } else {
+        let path = PathBuf::from(source);
+        let io_error = |err| StationError::Io(path.clone(), err);
+        let modified = fs::metadata(&path)
+            .and_then(|metadata| metadata.modified())
+            .map_err(io_error)?;
+        let contents = fs::read_to_string(&path).map_err(io_error)?;
+        Ok((contents, modified.into()))
+    }
+}
+
+/// Reads an observation in Raijin's own format
+fn read_file(agent: &Agent, source: &str) -> Result<(Observation, DateTime<Utc>), StationError> {
+    let (contents, modified) = read(agent, source)?;
+    let observation: Observation =
+        serde_json::from_str(&contents).map_err(|err| StationError::Parse(err.to_string()))?;
+    let time = match &observation.time {
+        Some(time) => DateTime::parse_from_rfc3339(time)
+            .map_err(|err| StationError::Parse(format!("time \"{}\": {}", time, err)))?
+            .to_utc(),
+        None => modified,
+    };
+    Ok((observation, time))
+}
+
+/// Where the Ecowitt receiver on `address` keeps the latest upload, for every Raijin process
+/// to read
+/// Keyed by the address like the forecast cache, so configs with their own stations don't
+/// read each other's uploads
+fn uploads_path(address: &str) -> Option<PathBuf> {
+    let dir = dirs::cache_dir()?.join("raijin");
+    Some(dir.join(format!("station-{:016x}.json", fnv1a(address))))
+}
+
+/// Stores an upload, through a temporary file so readers never see half of it
+fn store(address: &str, observation: &Observation) -> io::Result<()> {
+    let Some(path) = uploads_path(address) else {
+        return Ok(());
+    };
+    if let Some(parent) = path.parent() {
+        fs::create_dir_all(parent)?;
+    }
+    let temporary = path.with_extension(format!("json.{}", std::process::id()));
+    fs::write(&temporary, serde_json::to_string(observation)?)?;
+    fs::rename(&temporary, &path)
+}
+
+/// Receives Ecowitt uploads on `station.ecowitt` in the background, for the TUI, `raijin serve`
+/// and `raijin daemon`
+/// The address being taken, most likely by another Raijin storing the same uploads, is reported
+/// but not fatal
+pub fn listen(config: &Config) {
+    let Some(address) = config.station.ecowitt.clone() else {
+        return;
+    };
+    let server = match Server::http(&address) {
+        Ok(server) => server,
+        Err(err) => {
+            eprintln!("Not receiving station uploads on {}: {}", address, err);
+            return;
+        }
+    };
+    thread::spawn(move || {
+        for mut request in server.incoming_requests() {
+            let mut body = String::new();
+            let status = if *request.method() != Method::Post {
+                405
+            } else if request.as_reader().read_to_string(&mut body).is_err() {
+                400
+            } else {
+                match parse_ecowitt(&body) {
+                    Ok(observation) => {
+                        if store(&address, &observation).is_ok() {
+                            200
+                        } else {
+                            500
+                        }
+                    }
+                    Err(_) => 400,
+                }
+            };
+            let _ = request.respond(Response::empty(status));
+        }
+    });
+}
+
+/// The station's latest observation and its time, if a station is configured
+pub fn latest(
+    agent: &Agent,
+    config: &Config,
+) -> Result<Option<(Observation, DateTime<Utc>)>, StationError> {
+    let station = &config.station;
+    if let Some(address) = &station.ecowitt {
+        let Some(path) = uploads_path(address) else {
+            return Ok(None);
+        };
+        match read_file(agent, &path.to_string_lossy()) {
+            // Nothing uploaded yet
+            Err(StationError::Io(_, err)) if err.kind() == io::ErrorKind::NotFound => Ok(None),
+            result => result.map(Some),
+        }
+    } else if let Some(source) = &station.weewx {
+        let (contents, modified) = read(agent, source)?;
+        Ok(Some((parse_weewx(&contents)?, modified)))
+    } else if let Some(path) = &station.file {
+        read_file(agent, path).map(Some)
+    } else {
+        Ok(None)
+    }
+}
+
+/// Replaces the forecast's current conditions with the station's latest observation, unless it's
+/// older than `station.max_age` minutes
+/// A station can't see the sky, so the weather stays the provider's unless the station measures
+/// rain the provider didn't expect
+pub fn apply(agent: &Agent, config: &Config, forecast: &mut Forecast) -> Result<(), StationError> {
+    let Some((observation, time)) = latest(agent, config)? else {
+        return Ok(());
+    };
+    if Utc::now() - time > TimeDelta::minutes(config.station.max_age as i64) {
+        return Ok(());
+    }
+
+    let units = &config.units;
+    let offset = FixedOffset::east_opt(forecast.utc_offset_seconds).unwrap_or(Utc.fix());
+    let current = &mut forecast.current;
+    current.time = time
+        .with_timezone(&offset)
+        .format("%Y-%m-%dT%H:%M")
+        .to_string();
+    current.temperature = units.temperature.convert_celsius(observation.temperature);
+    current.apparent_temperature =
+        units
+            .temperature
+            .convert_celsius(observation.apparent_temperature.unwrap_or_else(|| {
+                apparent_temperature(
+                    observation.temperature,
+                    observation.humidity,
+                    observation.wind_speed.map(|kmh| kmh / 3.6),
+                )
+            }));
+    // Codes below 51 have no precipitation
+    if let Some(rate) = observation.rain_rate
+        && rate > 0.0
+        && current.weather_code < 51
+    {
+        current.weather_code = if rate < 2.5 {
+            61
+        } else if rate < 7.6 {
+            63
+        } else {
+            65
+        };
+        current.weather = String::new();
+    }
+    current.station = config.station.name.as_ref().map(|name| Station {
+        name: name.clone(),
+        distance: None,
+    });
+    current.source = Source::PersonalStation;
+    Ok(())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn close(a: Option<f32>, b: f32) -> bool {
+        a.is_some_and(|a| (a - b).abs() < 0.01)
+    }
+
+    #[test]
+    fn ecowitt_uploads_are_converted_to_metric() {
+        let observation = parse_ecowitt(
+            "PASSKEY=ABC&stationtype=GW1100A&dateutc=2026-10-18+14:05:00&tempf=50.0&humidity=81&windspeedmph=10.0&rainratein=0.100&model=GW1100A",
+        )
+        .unwrap();
+        assert_eq!(
+            observation.time.as_deref(),
+            Some("2026-10-18T14:05:00+00:00")
+        );
+        assert!(close(Some(observation.temperature), 10.0));
+        assert_eq!(observation.humidity, Some(81.0));
+        assert!(close(observation.wind_speed, 16.09));
+        assert!(close(observation.rain_rate, 2.54));
+        assert_eq!(observation.apparent_temperature, None);
+    }
+
+    #[test]
+    fn ecowitt_uploads_need_a_temperature() {
+        assert!(parse_ecowitt("tempf=41.0").unwrap().humidity.is_none());
+        // Gateways without a clock send "now", which is replaced by the time of arrival
+        assert!(
+            parse_ecowitt("tempf=41.0&dateutc=now")
+                .unwrap()
+                .time
+                .is_some()
+        );
+        assert!(matches!(
+            parse_ecowitt("humidity=81&tempf=warm"),
+            Err(StationError::Parse(_))
+        ));
+    }
+
+    #[test]
+    fn weewx_reports_are_converted_from_their_units() {
+        let observation = parse_weewx(
+            r#"{"current": {
+                "temperature": {"value": 50.0, "units": "°F"},
+                "humidity": {"value": "81", "units": "%"},
+                "wind speed": {"value": 10.0, "units": "mph"},
+                "rain rate": {"value": 0.1, "units": "in/h"}
+            }}"#,
+        )
+        .unwrap();
+        assert!(close(Some(observation.temperature), 10.0));
+        assert_eq!(observation.humidity, Some(81.0));
+        assert!(close(observation.wind_speed, 16.09));
+        assert!(close(observation.rain_rate, 2.54));
+        assert_eq!(observation.time, None);
+
+        let metric = parse_weewx(
+            r#"{"current": {
+                "temperature": {"value": 12.5, "units": "°C"},
+                "wind speed": {"value": 2.0, "units": "m/s"},
+                "rain rate": {"value": "N/A", "units": "mm/h"}
+            }}"#,
+        )
+        .unwrap();
+        assert_eq!(metric.temperature, 12.5);
+        assert!(close(metric.wind_speed, 7.2));
+        assert_eq!(metric.rain_rate, None);
+        assert_eq!(metric.humidity, None);
+    }
+
+    #[test]
+    fn weewx_reports_need_a_temperature() {
+        assert!(matches!(
+            parse_weewx(r#"{"current": {"temperature": {"value": "N/A"}}}"#),
+            Err(StationError::Parse(_))
+        ));
+        assert!(matches!(parse_weewx("[]"), Err(StationError::Parse(_))));
+    }
+
+    #[test]
+    fn receivers_keep_their_own_uploads() {
+        assert_eq!(uploads_path("0.0.0.0:8080"), uploads_path("0.0.0.0:8080"));
+        assert_ne!(uploads_path("0.0.0.0:8080"), uploads_path("0.0.0.0:8081"));
+    }
+}
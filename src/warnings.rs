@@ -0,0 +1,547 @@
+use crate::config::{Config, Location, Theme};
+use crate::provider::{self, ProviderError};
+use chrono::{DateTime, FixedOffset, TimeDelta, Utc};
+use ratatui::style::Color;
+use roxmltree::{Document, Node};
+use serde::Serialize;
+use std::path::PathBuf;
+use std::{fmt, fs, io};
+use ureq::Agent;
+
+/// CAP severity, most severe first so sorting puts the worst warning on top
+#[derive(Serialize, Debug, Clone, Copy, PartialEq, Eq, PartialOrd, Ord)]
+pub enum Severity {
+    Extreme,
+    Severe,
+    Moderate,
+    Minor,
+    Unknown,
+}
+
+impl Severity {
+    pub fn parse(value: &str) -> Severity {
+        match value.trim() {
+            "Extreme" => Severity::Extreme,
+            "Severe" => Severity::Severe,
+            "Moderate" => Severity::Moderate,
+            "Minor" => Severity::Minor,
+            _ => Severity::Unknown,
+        }
+    }
+
+    pub fn label(&self) -> &'static str {
+        match self {
+            Severity::Extreme => "EXTREME",
+            Severity::Severe => "SEVERE",
+            Severity::Moderate => "MODERATE",
+            Severity::Minor => "MINOR",
+            Severity::Unknown => "WARNING",
+        }
+    }
+
+    pub fn color(&self, theme: &Theme) -> Color {
+        match self {
+            Severity::Extreme => Theme::color(&theme.warning_extreme),
+            Severity::Severe => Theme::color(&theme.warning_severe),
+            Severity::Moderate => Theme::color(&theme.warning_moderate),
+            Severity::Minor => Theme::color(&theme.warning_minor),
+            Severity::Unknown => Color::Reset,
+        }
+    }
+}
+
+/// An official warning covering the configured location
+#[derive(Debug, Clone)]
+pub struct Warning {
+    /// Kind of hazard, e.g. "Wind"
+    pub event: String,
+    pub headline: String,
+    pub severity: Severity,
+    /// Description of the warned area as given by the issuer
+    pub area: String,
+    pub onset: Option<DateTime<FixedOffset>>,
+    pub expires: Option<DateTime<FixedOffset>>,
+}
+
+impl Warning {
+    /// Not yet expired; warnings without an expiry stay until the feed drops them
+    pub fn is_active(&self, now: DateTime<Utc>) -> bool {
+        self.expires.is_none_or(|expires| expires > now)
+    }
+
+    /// When the warning applies, e.g. "until Mon 18:00" or "Mon 06:00–Tue 00:00"
+    /// Times are shown in the issuer's UTC offset, with the date once the weekday is ambiguous
+    pub fn period(&self, now: DateTime<Utc>) -> String {
+        let time = |t: &DateTime<FixedOffset>| {
+            if t.to_utc() - now < TimeDelta::days(6) {
+                t.format("%a %H:%M").to_string()
+            } else {
+                t.format("%a %-d %b %H:%M").to_string()
+            }
+        };
+        match (self.onset, self.expires) {
+            (Some(onset), Some(expires)) if onset > now => {
+                format!("{}–{}", time(&onset), time(&expires))
+            }
+            (Some(onset), None) if onset > now => format!("from {}", time(&onset)),
+            (_, Some(expires)) => format!("until {}", time(&expires)),
+            (_, None) => String::new(),
+        }
+    }
+}
+
+#[derive(Debug)]
+pub enum WarningsError {
+    Io(PathBuf, io::Error),
+    Http(ureq::Error),
+    Xml(roxmltree::Error),
+    /// The document is neither a CAP alert nor an Atom feed
+    Format(String),
+    /// The forecast provider's own alerts couldn't be fetched
+    Provider(ProviderError),
+}
+
+impl fmt::Display for WarningsError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            WarningsError::Io(path, err) => write!(f, "{}: {}", path.display(), err),
+            WarningsError::Http(err) => write!(f, "{}", err),
+            WarningsError::Xml(err) => write!(f, "invalid XML: {}", err),
+            WarningsError::Format(root) => {
+                write!(f, "expected a CAP alert or an Atom feed, found <{}>", root)
+            }
+            WarningsError::Provider(err) => write!(f, "{}", err),
+        }
+    }
+}
+
+impl std::error::Error for WarningsError {}
+
+/// Where a warning applies, as far as Raijin understands CAP's <area>
+struct Area {
+    description: String,
+    geocodes: Vec<String>,
+    /// Closed rings of (latitude, longitude)
+    polygons: Vec<Vec<(f64, f64)>>,
+    /// Centre (latitude, longitude) and radius in kilometres
+    circles: Vec<(f64, f64, f64)>,
+}
+
+/// Elements are matched by local name, so both `<cap:event>` in Atom feeds and `<event>` in
+/// plain CAP documents are found
+fn children<'a, 'input>(
+    node: Node<'a, 'input>,
+    name: &'static str,
+) -> impl Iterator<Item = Node<'a, 'input>> {
+    node.children()
+        .filter(move |child| child.is_element() && child.tag_name().name() == name)
+}
+
+fn child_text<'a>(node: Node<'a, '_>, name: &'static str) -> Option<&'a str> {
+    children(node, name)
+        .next()
+        .and_then(|child| child.text())
+        .map(str::trim)
+}
+
+fn parse_time(node: Node, name: &'static str) -> Option<DateTime<FixedOffset>> {
+    DateTime::parse_from_rfc3339(child_text(node, name)?).ok()
+}
+
+/// Parses CAP's space separated "latitude,longitude" pairs
+fn parse_points(text: &str) -> Vec<(f64, f64)> {
+    text.split_whitespace()
+        .filter_map(|pair| {
+            let (latitude, longitude) = pair.split_once(',')?;
+            Some((latitude.parse().ok()?, longitude.parse().ok()?))
+        })
+        .collect()
+}
+
+fn parse_area(node: Node) -> Area {
+    Area {
+        description: child_text(node, "areaDesc").unwrap_or_default().to_string(),
+        geocodes: children(node, "geocode")
+            .filter_map(|geocode| child_text(geocode, "value"))
+            .map(str::to_string)
+            .collect(),
+        polygons: children(node, "polygon")
+            .filter_map(|polygon| polygon.text())
+            .map(parse_points)
+            .filter(|points| points.len() >= 3)
+            .collect(),
+        circles: children(node, "circle")
+            .filter_map(|circle| {
+                let (centre, radius) = circle.text()?.trim().split_once(' ')?;
+                let (latitude, longitude) = *parse_points(centre).first()?;
+                Some((latitude, longitude, radius.trim().parse().ok()?))
+            })
+            .collect(),
+    }
+}
+
+/// Ray casting on the polygon's latitude/longitude pairs, good enough for warning areas
+fn polygon_contains(polygon: &[(f64, f64)], latitude: f64, longitude: f64) -> bool {
+    let mut inside = false;
+    let mut previous = polygon[polygon.len() - 1];
+    for &point in polygon {
+        let ((lat_a, lon_a), (lat_b, lon_b)) = (point, previous);
+        if (lat_a > latitude) != (lat_b > latitude)
+            && longitude < (lon_b - lon_a) * (latitude - lat_a) / (lat_b - lat_a) + lon_a
+        {
+            inside = !inside;
+        }
+        previous = point;
+    }
+    inside
+}
+
+/// Great-circle distance in kilometres
+fn distance(a: (f64, f64), b: (f64, f64)) -> f64 {
+    let (lat_a, lon_a) = (a.0.to_radians(), a.1.to_radians());
+    let (lat_b, lon_b) = (b.0.to_radians(), b.1.to_radians());
+    let h = ((lat_b - lat_a) / 2.0).sin().powi(2)
+        + lat_a.cos() * lat_b.cos() * ((lon_b - lon_a) / 2.0).sin().powi(2);
+    2.0 * 6371.0 * h.sqrt().asin()
+}
+
+/// Whether the area covers the location, by geocode, shape or area description
+fn covers(area: &Area, location: &Location) -> bool {
+    let by_geocode = area.geocodes.iter().any(|code| {
+        location
+            .geocodes
+            .iter()
+            .any(|wanted| wanted.eq_ignore_ascii_case(code))
+    });
+    let by_polygon = area
+        .polygons
+        .iter()
+        .any(|polygon| polygon_contains(polygon, location.latitude, location.longitude));
+    let by_circle = area.circles.iter().any(|&(latitude, longitude, radius)| {
+        distance(
+            (latitude, longitude),
+            (location.latitude, location.longitude),
+        ) <= radius
+    });
+    let by_description = location.warning_area.as_ref().is_some_and(|wanted| {
+        area.description
+            .to_lowercase()
+            .contains(&wanted.to_lowercase())
+    });
+    by_geocode || by_polygon || by_circle || by_description
+}
+
+/// Whether the linked CAP document of an Atom entry with this area may cover the location
+/// Only what both sides describe rules it out: a shape, or geocodes or an area name the location
+/// is configured with; entries without any of those have to be fetched
+fn may_cover(area: &Area, location: &Location) -> bool {
+    let by_shape = !area.polygons.is_empty() || !area.circles.is_empty();
+    let by_geocode = !area.geocodes.is_empty() && !location.geocodes.is_empty();
+    let by_description = !area.description.is_empty() && location.warning_area.is_some();
+    covers(area, location) || !(by_shape || by_geocode || by_description)
+}
+
+/// Actual, non-cancelled messages only; tests and exercises are ignored
+fn is_actual(message: Node) -> bool {
+    child_text(message, "status").is_none_or(|status| status == "Actual")
+        && child_text(message, "msgType") != Some("Cancel")
+}
+
+/// Builds a warning from a CAP <info>, or from an Atom <entry> carrying CAP fields
+fn warning(info: Node, area: &Area) -> Warning {
+    let event = child_text(info, "event").unwrap_or_default().to_string();
+    let headline = child_text(info, "headline")
+        .or_else(|| child_text(info, "title"))
+        .unwrap_or(&event)
+        .to_string();
+    Warning {
+        headline,
+        event,
+        severity: Severity::parse(child_text(info, "severity").unwrap_or_default()),
+        area: area.description.clone(),
+        onset: parse_time(info, "onset").or_else(|| parse_time(info, "effective")),
+        expires: parse_time(info, "expires"),
+    }
+}
+
+/// Warnings of a CAP <alert> covering the location
+/// Alerts often repeat their <info> per language; English is preferred, then the first one
+fn parse_alert(alert: Node, location: &Location, warnings: &mut Vec<Warning>) {
+    if !is_actual(alert) {
+        return;
+    }
+    let infos: Vec<Node> = children(alert, "info").collect();
+    let info = infos
+        .iter()
+        .find(|info| child_text(**info, "language").is_some_and(|l| l.starts_with("en")))
+        .or(infos.first());
+    let Some(&info) = info else {
+        return;
+    };
+    if let Some(area) = children(info, "area")
+        .map(parse_area)
+        .find(|area| covers(area, location))
+    {
+        warnings.push(warning(info, &area));
+    }
+}
+
+/// Parses a CAP alert or an Atom feed of them
+/// Returns the matching warnings and the CAP documents linked from entries without CAP fields,
+/// unless the entry's area rules out the location; national feeds list hundreds of them
+fn parse(xml: &str, location: &Location) -> Result<(Vec<Warning>, Vec<String>), WarningsError> {
+    let document = Document::parse(xml).map_err(WarningsError::Xml)?;
+    let root = document.root_element();
+    let mut warnings = Vec::new();
+    let mut linked = Vec::new();
+
+    match root.tag_name().name() {
+        "alert" => parse_alert(root, location, &mut warnings),
+        "feed" => {
+            for entry in children(root, "entry") {
+                if child_text(entry, "event").is_some() {
+                    let area = parse_area(entry);
+                    if is_actual(entry) && covers(&area, location) {
+                        warnings.push(warning(entry, &area));
+                    }
+                } else if let Some(href) = children(entry, "link")
+                    .find(|link| link.attribute("type") == Some("application/cap+xml"))
+                    .and_then(|link| link.attribute("href"))
+                    && may_cover(&parse_area(entry), location)
+                {
+                    linked.push(href.to_string());
+                }
+            }
+        }
+        other => return Err(WarningsError::Format(other.to_string())),
+    }
+    Ok((warnings, linked))
+}
+
+fn read(agent: &Agent, source: &str) -> Result<String, WarningsError> {
+    if source.starts_with("http://") || source.starts_with("https://") {
+        agent
+            .get(source)
+            .call()
+            .and_then(|mut response| response.body_mut().read_to_string())
+            .map_err(WarningsError::Http)
+    } else {
+        fs::read_to_string(source).map_err(|err| WarningsError::Io(PathBuf::from(source), err))
+    }
+}
+
+/// Warnings of `warnings.feed` covering the location, including those of the CAP documents its
+/// entries link to
+fn fetch_feed(
+    agent: &Agent,
+    feed: &str,
+    location: &Location,
+) -> Result<Vec<Warning>, WarningsError> {
+    let (mut warnings, linked) = parse(&read(agent, feed)?, location)?;
+
+    // Entries that only link to their CAP document; one broken link shouldn't hide the rest
+    for source in linked {
+        if let Ok((linked_warnings, _)) = read(agent, &source).and_then(|xml| parse(&xml, location))
+        {
+            warnings.extend(linked_warnings);
+        }
+    }
+    Ok(warnings)
+}
+
+/// Fetches the provider's own alerts and `warnings.feed`, and returns the unexpired warnings
+/// covering the configured location, most severe first
+/// Either source failing doesn't hide the other's warnings; the failures are returned alongside
+pub fn fetch(agent: &Agent, config: &Config) -> (Vec<Warning>, Vec<WarningsError>) {
+    let mut warnings = Vec::new();
+    let mut errors = Vec::new();
+    match provider::from_config(config).warnings(agent, config) {
+        Ok(provider_warnings) => warnings.extend(provider_warnings),
+        Err(err) => errors.push(WarningsError::Provider(err)),
+    }
+    if let Some(feed) = &config.warnings.feed {
+        match fetch_feed(agent, feed, config.location()) {
+            Ok(feed_warnings) => warnings.extend(feed_warnings),
+            Err(err) => errors.push(err),
+        }
+    }
+
+    let now = Utc::now();
+    warnings.retain(|warning| warning.is_active(now));
+    warnings.sort_by_key(|warning| (warning.severity, warning.onset));
+    (warnings, errors)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn berlin() -> Location {
+        Location {
+            name: String::from("Berlin"),
+            latitude: 52.52,
+            longitude: 13.41,
+            timezone: String::from("Europe/Berlin"),
+            warning_area: None,
+            geocodes: vec![String::from("DE300")],
+        }
+    }
+
+    const ALERT: &str = r#"<?xml version="1.0" encoding="UTF-8"?>
+<alert xmlns="urn:oasis:names:tc:emergency:cap:1.2">
+  <status>Actual</status>
+  <msgType>Alert</msgType>
+  <info>
+    <language>de-DE</language>
+    <event>STURMBÖEN</event>
+    <severity>Moderate</severity>
+    <area><areaDesc>Berlin</areaDesc><polygon>52.3,13.0 52.7,13.0 52.7,13.8 52.3,13.8 52.3,13.0</polygon></area>
+  </info>
+  <info>
+    <language>en-GB</language>
+    <event>gale-force gusts</event>
+    <headline>Official WARNING of GALE-FORCE GUSTS</headline>
+    <severity>Severe</severity>
+    <onset>2026-10-18T14:00:00+02:00</onset>
+    <expires>2026-10-19T02:00:00+02:00</expires>
+    <area><areaDesc>Berlin</areaDesc><polygon>52.3,13.0 52.7,13.0 52.7,13.8 52.3,13.8 52.3,13.0</polygon></area>
+  </info>
+</alert>"#;
+
+    #[test]
+    fn polygons_contain_points_inside_only() {
+        let square = [(52.0, 13.0), (53.0, 13.0), (53.0, 14.0), (52.0, 14.0)];
+        assert!(polygon_contains(&square, 52.52, 13.41));
+        assert!(!polygon_contains(&square, 51.9, 13.41));
+        assert!(!polygon_contains(&square, 52.52, 14.1));
+
+        // A "U" open to the north; its notch is outside
+        let u = [
+            (52.0, 13.0),
+            (52.0, 14.0),
+            (53.0, 14.0),
+            (53.0, 13.7),
+            (52.3, 13.7),
+            (52.3, 13.3),
+            (53.0, 13.3),
+            (53.0, 13.0),
+        ];
+        assert!(polygon_contains(&u, 52.5, 13.1));
+        assert!(polygon_contains(&u, 52.1, 13.5));
+        assert!(!polygon_contains(&u, 52.5, 13.5));
+    }
+
+    #[test]
+    fn alerts_prefer_english_and_match_by_polygon() {
+        let (warnings, linked) = parse(ALERT, &berlin()).unwrap();
+        assert!(linked.is_empty());
+        assert_eq!(warnings.len(), 1);
+        let warning = &warnings[0];
+        assert_eq!(warning.event, "gale-force gusts");
+        assert_eq!(warning.headline, "Official WARNING of GALE-FORCE GUSTS");
+        assert_eq!(warning.severity, Severity::Severe);
+        assert_eq!(warning.area, "Berlin");
+        assert_eq!(
+            warning.expires,
+            DateTime::parse_from_rfc3339("2026-10-19T02:00:00+02:00").ok()
+        );
+
+        let hamburg = Location {
+            latitude: 53.55,
+            longitude: 9.99,
+            geocodes: Vec::new(),
+            ..berlin()
+        };
+        assert!(parse(ALERT, &hamburg).unwrap().0.is_empty());
+    }
+
+    #[test]
+    fn test_messages_are_ignored() {
+        let exercise = ALERT.replace("<status>Actual</status>", "<status>Exercise</status>");
+        assert!(parse(&exercise, &berlin()).unwrap().0.is_empty());
+    }
+
+    #[test]
+    fn feeds_match_by_geocode_and_collect_links() {
+        let feed = r#"<?xml version="1.0" encoding="UTF-8"?>
+<feed xmlns="http://www.w3.org/2005/Atom" xmlns:cap="urn:oasis:names:tc:emergency:cap:1.2">
+  <entry>
+    <title>Frost warning</title>
+    <cap:event>Frost</cap:event>
+    <cap:severity>Minor</cap:severity>
+    <cap:areaDesc>Berlin</cap:areaDesc>
+    <cap:geocode><valueName>EMMA_ID</valueName><value>de300</value></cap:geocode>
+  </entry>
+  <entry>
+    <cap:event>Fog</cap:event>
+    <cap:areaDesc>Hamburg</cap:areaDesc>
+    <cap:geocode><valueName>EMMA_ID</valueName><value>DE600</value></cap:geocode>
+  </entry>
+  <entry>
+    <title>Linked alert</title>
+    <link type="application/cap+xml" href="https://example.org/alert.xml"/>
+  </entry>
+  <entry>
+    <title>Linked alert for Berlin</title>
+    <cap:geocode><valueName>EMMA_ID</valueName><value>DE300</value></cap:geocode>
+    <link type="application/cap+xml" href="https://example.org/berlin.xml"/>
+  </entry>
+  <entry>
+    <title>Linked alert for Hamburg</title>
+    <cap:areaDesc>Hamburg</cap:areaDesc>
+    <cap:geocode><valueName>EMMA_ID</valueName><value>DE600</value></cap:geocode>
+    <link type="application/cap+xml" href="https://example.org/hamburg.xml"/>
+  </entry>
+  <entry>
+    <title>Linked alert far away</title>
+    <cap:polygon>40.0,-75.0 41.0,-75.0 41.0,-74.0 40.0,-75.0</cap:polygon>
+    <link type="application/cap+xml" href="https://example.org/elsewhere.xml"/>
+  </entry>
+</feed>"#;
+        let (warnings, linked) = parse(feed, &berlin()).unwrap();
+        assert_eq!(warnings.len(), 1);
+        assert_eq!(warnings[0].event, "Frost");
+        assert_eq!(warnings[0].headline, "Frost warning");
+        assert_eq!(warnings[0].severity, Severity::Minor);
+        // Linked documents are only fetched if the entry doesn't rule Berlin out
+        assert_eq!(
+            linked,
+            [
+                "https://example.org/alert.xml",
+                "https://example.org/berlin.xml"
+            ]
+        );
+    }
+
+    #[test]
+    fn other_documents_are_rejected() {
+        assert!(matches!(
+            parse("<rss/>", &berlin()),
+            Err(WarningsError::Format(root)) if root == "rss"
+        ));
+        assert!(matches!(
+            parse("not xml", &berlin()),
+            Err(WarningsError::Xml(_))
+        ));
+    }
+
+    #[test]
+    fn feed_warnings_survive_a_failing_provider() {
+        let path = std::env::temp_dir().join(format!("raijin-{}-alert.xml", std::process::id()));
+        let alert = ALERT.replace("<expires>2026-10-19T02:00:00+02:00</expires>", "");
+        fs::write(&path, alert).unwrap();
+        let mut config = Config {
+            provider: crate::config::ProviderKind::Nws,
+            ..Config::default()
+        };
+        config.locations.push(berlin());
+        config.endpoints.nws = String::from("http://127.0.0.1:9");
+        config.warnings.feed = Some(path.to_string_lossy().into_owned());
+
+        let (warnings, errors) = fetch(&Agent::new_with_defaults(), &config);
+        fs::remove_file(&path).unwrap();
+        assert_eq!(warnings.len(), 1);
+        assert_eq!(warnings[0].event, "gale-force gusts");
+        assert!(matches!(errors[..], [WarningsError::Provider(_)]));
+    }
+}